@@ -1,30 +1,109 @@
-use egui::{Pos2, Rect, Rounding, Vec2};
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::system::battery::BatteryInfo;
-use crate::system::gpu::GpuInfo;
-use crate::system::info::SystemInfo;
-use crate::system::process::ProcessView;
+use egui::{Pos2, Rect, Rounding, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::keymap::{Action, Keymap};
+use crate::system::alerts::{self, AlertEngine, AlertRule, Severity};
+use crate::system::process::{ProcFiles, ProcessInfo, ProcessView};
+use crate::system::recording::Player;
+use crate::system::sampler::{Sample, Sampler};
+use crate::system::signal::ProcessAction;
 use crate::theme;
 use crate::widgets::elbow::{ElbowCorner, LcarsElbow};
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum View {
     Dashboard,
     Processes,
     Battery,
     Gpu,
+    Sensors,
+    /// Detail for one PID; opened from the process table, never saved
+    #[serde(skip)]
+    Process(u32),
+}
+
+/// Where samples come from: the live collectors or a `--replay` recording.
+pub enum Source {
+    Live(Sampler),
+    Replay(Player),
+}
+
+impl Source {
+    fn poll(&mut self) -> bool {
+        match self {
+            Source::Live(sampler) => sampler.poll(),
+            Source::Replay(player) => player.poll(),
+        }
+    }
+
+    fn latest(&self) -> &Arc<Sample> {
+        match self {
+            Source::Live(sampler) => sampler.latest(),
+            Source::Replay(player) => player.latest(),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        match self {
+            Source::Live(sampler) => sampler.is_paused(),
+            Source::Replay(player) => player.is_paused(),
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        match self {
+            Source::Live(sampler) => sampler.set_paused(!sampler.is_paused()),
+            Source::Replay(player) => player.toggle_pause(),
+        }
+    }
+
+    /// Exactly one more sample, then stay paused.
+    fn step(&mut self) {
+        match self {
+            Source::Live(sampler) => sampler.step(),
+            Source::Replay(player) => {
+                if !player.is_paused() {
+                    player.toggle_pause();
+                }
+                player.seek(player.position() + 1);
+            }
+        }
+    }
+
+    fn set_proc_files(&self, files: ProcFiles) {
+        if let Source::Live(sampler) = self {
+            sampler.set_proc_files(files);
+        }
+    }
+
+    /// A recording only holds whichever process was inspected while recording.
+    fn set_focus(&self, pid: Option<u32>) {
+        if let Source::Live(sampler) = self {
+            sampler.set_focus(pid);
+        }
+    }
 }
 
 pub struct LcarsApp {
-    sys_info: SystemInfo,
+    source: Source,
     process_view: ProcessView,
-    battery_info: BatteryInfo,
-    gpu_info: GpuInfo,
+    selected_gpu: Option<String>,
     current_view: View,
+    alerts: AlertEngine,
+    notify: bool,
+    keymap: Keymap,
+    show_help: bool,
+    config: Config,
+    config_path: Option<PathBuf>,
 }
 
 impl LcarsApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, source: Source, config: Config, config_path: Option<PathBuf>) -> Self {
         let mut fonts = egui::FontDefinitions::default();
         fonts.font_data.insert(
             "helvetica_uc".to_owned(),
@@ -38,17 +117,126 @@ impl LcarsApp {
             .insert(0, "helvetica_uc".to_owned());
         cc.egui_ctx.set_fonts(fonts);
 
+        let replay = matches!(source, Source::Replay(_));
         Self {
-            sys_info: SystemInfo::new(),
-            process_view: ProcessView::default(),
-            battery_info: BatteryInfo::new(),
-            gpu_info: GpuInfo::new(),
-            current_view: View::Dashboard,
+            source,
+            process_view: ProcessView {
+                tree: config.processes.tree,
+                columns: config.processes.columns.clone(),
+                widths: config.processes.widths.clone(),
+                read_only: replay,
+                ..ProcessView::new(config.processes.sort, config.processes.order, config.processes.limit)
+            },
+            selected_gpu: None,
+            current_view: config.default_view,
+            alerts: AlertEngine::new(AlertRule::defaults()),
+            notify: false,
+            // Already checked by Config::validate
+            keymap: Keymap::new(&config.keys).unwrap_or_default(),
+            show_help: false,
+            config,
+            config_path,
+        }
+    }
+
+    pub fn with_alerts(mut self, rules: Vec<AlertRule>, notify: bool) -> Self {
+        self.alerts = AlertEngine::new(rules);
+        self.notify = notify;
+        self
+    }
+
+    /// Called once per new sample, live or replayed.
+    fn on_sample(&mut self) {
+        match &self.source {
+            Source::Live(sampler) => {
+                let started = self.alerts.evaluate(sampler.latest());
+                if self.notify {
+                    started.iter().for_each(alerts::notify_desktop);
+                }
+            }
+            // Replayed alerts only drive the chrome; notifying about the past helps no one
+            Source::Replay(player) => {
+                self.alerts.evaluate_at(player.latest(), player.clock());
+            }
         }
     }
 
-    fn stardate() -> String {
-        let now = chrono::Local::now();
+    fn on_action(&mut self, ctx: &egui::Context, action: Action, procs: &[ProcessInfo]) {
+        let in_table = self.current_view == View::Processes;
+        let pv = &mut self.process_view;
+        match action {
+            Action::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            Action::Help => self.show_help = !self.show_help,
+            Action::Dashboard => self.current_view = View::Dashboard,
+            Action::Processes => self.current_view = View::Processes,
+            Action::Battery => self.current_view = View::Battery,
+            Action::Gpu => self.current_view = View::Gpu,
+            Action::Sensors => self.current_view = View::Sensors,
+            Action::Up if in_table => pv.move_selection(procs, -1),
+            Action::Down if in_table => pv.move_selection(procs, 1),
+            Action::Expand if in_table => {
+                if let Some(p) = pv.selected(procs) {
+                    pv.toggle_expanded(p.key());
+                }
+            }
+            Action::Details if in_table => {
+                if let Some(p) = pv.selected(procs) {
+                    self.current_view = View::Process(p.pid);
+                }
+            }
+            Action::Back => {
+                if self.show_help {
+                    self.show_help = false;
+                } else if let View::Process(_) = self.current_view {
+                    self.current_view = View::Processes;
+                } else if pv.pending_action.is_some() {
+                    pv.cancel_action();
+                } else {
+                    pv.selected_pid = None;
+                }
+            }
+            Action::Search => {
+                self.current_view = View::Processes;
+                crate::views::processes::focus_filter(ctx);
+            }
+            Action::CycleSort if in_table => pv.cycle_sort(),
+            Action::Signal if in_table && !pv.read_only => {
+                if let Some(p) = pv.selected(procs) {
+                    pv.request_action(p.pid, p.name.clone(), ProcessAction::Terminate, procs);
+                }
+            }
+            Action::Pause => self.source.toggle_pause(),
+            Action::Step => self.source.step(),
+            _ => {}
+        }
+    }
+
+    /// Write back settings changed from the UI (view, sort, window size).
+    /// The file is only touched when something actually differs.
+    fn save_config(&self, ctx: &egui::Context) {
+        let Some(path) = &self.config_path else { return };
+        let mut config = self.config.clone();
+        config.default_view = match self.current_view {
+            View::Process(_) => View::Processes,
+            view => view,
+        };
+        config.processes.sort = self.process_view.sort_column;
+        config.processes.order = self.process_view.sort_order;
+        config.processes.tree = self.process_view.tree;
+        config.processes.columns = self.process_view.columns.clone();
+        config.processes.widths = self.process_view.widths.clone();
+        if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+            config.window.width = rect.width().round();
+            config.window.height = rect.height().round();
+        }
+        if config != self.config {
+            if let Err(e) = config.save(&self.config, path) {
+                eprintln!("lcars-ops: saving settings: {}", e);
+            }
+        }
+    }
+
+    fn stardate(now: chrono::DateTime<chrono::Local>) -> String {
         let year = now.format("%Y").to_string();
         let day_of_year = now.format("%j").to_string();
         let fraction = now.format("%H").to_string().parse::<f32>().unwrap_or(0.0) / 24.0;
@@ -58,13 +246,56 @@ impl LcarsApp {
 
 impl eframe::App for LcarsApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.sys_info.refresh_if_needed();
-        self.battery_info.refresh_if_needed();
-        self.gpu_info.refresh_if_needed();
-        ctx.request_repaint_after(std::time::Duration::from_millis(500));
+        if self.source.poll() {
+            self.on_sample();
+        }
+        let sample = self.source.latest().clone();
+        for action in self.keymap.pressed(ctx) {
+            self.on_action(ctx, action, sample.sys.processes());
+        }
+        let frozen = if self.source.is_paused() { "   FROZEN" } else { "" };
+        let (now, mode) = match &self.source {
+            Source::Live(_) => (chrono::Local::now(), "SYSTEM MONITOR"),
+            Source::Replay(player) => (player.time(), "PLAYBACK"),
+        };
+
+        // Most severe firing rule drives the red/yellow alert chrome
+        let firing = self.alerts.firing();
+        let alert = firing.first().map(|a| {
+            let time = ctx.input(|i| i.time);
+            let color = match a.rule.severity {
+                Severity::Critical => theme::flash(theme::RED, theme::RED_DIM, time),
+                Severity::Warning => theme::flash(theme::YELLOW, theme::YELLOW_DIM, time),
+            };
+            let mut banner = format!("{}   {}", a.rule.severity.label(), a.rule.name.to_uppercase());
+            if firing.len() > 1 {
+                banner.push_str(&format!("   +{}", firing.len() - 1));
+            }
+            (color, banner)
+        });
+        drop(firing);
+        // Live samples request their own repaint; the alert flash and
+        // replay playback need a timer
+        if alert.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        if let Source::Replay(player) = &self.source {
+            if let Some(next) = player.until_next() {
+                ctx.request_repaint_after(next);
+            }
+        }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::Q)) {
-            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        if let Some(pid) = self.process_view.inspect_pid.take() {
+            self.current_view = View::Process(pid);
+        }
+        self.source.set_focus(match self.current_view {
+            View::Process(pid) => Some(pid),
+            _ => None,
+        });
+        self.source.set_proc_files(self.process_view.proc_files());
+
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.save_config(ctx);
         }
 
         // Set dark background
@@ -90,7 +321,8 @@ impl eframe::App for LcarsApp {
                     Pos2::new(total_rect.min.x + padding, total_rect.min.y + padding),
                     Vec2::new(sidebar_w + elbow_r, header_h + elbow_r),
                 );
-                LcarsElbow::new(theme::ORANGE, ElbowCorner::TopLeft).draw(
+                let alert_color = alert.as_ref().map(|(color, _)| *color);
+                LcarsElbow::new(alert_color.unwrap_or(theme::ORANGE), ElbowCorner::TopLeft).draw(
                     ui,
                     top_elbow_rect,
                     sidebar_w,
@@ -109,10 +341,13 @@ impl eframe::App for LcarsApp {
                     Pos2::new(header_bar_x, total_rect.min.y + padding),
                     Vec2::new(header_main_w, header_h),
                 );
-                ui.painter().rect_filled(header_main_rect, Rounding::ZERO, theme::ORANGE);
+                ui.painter().rect_filled(header_main_rect, Rounding::ZERO, alert_color.unwrap_or(theme::ORANGE));
 
-                // Header text
-                let title_text = format!("LCARS-OPS   SYSTEM MONITOR   SD:{}", Self::stardate());
+                // Header text, replaced by the alert banner while a rule fires
+                let title_text = match &alert {
+                    Some((_, banner)) => format!("{}   SD:{}{}", banner, Self::stardate(now), frozen),
+                    None => format!("LCARS-OPS   {}   SD:{}{}", mode, Self::stardate(now), frozen),
+                };
                 ui.painter().text(
                     header_main_rect.center() + egui::vec2(0.0, 6.0),
                     egui::Align2::CENTER_CENTER,
@@ -140,7 +375,7 @@ impl eframe::App for LcarsApp {
                     ),
                     Vec2::new(sidebar_w + elbow_r, footer_h + elbow_r),
                 );
-                LcarsElbow::new(theme::LAVENDER, ElbowCorner::BottomLeft).draw(
+                LcarsElbow::new(alert_color.unwrap_or(theme::LAVENDER), ElbowCorner::BottomLeft).draw(
                     ui,
                     bottom_elbow_rect,
                     sidebar_w,
@@ -161,14 +396,19 @@ impl eframe::App for LcarsApp {
                 );
                 ui.painter().rect_filled(footer_main_rect, Rounding::ZERO, theme::LAVENDER);
 
-                // Footer text
-                ui.painter().text(
-                    footer_main_rect.center() + egui::vec2(0.0, 6.0),
-                    egui::Align2::CENTER_CENTER,
-                    "UNITED FEDERATION OF PLANETS",
-                    egui::FontId::monospace(40.0),
-                    theme::BLACK,
-                );
+                // Footer text, or the transport controls while replaying
+                match &mut self.source {
+                    Source::Replay(player) => crate::views::replay::show_controls(ui, footer_main_rect, player),
+                    Source::Live(_) => {
+                        ui.painter().text(
+                            footer_main_rect.center() + egui::vec2(0.0, 6.0),
+                            egui::Align2::CENTER_CENTER,
+                            "UNITED FEDERATION OF PLANETS",
+                            egui::FontId::monospace(40.0),
+                            theme::BLACK,
+                        );
+                    }
+                }
 
                 // Footer end cap (rounded right side)
                 let footer_cap_rect = Rect::from_min_size(
@@ -231,7 +471,7 @@ impl eframe::App for LcarsApp {
                     Pos2::new(sidebar_x, y),
                     Vec2::new(sidebar_w, button_h),
                 );
-                let proc_color = if self.current_view == View::Processes {
+                let proc_color = if matches!(self.current_view, View::Processes | View::Process(_)) {
                     theme::ORANGE
                 } else {
                     theme::BLUE
@@ -318,6 +558,36 @@ impl eframe::App for LcarsApp {
                     self.current_view = View::Gpu;
                 }
 
+                y += button_h + theme::BAR_SPACING;
+
+                // Sensors button
+                let sensors_rect = Rect::from_min_size(
+                    Pos2::new(sidebar_x, y),
+                    Vec2::new(sidebar_w, button_h),
+                );
+                let sensors_color = if self.current_view == View::Sensors {
+                    theme::ORANGE
+                } else {
+                    theme::LAVENDER
+                };
+                let sensors_resp = ui.allocate_rect(sensors_rect, egui::Sense::click());
+                let sensors_draw_color = if sensors_resp.hovered() {
+                    theme::brighten(sensors_color, 40)
+                } else {
+                    sensors_color
+                };
+                ui.painter().rect_filled(sensors_rect, btn_rounding, sensors_draw_color);
+                ui.painter().text(
+                    sensors_rect.right_center() - egui::vec2(button_h / 2.0 + 4.0, -3.0),
+                    egui::Align2::RIGHT_CENTER,
+                    "SENSORS",
+                    egui::FontId::monospace(30.0),
+                    theme::BLACK,
+                );
+                if sensors_resp.clicked() {
+                    self.current_view = View::Sensors;
+                }
+
                 y += button_h + theme::BAR_SPACING * 3.0;
 
                 // Decorative labels
@@ -332,7 +602,7 @@ impl eframe::App for LcarsApp {
                     );
                     // Use allocate_rect so egui knows this space is taken
                     let _ = ui.allocate_rect(deco_rect, egui::Sense::hover());
-                    let color = theme::color_for_index(i + 2);
+                    let color = alert_color.unwrap_or(theme::color_for_index(i + 2));
                     ui.painter().rect_filled(
                         deco_rect,
                         btn_rounding,
@@ -369,7 +639,7 @@ impl eframe::App for LcarsApp {
                 // Sticky header for processes (outside scroll)
                 if self.current_view == View::Processes {
                     content_ui.add_space(8.0);
-                    crate::views::processes::show_header(&mut content_ui, &mut self.process_view);
+                    crate::views::processes::show_header(&mut content_ui, &mut self.process_view, sample.sys.processes());
                 }
 
                 egui::ScrollArea::vertical()
@@ -378,26 +648,36 @@ impl eframe::App for LcarsApp {
                         match self.current_view {
                             View::Dashboard => {
                                 ui.add_space(8.0);
-                                crate::views::dashboard::show(ui, &self.sys_info);
+                                crate::views::dashboard::show(ui, &sample.sys, &sample.battery, &sample.gpus, &self.config.panels);
                             }
                             View::Processes => {
-                                crate::views::processes::show_rows(
-                                    ui,
-                                    &mut self.process_view,
-                                    &self.sys_info.system,
-                                    &self.sys_info.users,
-                                );
+                                crate::views::processes::show_rows(ui, &mut self.process_view, sample.sys.processes());
                             }
                             View::Battery => {
                                 ui.add_space(8.0);
-                                crate::views::battery::show(ui, &self.battery_info);
+                                crate::views::battery::show(ui, &sample.battery);
                             }
                             View::Gpu => {
                                 ui.add_space(8.0);
-                                crate::views::gpu::show(ui, &self.gpu_info);
+                                crate::views::gpu::show(ui, &sample.gpus, &mut self.selected_gpu);
+                            }
+                            View::Sensors => {
+                                ui.add_space(8.0);
+                                crate::views::sensors::show(ui, &sample.sensors);
+                            }
+                            View::Process(pid) => {
+                                ui.add_space(8.0);
+                                let replay = matches!(self.source, Source::Replay(_));
+                                if crate::views::process::show(ui, pid, sample.process.as_ref(), replay) {
+                                    self.current_view = View::Processes;
+                                }
                             }
                         }
                     });
             });
+
+        if self.show_help && crate::views::help::show(ctx, &self.keymap) {
+            self.show_help = false;
+        }
     }
 }
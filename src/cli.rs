@@ -0,0 +1,183 @@
+#[cfg(feature = "exporter")]
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::REFRESH_RANGE;
+use crate::system::alerts::AlertRule;
+
+pub const USAGE: &str = "\
+Usage: lcars-ops [OPTIONS]
+
+Options:
+      --config <FILE>   Load settings from FILE instead of
+                        $XDG_CONFIG_HOME/lcars-ops/config.toml
+      --sysroot <DIR>   Read /sys and /proc from DIR instead of the host
+      --json            Print a JSON snapshot to stdout instead of opening a window
+      --headless        Same as --json
+      --interval <SECS> With --json, stream one NDJSON snapshot every SECS seconds
+      --count <N>       With --interval, stop after N snapshots
+      --alert <RULE>    Add an alert rule, replacing the built-in ones (repeatable),
+                        e.g. \"Hot: cpu.temp >= 90 for 30s critical\"
+      --notify          Raise a desktop notification when an alert fires
+      --record <FILE>   Append every sample to FILE (window or --json mode)
+      --replay <FILE>   Show a recording made with --record instead of live data
+      --listen <ADDR>   Serve OpenMetrics on http://ADDR/metrics (exporter feature)
+  -h, --help            Print this help";
+
+#[derive(Default)]
+pub struct Options {
+    pub config: Option<PathBuf>,
+    pub sysroot: Option<PathBuf>,
+    pub headless: bool,
+    pub interval: Option<Duration>,
+    pub count: Option<u64>,
+    pub alerts: Vec<AlertRule>,
+    pub notify: bool,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    #[cfg(feature = "exporter")]
+    pub listen: Option<SocketAddr>,
+    pub help: bool,
+}
+
+impl Options {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut opts = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+                _ => (arg, None),
+            };
+            match flag.as_str() {
+                "--config" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--config requires a file")?;
+                    opts.config = Some(PathBuf::from(value));
+                }
+                "--sysroot" => {
+                    let value = inline
+                        .or_else(|| args.next())
+                        .ok_or("--sysroot requires a directory")?;
+                    let path = PathBuf::from(value);
+                    if !path.is_dir() {
+                        return Err(format!("--sysroot {}: not a directory", path.display()));
+                    }
+                    opts.sysroot = Some(path);
+                }
+                "--json" | "--headless" => opts.headless = true,
+                "--interval" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--interval requires seconds")?;
+                    let secs: f64 = value
+                        .parse()
+                        .ok()
+                        .filter(|s: &f64| s.is_finite() && *s >= REFRESH_RANGE.0)
+                        .ok_or_else(|| format!("--interval {}: expected at least {} seconds", value, REFRESH_RANGE.0))?;
+                    opts.interval = Some(Duration::from_secs_f64(secs));
+                }
+                "--count" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--count requires a number")?;
+                    let n: u64 = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("--count {}: expected a positive integer", value))?;
+                    opts.count = Some(n);
+                }
+                #[cfg(feature = "exporter")]
+                "--listen" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--listen requires an address")?;
+                    let addr = value
+                        .parse()
+                        .map_err(|_| format!("--listen {}: expected HOST:PORT, e.g. 127.0.0.1:9184", value))?;
+                    opts.listen = Some(addr);
+                }
+                #[cfg(not(feature = "exporter"))]
+                "--listen" => return Err("--listen requires building with `--features exporter`".to_string()),
+                "--alert" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--alert requires a rule")?;
+                    let rule = value.parse().map_err(|e| format!("--alert '{}': {}", value, e))?;
+                    opts.alerts.push(rule);
+                }
+                "--notify" => opts.notify = true,
+                "--record" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--record requires a file")?;
+                    opts.record = Some(PathBuf::from(value));
+                }
+                "--replay" => {
+                    let value = inline.or_else(|| args.next()).ok_or("--replay requires a file")?;
+                    opts.replay = Some(PathBuf::from(value));
+                }
+                "-h" | "--help" => opts.help = true,
+                other => return Err(format!("unknown option '{}'", other)),
+            }
+        }
+        if opts.interval.is_some() && !opts.headless {
+            return Err("--interval requires --json".to_string());
+        }
+        if opts.count.is_some() && opts.interval.is_none() {
+            return Err("--count requires --interval".to_string());
+        }
+        if opts.replay.is_some() {
+            let conflict = [
+                (opts.headless, "--json"),
+                (opts.record.is_some(), "--record"),
+                (opts.sysroot.is_some(), "--sysroot"),
+                #[cfg(feature = "exporter")]
+                (opts.listen.is_some(), "--listen"),
+            ];
+            if let Some((_, flag)) = conflict.iter().find(|(set, _)| *set) {
+                return Err(format!("--replay cannot be combined with {}", flag));
+            }
+        }
+        #[cfg(feature = "exporter")]
+        if opts.listen.is_some() && opts.headless && opts.interval.is_none() {
+            return Err("--listen with --json requires --interval".to_string());
+        }
+        Ok(opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parses_json_stream_options() {
+        let opts = parse(&["--json", "--interval=0.5", "--count", "3"]).unwrap();
+        assert!(opts.headless);
+        assert_eq!(opts.interval, Some(Duration::from_millis(500)));
+        assert_eq!(opts.count, Some(3));
+        assert!(parse(&["--headless"]).unwrap().headless);
+    }
+
+    #[test]
+    fn collects_alert_rules() {
+        let opts = parse(&["--alert", "cpu.temp > 90", "--alert=memory.usage>=99 warning", "--notify"]).unwrap();
+        assert_eq!(opts.alerts.len(), 2);
+        assert_eq!(opts.alerts[1].threshold, 99.0);
+        assert!(opts.notify);
+    }
+
+    #[test]
+    fn rejects_inconsistent_options() {
+        assert!(parse(&["--interval", "2"]).is_err());
+        assert!(parse(&["--json", "--count", "2"]).is_err());
+        assert!(parse(&["--json", "--interval", "-1"]).is_err());
+        assert_eq!(parse(&["--json", "--interval", "0.1"]).err().as_deref(), Some("--interval 0.1: expected at least 0.25 seconds"));
+        assert!(parse(&["--frobnicate"]).is_err());
+        assert!(parse(&["--alert", "cpu.temp > hot"]).is_err());
+        assert!(parse(&["--replay", "night.lcr", "--json"]).is_err());
+        assert!(parse(&["--replay", "night.lcr", "--record", "copy.lcr"]).is_err());
+        assert!(parse(&["--replay=night.lcr"]).unwrap().replay.is_some());
+        assert!(parse(&["--json", "--interval", "5", "--record", "night.lcr"]).unwrap().record.is_some());
+    }
+}
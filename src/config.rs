@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::View;
+use crate::keymap::{Action, Keymap};
+use crate::system::alerts::AlertRule;
+use crate::system::history::HISTORY_RETENTION;
+use crate::system::process::{Column, SortOrder};
+
+/// Settings loaded from `$XDG_CONFIG_HOME/lcars-ops/config.toml`. Every key
+/// is optional; missing ones take the built-in defaults.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub default_view: View,
+    pub window: WindowConfig,
+    pub refresh: RefreshConfig,
+    pub history: HistoryConfig,
+    pub processes: ProcessConfig,
+    pub panels: PanelConfig,
+    pub alerts: AlertConfig,
+    /// Keys per action, replacing that action's built-in keys.
+    pub keys: BTreeMap<Action, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Collector refresh intervals, in seconds.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefreshConfig {
+    pub system: f64,
+    pub gpu: f64,
+    pub battery: f64,
+}
+
+/// How far back the sparklines reach, in seconds.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    pub retention: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessConfig {
+    pub limit: usize,
+    pub sort: Column,
+    pub order: SortOrder,
+    pub tree: bool,
+    pub columns: Vec<Column>,
+    /// Pixel widths of resized columns; others keep their default.
+    pub widths: BTreeMap<Column, f32>,
+}
+
+/// Dashboard panels to draw.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PanelConfig {
+    pub processor: bool,
+    pub sensors: bool,
+    pub power: bool,
+    pub properties: bool,
+    pub memory: bool,
+    pub storage: bool,
+    pub network: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Rules in `--alert` syntax; `None` keeps the built-in set, `[]` disables alerts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<Vec<String>>,
+    pub notify: bool,
+}
+
+pub const MIN_WINDOW: [f32; 2] = [1024.0, 600.0];
+pub const REFRESH_RANGE: (f64, f64) = (0.25, 60.0); // seconds
+const RETENTION_RANGE: (f64, f64) = (10.0, 3600.0); // seconds
+const MAX_PROCESS_LIMIT: usize = 10_000;
+pub const COLUMN_WIDTH_RANGE: (f32, f32) = (40.0, 1000.0); // pixels
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_view: View::Dashboard,
+            window: WindowConfig::default(),
+            refresh: RefreshConfig::default(),
+            history: HistoryConfig::default(),
+            processes: ProcessConfig::default(),
+            panels: PanelConfig::default(),
+            alerts: AlertConfig::default(),
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self { width: 1280.0, height: 800.0 }
+    }
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self { system: 1.5, gpu: 1.5, battery: 5.0 }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { retention: HISTORY_RETENTION.as_secs_f64() }
+    }
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            limit: 50,
+            sort: Column::Memory,
+            order: SortOrder::Descending,
+            tree: false,
+            columns: Column::DEFAULT.to_vec(),
+            widths: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self {
+        Self {
+            processor: true,
+            sensors: true,
+            power: true,
+            properties: true,
+            memory: true,
+            storage: true,
+            network: true,
+        }
+    }
+}
+
+impl RefreshConfig {
+    pub fn system(&self) -> Duration {
+        Duration::from_secs_f64(self.system)
+    }
+
+    pub fn gpu(&self) -> Duration {
+        Duration::from_secs_f64(self.gpu)
+    }
+
+    pub fn battery(&self) -> Duration {
+        Duration::from_secs_f64(self.battery)
+    }
+}
+
+impl HistoryConfig {
+    pub fn retention(&self) -> Duration {
+        Duration::from_secs_f64(self.retention)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/lcars-ops/config.toml`, falling back to `~/.config`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("lcars-ops").join("config.toml"))
+}
+
+impl Config {
+    /// Read and validate `path`; a missing file yields the defaults.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.message().to_string() + &span_hint(text, e.span()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (lo, hi) = REFRESH_RANGE;
+        for (key, secs) in [
+            ("refresh.system", self.refresh.system),
+            ("refresh.gpu", self.refresh.gpu),
+            ("refresh.battery", self.refresh.battery),
+        ] {
+            if !(lo..=hi).contains(&secs) {
+                return Err(format!("{} = {}: must be between {} and {} seconds", key, secs, lo, hi));
+            }
+        }
+        let (lo, hi) = RETENTION_RANGE;
+        if !(lo..=hi).contains(&self.history.retention) {
+            return Err(format!(
+                "history.retention = {}: must be between {} and {} seconds",
+                self.history.retention, lo, hi
+            ));
+        }
+        if !(1..=MAX_PROCESS_LIMIT).contains(&self.processes.limit) {
+            return Err(format!(
+                "processes.limit = {}: must be between 1 and {}",
+                self.processes.limit, MAX_PROCESS_LIMIT
+            ));
+        }
+        let columns = &self.processes.columns;
+        if !columns.contains(&Column::Name) {
+            return Err("processes.columns: must include \"name\"".to_string());
+        }
+        if let Some(c) = columns.iter().enumerate().find_map(|(i, c)| columns[..i].contains(c).then_some(c)) {
+            return Err(format!("processes.columns: {} listed twice", column_name(c)));
+        }
+        let (lo, hi) = COLUMN_WIDTH_RANGE;
+        if let Some((c, w)) = self.processes.widths.iter().find(|(_, w)| !(lo..=hi).contains(*w)) {
+            return Err(format!("processes.widths.{} = {}: must be between {} and {}", column_name(c), w, lo, hi));
+        }
+        if self.window.width < MIN_WINDOW[0] || self.window.height < MIN_WINDOW[1] {
+            return Err(format!(
+                "window = {}x{}: must be at least {}x{}",
+                self.window.width, self.window.height, MIN_WINDOW[0], MIN_WINDOW[1]
+            ));
+        }
+        Keymap::new(&self.keys)?;
+        self.alert_rules().map(|_| ())
+    }
+
+    pub fn alert_rules(&self) -> Result<Vec<AlertRule>, String> {
+        match &self.alerts.rules {
+            None => Ok(AlertRule::defaults()),
+            Some(rules) => rules
+                .iter()
+                .map(|r| r.parse().map_err(|e| format!("alerts.rules '{}': {}", r, e)))
+                .collect(),
+        }
+    }
+
+    /// Write the keys that differ from `previous` into `path`, leaving its
+    /// comments and every other key as they were. The file is replaced by a
+    /// rename, so a crash mid-write never leaves it truncated.
+    pub fn save(&self, previous: &Config, path: &Path) -> Result<(), String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        let mut doc: toml_edit::DocumentMut = text.parse().map_err(|e| format!("{}: {}", path.display(), e))?;
+        let old = toml::Table::try_from(previous).map_err(|e| e.to_string())?;
+        let new = toml::Table::try_from(self).map_err(|e| e.to_string())?;
+        update_keys(doc.as_table_mut(), &old, &new)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, doc.to_string()).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Set the keys of `doc` whose value went from `old` to `new`, descending into
+/// tables so untouched siblings keep their formatting.
+fn update_keys(doc: &mut dyn toml_edit::TableLike, old: &toml::Table, new: &toml::Table) -> Result<(), String> {
+    for key in old.keys().filter(|k| !new.contains_key(*k)) {
+        doc.remove(key);
+    }
+    for (key, value) in new {
+        match (old.get(key), value) {
+            (Some(old), _) if old == value => {}
+            (Some(toml::Value::Table(old)), toml::Value::Table(new)) => {
+                let item = doc.entry(key).or_insert(toml_edit::table());
+                if !item.is_table_like() {
+                    *item = toml_edit::table();
+                }
+                update_keys(item.as_table_like_mut().unwrap(), old, new)?;
+            }
+            _ => {
+                let mut value: toml_edit::Value = value.to_string().parse().map_err(|e| format!("{}: {}", key, e))?;
+                // Keep the spacing and trailing comment of the value being replaced
+                if let Some(old) = doc.get(key).and_then(|item| item.as_value()) {
+                    *value.decor_mut() = old.decor().clone();
+                }
+                doc.insert(key, toml_edit::value(value));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The config spelling of `column`, quoted: `"disk_read"`.
+fn column_name(column: &Column) -> String {
+    serde_json::to_string(column).unwrap_or_default()
+}
+
+/// " (line N)" for a parse error span, so messages point at the offending key.
+fn span_hint(text: &str, span: Option<std::ops::Range<usize>>) -> String {
+    span.map(|s| format!(" (line {})", text[..s.start.min(text.len())].matches('\n').count() + 1))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_is_default() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parses_partial_config() {
+        let config = Config::parse(
+            r#"
+default_view = "processes"
+
+[refresh]
+gpu = 3.0
+
+[processes]
+limit = 200
+sort = "cpu"
+order = "ascending"
+columns = ["name", "pid", "disk_write", "cgroup"]
+
+[processes.widths]
+name = 320
+
+[panels]
+storage = false
+
+[alerts]
+rules = ["cpu.temp > 90 for 20s"]
+notify = true
+
+[keys]
+signal = ["k"]
+up = ["ArrowUp"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_view, View::Processes);
+        assert_eq!(config.refresh.gpu(), Duration::from_secs(3));
+        assert_eq!(config.refresh.system, 1.5);
+        assert_eq!(config.processes.limit, 200);
+        assert_eq!(config.processes.sort, Column::Cpu);
+        assert_eq!(config.processes.order, SortOrder::Ascending);
+        assert_eq!(config.processes.columns, [Column::Name, Column::Pid, Column::DiskWrite, Column::Cgroup]);
+        assert_eq!(config.processes.widths[&Column::Name], 320.0);
+        assert!(!config.panels.storage && config.panels.memory);
+        assert_eq!(config.alert_rules().unwrap().len(), 1);
+        assert!(config.alerts.notify);
+        assert_eq!(config.keys[&Action::Signal], ["k"]);
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let err = Config::parse("[refresh]\nsystem = 0.01\n").unwrap_err();
+        assert!(err.contains("refresh.system"), "{}", err);
+        let err = Config::parse("[processes]\nlimit = 0\n").unwrap_err();
+        assert!(err.contains("processes.limit"), "{}", err);
+        let err = Config::parse("default_view = \"bridge\"\n").unwrap_err();
+        assert!(err.contains("unknown variant") && err.contains("line 1"), "{}", err);
+        let err = Config::parse("\n[panels]\nwarp = true\n").unwrap_err();
+        assert!(err.contains("warp") && err.contains("line 3"), "{}", err);
+        let err = Config::parse("[alerts]\nrules = [\"cpu.temp ~ 3\"]\n").unwrap_err();
+        assert!(err.contains("alerts.rules"), "{}", err);
+        let err = Config::parse("[processes]\ncolumns = [\"name\", \"cpu\", \"cpu\"]\n").unwrap_err();
+        assert_eq!(err, "processes.columns: \"cpu\" listed twice");
+        let err = Config::parse("[processes]\ncolumns = [\"pid\"]\n").unwrap_err();
+        assert!(err.contains("\"name\""), "{}", err);
+        let err = Config::parse("[keys]\npause = [\"Space\", \"Warp\"]\n").unwrap_err();
+        assert!(err.starts_with("keys.pause"), "{}", err);
+        let err = Config::parse("[processes.widths]\nnet_rx = 5\n").unwrap_err();
+        assert!(err.starts_with("processes.widths.\"net_rx\""), "{}", err);
+    }
+
+    #[test]
+    fn sets_history_retention() {
+        assert_eq!(Config::default().history.retention(), HISTORY_RETENTION);
+        let config = Config::parse("[history]\nretention = 600\n").unwrap();
+        assert_eq!(config.history.retention(), Duration::from_secs(600));
+        let err = Config::parse("[history]\nretention = 5\n").unwrap_err();
+        assert_eq!(err, "history.retention = 5: must be between 10 and 3600 seconds");
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let path = std::env::temp_dir().join(format!("lcars-ops-config-{}/config.toml", std::process::id()));
+        let mut config = Config::default();
+        config.processes.sort = Column::Gpu;
+        config.processes.columns.push(Column::StartTime);
+        config.processes.widths.insert(Column::StartTime, 180.0);
+        config.window.width = 1600.0;
+        config.save(&Config::default(), &path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        assert_eq!(Config::load(Path::new("/nonexistent/config.toml")).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn saves_only_changed_keys() {
+        let path = std::env::temp_dir().join(format!("lcars-ops-edit-{}/config.toml", std::process::id()));
+        let text = "# my settings\n[processes]\nsort = \"cpu\"   # busiest first\nlimit = 80\n\n[processes.widths]\nname = 300\n";
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, text).unwrap();
+        let previous = Config::load(&path).unwrap();
+        let mut config = previous.clone();
+        config.processes.sort = Column::Gpu;
+        config.processes.widths.remove(&Column::Name);
+        config.processes.widths.insert(Column::Pid, 90.0);
+        config.save(&previous, &path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            saved,
+            "# my settings\n[processes]\nsort = \"gpu\"   # busiest first\nlimit = 80\n\n[processes.widths]\npid = 90.0\n"
+        );
+        assert_eq!(Config::load(&path).unwrap(), config);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
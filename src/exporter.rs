@@ -0,0 +1,257 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::system::snapshot::Snapshot;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Embedded HTTP endpoint serving the latest snapshot as OpenMetrics text on
+/// `/metrics`. The sampler calls `publish`; scrapes never touch collectors.
+pub struct Exporter {
+    latest: Arc<Mutex<Option<String>>>,
+    addr: SocketAddr,
+}
+
+impl Exporter {
+    pub fn start(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let latest = Arc::new(Mutex::new(None));
+        let shared = latest.clone();
+        thread::Builder::new()
+            .name("metrics".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // A misbehaving client only costs its own response
+                    let _ = serve(stream, &shared);
+                }
+            })?;
+        Ok(Self { latest, addr })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn publish(&self, snap: &Snapshot) {
+        let text = render(snap);
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some(text);
+        }
+    }
+}
+
+fn serve(stream: TcpStream, latest: &Mutex<Option<String>>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain headers; requests to a metrics endpoint carry no body
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or("");
+
+    let body = latest.lock().ok().and_then(|l| l.clone());
+    let (status, content_type, body) = match (method, path, body) {
+        ("GET", "/metrics", Some(body)) => ("200 OK", CONTENT_TYPE, body),
+        ("GET", "/metrics", None) => ("503 Service Unavailable", "text/plain", "no sample yet\n".to_string()),
+        ("GET", "/", _) => ("200 OK", "text/plain", "lcars-ops exporter: see /metrics\n".to_string()),
+        ("GET", _, _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "method not allowed\n".to_string()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// ── OpenMetrics text ─────────────────────────────────────────────────────────
+
+struct Metrics {
+    out: String,
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, unit: &str, help: &str) {
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        if !unit.is_empty() {
+            let _ = writeln!(self.out, "# UNIT {} {}", name, unit);
+        }
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, val)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, escape(val));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", value);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub fn render(snap: &Snapshot) -> String {
+    let mut m = Metrics { out: String::new() };
+
+    m.family("lcars_cpu_usage_ratio", "gauge", "ratio", "Total CPU utilisation.");
+    m.sample("lcars_cpu_usage_ratio", &[], snap.cpu.usage as f64 / 100.0);
+    m.family("lcars_cpu_core_usage_ratio", "gauge", "ratio", "Per-core CPU utilisation.");
+    for (i, usage) in snap.cpu.per_core.iter().enumerate() {
+        m.sample("lcars_cpu_core_usage_ratio", &[("core", &i.to_string())], *usage as f64 / 100.0);
+    }
+    m.family("lcars_cpu_temperature_celsius", "gauge", "celsius", "CPU package temperature.");
+    m.sample("lcars_cpu_temperature_celsius", &[], snap.cpu.temp_c as f64);
+
+    m.family("lcars_memory_used_bytes", "gauge", "bytes", "Used physical memory.");
+    m.sample("lcars_memory_used_bytes", &[], snap.memory.used as f64);
+    m.family("lcars_memory_total_bytes", "gauge", "bytes", "Total physical memory.");
+    m.sample("lcars_memory_total_bytes", &[], snap.memory.total as f64);
+    m.family("lcars_swap_used_bytes", "gauge", "bytes", "Used swap.");
+    m.sample("lcars_swap_used_bytes", &[], snap.memory.swap_used as f64);
+    m.family("lcars_swap_total_bytes", "gauge", "bytes", "Total swap.");
+    m.sample("lcars_swap_total_bytes", &[], snap.memory.swap_total as f64);
+
+    m.family("lcars_disk_used_bytes", "gauge", "bytes", "Used space per mount point.");
+    for d in &snap.disks {
+        m.sample("lcars_disk_used_bytes", &[("mount", &d.mount)], d.used as f64);
+    }
+    m.family("lcars_disk_total_bytes", "gauge", "bytes", "Size per mount point.");
+    for d in &snap.disks {
+        m.sample("lcars_disk_total_bytes", &[("mount", &d.mount)], d.total as f64);
+    }
+
+    m.family("lcars_network_receive_bytes", "counter", "bytes", "Bytes received per interface.");
+    for n in &snap.networks {
+        m.sample("lcars_network_receive_bytes_total", &[("interface", &n.name)], n.rx_bytes as f64);
+    }
+    m.family("lcars_network_transmit_bytes", "counter", "bytes", "Bytes transmitted per interface.");
+    for n in &snap.networks {
+        m.sample("lcars_network_transmit_bytes_total", &[("interface", &n.name)], n.tx_bytes as f64);
+    }
+
+    m.family("lcars_gpu", "info", "", "GPU identity.");
+    for g in &snap.gpus {
+        m.sample(
+            "lcars_gpu_info",
+            &[("card", &g.card), ("driver", &g.driver), ("backend", &g.backend), ("kind", &g.kind), ("pci_slot", &g.pci_slot)],
+            1.0,
+        );
+    }
+    m.family("lcars_gpu_usage_ratio", "gauge", "ratio", "GPU utilisation.");
+    for g in &snap.gpus {
+        m.sample("lcars_gpu_usage_ratio", &[("card", &g.card)], g.usage as f64 / 100.0);
+    }
+    m.family("lcars_gpu_vram_used_bytes", "gauge", "bytes", "Used video memory.");
+    for g in &snap.gpus {
+        m.sample("lcars_gpu_vram_used_bytes", &[("card", &g.card)], g.vram_used as f64);
+    }
+    m.family("lcars_gpu_vram_total_bytes", "gauge", "bytes", "Total video memory.");
+    for g in &snap.gpus {
+        m.sample("lcars_gpu_vram_total_bytes", &[("card", &g.card)], g.vram_total as f64);
+    }
+    m.family("lcars_gpu_frequency_hertz", "gauge", "hertz", "Current GPU core clock.");
+    for g in &snap.gpus {
+        if let Some(mhz) = g.gpu_freq_mhz {
+            m.sample("lcars_gpu_frequency_hertz", &[("card", &g.card)], mhz as f64 * 1e6);
+        }
+    }
+    m.family("lcars_gpu_power_watts", "gauge", "watts", "GPU power draw.");
+    for g in &snap.gpus {
+        m.sample("lcars_gpu_power_watts", &[("card", &g.card)], g.power_w);
+    }
+    m.family("lcars_gpu_temperature_celsius", "gauge", "celsius", "GPU temperature.");
+    for g in &snap.gpus {
+        m.sample("lcars_gpu_temperature_celsius", &[("card", &g.card)], g.temp_c as f64);
+    }
+
+    if let Some(b) = &snap.battery {
+        let device = [("device", b.device.as_str())];
+        m.family("lcars_battery", "info", "", "Battery identity and charge status.");
+        m.sample(
+            "lcars_battery_info",
+            &[("device", &b.device), ("status", &b.status), ("technology", &b.technology), ("model", &b.model_name)],
+            1.0,
+        );
+        m.family("lcars_battery_charge_ratio", "gauge", "ratio", "Battery charge level.");
+        m.sample("lcars_battery_charge_ratio", &device, b.capacity as f64 / 100.0);
+        m.family("lcars_battery_health_ratio", "gauge", "ratio", "Full capacity relative to design capacity.");
+        m.sample("lcars_battery_health_ratio", &device, b.health as f64 / 100.0);
+        m.family("lcars_battery_power_watts", "gauge", "watts", "Battery charge or discharge power.");
+        m.sample("lcars_battery_power_watts", &device, b.power_w);
+        m.family("lcars_battery_cycles", "gauge", "", "Battery charge cycle count.");
+        m.sample("lcars_battery_cycles", &device, b.cycle_count as f64);
+    }
+
+    m.out.push_str("# EOF\n");
+    m.out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HistoryConfig, RefreshConfig};
+    use crate::system::sampler::Collectors;
+    use crate::system::sysfs::fixture;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn serves_published_snapshot() {
+        let exporter = Exporter::start("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = exporter.local_addr();
+        assert!(get(addr, "/metrics").starts_with("HTTP/1.1 503"));
+
+        let sample = Collectors::new(fixture("intel-laptop"), &RefreshConfig::default(), &HistoryConfig::default()).sample();
+        exporter.publish(&Snapshot::system(&sample));
+
+        let response = get(addr, "/metrics");
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(head.contains(CONTENT_TYPE));
+        assert!(body.contains("# TYPE lcars_cpu_usage_ratio gauge\n"));
+        assert!(body.contains("lcars_cpu_core_usage_ratio{core=\"0\"} "));
+        assert!(body.contains("lcars_gpu_frequency_hertz{card=\"card0\"} 650000000\n"));
+        assert!(body.contains("lcars_battery_charge_ratio{device=\"BAT0\"} 0.76\n"));
+        assert!(body.ends_with("# EOF\n"));
+
+        assert!(get(addr, "/nope").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::{self, Write};
+use std::thread;
+
+use crate::cli::Options;
+use crate::config::Config;
+use crate::system::process::ProcessView;
+use crate::system::recording::Recorder;
+use crate::system::sampler::Collectors;
+use crate::system::snapshot::Snapshot;
+use crate::system::sysfs::Fs;
+
+/// Print snapshots to stdout without opening a window: one pretty-printed
+/// JSON document, or NDJSON every `--interval` when streaming.
+pub fn run(opts: &Options, config: &Config, fs: Fs) -> io::Result<()> {
+    let mut recorder = match &opts.record {
+        Some(path) => Some(
+            Recorder::append(path).map_err(|e| io::Error::new(e.kind(), format!("--record {}: {}", path.display(), e)))?,
+        ),
+        None => None,
+    };
+    // Collectors slower than --interval would repeat readings between snapshots
+    let mut refresh = config.refresh.clone();
+    if let Some(secs) = opts.interval.map(|i| i.as_secs_f64()) {
+        refresh.system = refresh.system.min(secs);
+        refresh.gpu = refresh.gpu.min(secs);
+        refresh.battery = refresh.battery.min(secs);
+    }
+    let mut collectors = Collectors::new(fs, &refresh, &config.history);
+    let procs = ProcessView::new(config.processes.sort, config.processes.order, config.processes.limit);
+    #[cfg(feature = "exporter")]
+    let exporter = crate::start_exporter(opts);
+
+    // Usage and rates are deltas between refreshes, so let one full
+    // collector interval elapse before the first snapshot.
+    let interval = opts.interval;
+    thread::sleep(collectors.interval());
+
+    let mut out = io::stdout().lock();
+    let mut emitted = 0;
+    loop {
+        collectors.refresh();
+
+        let sample = collectors.sample();
+        if let Some(recorder) = &mut recorder {
+            recorder.write(&sample)?;
+        }
+        let snap = Snapshot::capture(&sample, &procs);
+        #[cfg(feature = "exporter")]
+        if let Some(exporter) = &exporter {
+            exporter.publish(&snap);
+        }
+        match interval {
+            Some(_) => serde_json::to_writer(&mut out, &snap)?,
+            None => serde_json::to_writer_pretty(&mut out, &snap)?,
+        }
+        writeln!(out)?;
+        out.flush()?;
+        emitted += 1;
+
+        let Some(interval) = interval else { return Ok(()) };
+        if opts.count.is_some_and(|n| emitted >= n) {
+            return Ok(());
+        }
+        thread::sleep(interval);
+    }
+}
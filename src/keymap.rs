@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use egui::{Key, KeyboardShortcut, ModifierNames, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Something a key can do; the `[keys]` config table is keyed by these names.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Help,
+    Dashboard,
+    Processes,
+    Battery,
+    Gpu,
+    Sensors,
+    Up,
+    Down,
+    Expand,
+    Details,
+    Back,
+    Search,
+    CycleSort,
+    Signal,
+    Pause,
+    Step,
+}
+
+impl Action {
+    pub const ALL: [Action; 17] = [
+        Action::Quit,
+        Action::Help,
+        Action::Dashboard,
+        Action::Processes,
+        Action::Battery,
+        Action::Gpu,
+        Action::Sensors,
+        Action::Up,
+        Action::Down,
+        Action::Expand,
+        Action::Details,
+        Action::Back,
+        Action::Search,
+        Action::CycleSort,
+        Action::Signal,
+        Action::Pause,
+        Action::Step,
+    ];
+
+    /// The config spelling, e.g. `cycle_sort`.
+    fn name(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default().trim_matches('"').to_string()
+    }
+
+    /// Shown in the help overlay.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Quit => "QUIT",
+            Action::Help => "THIS HELP",
+            Action::Dashboard => "DASHBOARD VIEW",
+            Action::Processes => "PROCESS VIEW",
+            Action::Battery => "BATTERY VIEW",
+            Action::Gpu => "GPU VIEW",
+            Action::Sensors => "SENSORS VIEW",
+            Action::Up => "SELECT PREVIOUS PROCESS",
+            Action::Down => "SELECT NEXT PROCESS",
+            Action::Expand => "EXPAND / COLLAPSE SELECTED",
+            Action::Details => "DETAILS OF SELECTED",
+            Action::Back => "BACK / CLOSE",
+            Action::Search => "SEARCH PROCESSES",
+            Action::CycleSort => "NEXT SORT COLUMN",
+            Action::Signal => "TERMINATE SELECTED",
+            Action::Pause => "FREEZE / RESUME",
+            Action::Step => "TAKE ONE SAMPLE",
+        }
+    }
+
+    fn defaults(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["Q"],
+            Action::Help => &["?", "F1"],
+            Action::Dashboard => &["1", "F2"],
+            Action::Processes => &["2", "F3"],
+            Action::Battery => &["3", "F4"],
+            Action::Gpu => &["4", "F5"],
+            Action::Sensors => &["5", "F6"],
+            Action::Up => &["ArrowUp", "K"],
+            Action::Down => &["ArrowDown", "J"],
+            Action::Expand => &["Enter"],
+            Action::Details => &["I"],
+            Action::Back => &["Escape"],
+            Action::Search => &["/"],
+            Action::CycleSort => &["S"],
+            // Plain `k` moves up, vi-style
+            Action::Signal => &["Shift+K"],
+            Action::Pause => &["P"],
+            Action::Step => &["."],
+        }
+    }
+}
+
+/// Key bindings: the built-in ones, with any action listed in the config
+/// replaced by its configured keys.
+pub struct Keymap {
+    bindings: Vec<(KeyboardShortcut, Action)>,
+}
+
+impl Keymap {
+    pub fn new(overrides: &BTreeMap<Action, Vec<String>>) -> Result<Self, String> {
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            match overrides.get(&action) {
+                Some(keys) => {
+                    for key in keys {
+                        let shortcut = parse_shortcut(key).map_err(|e| format!("keys.{}: {}", action.name(), e))?;
+                        bindings.push((shortcut, action));
+                    }
+                }
+                None => bindings.extend(action.defaults().iter().map(|k| (parse_shortcut(k).unwrap(), action))),
+            }
+        }
+        for (i, (shortcut, action)) in bindings.iter().enumerate() {
+            if let Some((_, other)) = bindings[..i].iter().find(|(s, _)| s == shortcut) {
+                return Err(format!(
+                    "keys: \"{}\" is bound to both {} and {}",
+                    format_shortcut(shortcut),
+                    other.name(),
+                    action.name()
+                ));
+            }
+        }
+        // egui ignores extra Shift/Alt, so Shift+K must be tried before K
+        bindings.sort_by_key(|(s, _)| std::cmp::Reverse(modifier_count(s.modifiers)));
+        Ok(Self { bindings })
+    }
+
+    /// Consume this frame's bound key presses. Nothing is taken while a text
+    /// field has focus, so typing a filter doesn't trigger commands.
+    pub fn pressed(&self, ctx: &egui::Context) -> Vec<Action> {
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+        ctx.input_mut(|i| {
+            self.bindings
+                .iter()
+                .filter(|(shortcut, _)| i.consume_shortcut(shortcut))
+                .map(|(_, action)| *action)
+                .collect()
+        })
+    }
+
+    /// Keys bound to `action`, formatted for display.
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(s, _)| format_shortcut(s))
+            .collect()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).unwrap()
+    }
+}
+
+/// `"Ctrl+Shift+F5"`, `"j"`, `"?"`: modifiers joined to an egui key name with `+`.
+fn parse_shortcut(text: &str) -> Result<KeyboardShortcut, String> {
+    // A lone "+" is the key itself
+    let (mods, key) = match text.rsplit_once('+') {
+        Some((mods, "")) => (mods.strip_suffix('+').unwrap_or(""), "+"),
+        Some((mods, key)) => (mods, key),
+        None => ("", text),
+    };
+    let mut modifiers = Modifiers::NONE;
+    for m in mods.split('+').filter(|m| !m.is_empty()) {
+        match m.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "shift" => modifiers.shift = true,
+            "alt" => modifiers.alt = true,
+            _ => return Err(format!("unknown modifier \"{}\" in \"{}\"", m, text)),
+        }
+    }
+    let key = Key::from_name(key).ok_or_else(|| format!("unknown key \"{}\"", text))?;
+    Ok(KeyboardShortcut::new(modifiers, key))
+}
+
+fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    shortcut.format(&ModifierNames::NAMES, false).to_uppercase()
+}
+
+fn modifier_count(m: Modifiers) -> usize {
+    [m.ctrl, m.shift, m.alt].iter().filter(|on| **on).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_names() {
+        assert_eq!(parse_shortcut("j").unwrap(), KeyboardShortcut::new(Modifiers::NONE, Key::J));
+        assert_eq!(parse_shortcut("Ctrl+Shift+F5").unwrap(), KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::F5));
+        assert_eq!(parse_shortcut("Alt++").unwrap(), KeyboardShortcut::new(Modifiers::ALT, Key::Plus));
+        assert!(parse_shortcut("Hyper+X").unwrap_err().contains("Hyper"));
+        assert!(parse_shortcut("Warp").is_err());
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert(Action::Signal, vec!["k".to_string(), "Delete".to_string()]);
+        overrides.insert(Action::Up, vec!["ArrowUp".to_string()]);
+        let keymap = Keymap::new(&overrides).unwrap();
+        assert_eq!(keymap.keys(Action::Signal), ["K", "DELETE"]);
+        assert_eq!(keymap.keys(Action::Quit), ["Q"]);
+
+        // Plain k is still bound to up by default
+        overrides.remove(&Action::Up);
+        let err = Keymap::new(&overrides).err().unwrap();
+        assert_eq!(err, "keys: \"K\" is bound to both up and signal");
+        overrides.insert(Action::Pause, vec!["F99".to_string()]);
+        assert!(Keymap::new(&overrides).err().unwrap().starts_with("keys.pause: unknown key"));
+    }
+}
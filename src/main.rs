@@ -1,21 +1,130 @@
 mod app;
+mod cli;
+mod config;
+#[cfg(feature = "exporter")]
+mod exporter;
+mod headless;
+mod keymap;
 mod system;
 mod theme;
 mod views;
 mod widgets;
 
+use config::Config;
+use app::Source;
+use system::recording::{Player, Recorder, Recording};
+use system::sampler::{Collectors, Sampler};
+
 fn main() -> eframe::Result<()> {
+    let opts = match cli::Options::parse() {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("lcars-ops: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if opts.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    let fs = match &opts.sysroot {
+        Some(root) => system::sysfs::rooted(root),
+        None => system::sysfs::host(),
+    };
+
+    let config_path = opts.config.clone().or_else(config::default_path);
+    let config = match &config_path {
+        Some(path) => Config::load(path).unwrap_or_else(|e| {
+            eprintln!("lcars-ops: {}", e);
+            std::process::exit(2);
+        }),
+        None => Config::default(),
+    };
+
+    if opts.headless {
+        match headless::run(&opts, &config, fs) {
+            // A closed pipe (`| head`) is a normal way to stop a stream
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                eprintln!("lcars-ops: {}", e);
+                std::process::exit(1);
+            }
+            _ => return Ok(()),
+        }
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1280.0, 800.0])
-            .with_min_inner_size([1024.0, 600.0])
+            .with_inner_size([config.window.width, config.window.height])
+            .with_min_inner_size(config::MIN_WINDOW)
             .with_title("LCARS-OPS"),
         ..Default::default()
     };
 
+    // Open files before the window so mistakes are reported on the terminal
+    let player = opts.replay.as_ref().map(|path| {
+        Recording::open(path).and_then(Player::new).unwrap_or_else(|e| {
+            eprintln!("lcars-ops: --replay {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+    let mut recorder = opts.record.as_ref().map(|path| {
+        Recorder::append(path).unwrap_or_else(|e| {
+            eprintln!("lcars-ops: --record {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    });
+    #[cfg(feature = "exporter")]
+    let exporter = start_exporter(&opts);
+
     eframe::run_native(
         "LCARS-OPS",
         options,
-        Box::new(|cc| Ok(Box::new(app::LcarsApp::new(cc)))),
+        Box::new(|cc| {
+            // Rules from the command line replace those in the config file
+            let rules = if opts.alerts.is_empty() {
+                config.alert_rules().unwrap_or_default()
+            } else {
+                opts.alerts
+            };
+            let notify = opts.notify || config.alerts.notify;
+
+            let source = match player {
+                Some(player) => Source::Replay(player),
+                None => {
+                    // Collection runs off the UI thread; each new sample wakes the window
+                    let ctx = cc.egui_ctx.clone();
+                    let collectors = Collectors::new(fs, &config.refresh, &config.history);
+                    let recording = recorder.is_some();
+                    let sampler = Sampler::spawn(collectors, move |sample| {
+                        #[cfg(feature = "exporter")]
+                        if let Some(exporter) = &exporter {
+                            exporter.publish(&system::snapshot::Snapshot::system(sample));
+                        }
+                        if let Some(Err(e)) = recorder.as_mut().map(|r| r.write(sample)) {
+                            eprintln!("lcars-ops: recording stopped: {}", e);
+                            recorder = None;
+                        }
+                        ctx.request_repaint();
+                    });
+                    Source::Live(if recording { sampler.with_all_proc_files() } else { sampler })
+                }
+            };
+            Ok(Box::new(app::LcarsApp::new(cc, source, config, config_path).with_alerts(rules, notify)))
+        }),
     )
 }
+
+#[cfg(feature = "exporter")]
+fn start_exporter(opts: &cli::Options) -> Option<exporter::Exporter> {
+    let addr = opts.listen?;
+    match exporter::Exporter::start(addr) {
+        Ok(exporter) => {
+            eprintln!("lcars-ops: serving metrics on http://{}/metrics", exporter.local_addr());
+            Some(exporter)
+        }
+        Err(e) => {
+            eprintln!("lcars-ops: --listen {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    }
+}
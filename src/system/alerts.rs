@@ -0,0 +1,405 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use super::sampler::Sample;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Metric {
+    CpuUsage,
+    CpuTemp,
+    MemoryUsage,
+    SwapUsage,
+    DiskUsage,
+    GpuUsage,
+    GpuTemp,
+    GpuPower,
+    BatteryCharge,
+    BatteryPower,
+}
+
+impl Metric {
+    pub const ALL: &'static [Metric] = &[
+        Metric::CpuUsage,
+        Metric::CpuTemp,
+        Metric::MemoryUsage,
+        Metric::SwapUsage,
+        Metric::DiskUsage,
+        Metric::GpuUsage,
+        Metric::GpuTemp,
+        Metric::GpuPower,
+        Metric::BatteryCharge,
+        Metric::BatteryPower,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Metric::CpuUsage => "cpu.usage",
+            Metric::CpuTemp => "cpu.temp",
+            Metric::MemoryUsage => "memory.usage",
+            Metric::SwapUsage => "swap.usage",
+            Metric::DiskUsage => "disk.usage",
+            Metric::GpuUsage => "gpu.usage",
+            Metric::GpuTemp => "gpu.temp",
+            Metric::GpuPower => "gpu.power",
+            Metric::BatteryCharge => "battery.charge",
+            Metric::BatteryPower => "battery.power",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Metric::CpuTemp | Metric::GpuTemp => "°C",
+            Metric::GpuPower | Metric::BatteryPower => "W",
+            _ => "%",
+        }
+    }
+
+    /// Current value, or `None` when the hardware is absent. Multi-device
+    /// metrics report the worst device (hottest GPU, fullest disk), and
+    /// battery charge only counts while running on battery.
+    pub fn read(&self, sample: &Sample) -> Option<f64> {
+        let (sys, gpus, battery) = (&sample.sys, &sample.gpus, &sample.battery);
+        let max = |values: Vec<f64>| values.into_iter().reduce(f64::max);
+        match self {
+            Metric::CpuUsage => Some(sys.cpu_total() as f64),
+            Metric::CpuTemp => (sys.cpu_temp > 0.0).then_some(sys.cpu_temp as f64),
+            Metric::MemoryUsage => Some(sys.memory_fraction() as f64 * 100.0),
+            Metric::SwapUsage => (sys.swap_total() > 0).then(|| sys.swap_fraction() as f64 * 100.0),
+            Metric::DiskUsage => max(sys.disk_info().iter().map(|d| d.fraction as f64 * 100.0).collect()),
+            Metric::GpuUsage => max(gpus.iter().map(|g| g.gpu_usage as f64).collect()),
+            Metric::GpuTemp => max(gpus.iter().filter(|g| g.temp_celsius > 0.0).map(|g| g.temp_celsius as f64).collect()),
+            Metric::GpuPower => max(gpus.iter().map(|g| g.power_w).collect()),
+            Metric::BatteryCharge => {
+                let on_battery = !matches!(battery.status.as_str(), "Charging" | "Full" | "Not charging");
+                (battery.available && on_battery).then_some(battery.capacity as f64)
+            }
+            Metric::BatteryPower => battery.available.then_some(battery.power_now),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Metric::ALL.iter().copied().find(|m| m.key() == s).ok_or_else(|| {
+            let keys: Vec<&str> = Metric::ALL.iter().map(|m| m.key()).collect();
+            format!("unknown metric '{}' (expected one of {})", s, keys.join(", "))
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Comparison {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+        }
+    }
+
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            ">" => Ok(Comparison::Above),
+            ">=" => Ok(Comparison::AtLeast),
+            "<" => Ok(Comparison::Below),
+            "<=" => Ok(Comparison::AtMost),
+            other => Err(format!("unknown comparison '{}' (expected >, >=, < or <=)", other)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Warning => "YELLOW ALERT",
+            Severity::Critical => "RED ALERT",
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warning" | "yellow" => Ok(Severity::Warning),
+            "critical" | "red" => Ok(Severity::Critical),
+            other => Err(format!("unknown severity '{}' (expected warning or critical)", other)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub duration: Duration, // how long the condition must hold before firing
+    pub severity: Severity,
+}
+
+impl AlertRule {
+    pub fn defaults() -> Vec<AlertRule> {
+        [
+            "CPU OVERHEAT: cpu.temp >= 95 for 10s critical",
+            "GPU OVERHEAT: gpu.temp >= 95 for 10s critical",
+            "BATTERY CRITICAL: battery.charge <= 5 critical",
+            "MEMORY EXHAUSTED: memory.usage >= 95 for 30s warning",
+        ]
+        .iter()
+        .map(|r| r.parse().expect("built-in alert rule"))
+        .collect()
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}{}", self.metric.key(), self.comparison.symbol(), self.threshold, self.metric.unit())
+    }
+}
+
+/// `[NAME:] METRIC OP THRESHOLD [for DURATION] [warning|critical]`,
+/// e.g. `cpu.temp >= 90 for 30s critical`. Unnamed rules use the condition
+/// as their name; severity defaults to critical.
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, condition) = match s.split_once(':') {
+            Some((name, rest)) => (Some(name.trim().to_string()), rest),
+            None => (None, s),
+        };
+        let op_at = condition.find(['<', '>']).ok_or("missing comparison (>, >=, < or <=)")?;
+        let (metric, rest) = condition.split_at(op_at);
+        let (op, rest) = rest.split_at(if rest[1..].starts_with('=') { 2 } else { 1 });
+        let metric: Metric = metric.trim().parse()?;
+        let comparison: Comparison = op.parse()?;
+
+        let mut tokens = rest.split_whitespace();
+        let threshold_str = tokens.next().ok_or("missing threshold")?;
+        let threshold: f64 = threshold_str
+            .trim_end_matches(metric.unit())
+            .parse()
+            .map_err(|_| format!("invalid threshold '{}'", threshold_str))?;
+
+        let mut duration = Duration::ZERO;
+        let mut severity = Severity::Critical;
+        while let Some(token) = tokens.next() {
+            if token == "for" {
+                duration = parse_duration(tokens.next().ok_or("missing duration after 'for'")?)?;
+            } else {
+                severity = token.parse()?;
+            }
+        }
+
+        let mut rule = AlertRule {
+            name: String::new(),
+            metric,
+            comparison,
+            threshold,
+            duration,
+            severity,
+        };
+        rule.name = name.filter(|n| !n.is_empty()).unwrap_or_else(|| rule.to_string());
+        Ok(rule)
+    }
+}
+
+/// `500ms`, `10s`, `5m`, `1h`; a bare number is seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let value: f64 = number.parse().map_err(|_| format!("invalid duration '{}'", s))?;
+    let secs = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("invalid duration '{}' (use ms, s, m or h)", s)),
+    };
+    Ok(Duration::from_secs_f64(secs))
+}
+
+// ── Evaluation ───────────────────────────────────────────────────────────────
+
+#[derive(Default, Clone)]
+struct RuleState {
+    breached_since: Option<Instant>,
+    firing: bool,
+    value: f64, // last reading
+}
+
+pub struct FiringAlert<'a> {
+    pub rule: &'a AlertRule,
+    pub value: f64,
+}
+
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    states: Vec<RuleState>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let states = vec![RuleState::default(); rules.len()];
+        Self { rules, states }
+    }
+
+    /// Check every rule against the collectors; returns rules that started
+    /// firing on this sample.
+    pub fn evaluate(&mut self, sample: &Sample) -> Vec<FiringAlert<'_>> {
+        self.evaluate_at(sample, Instant::now())
+    }
+
+    /// `evaluate` against a clock other than the wall clock, e.g. the
+    /// recording time of a replayed sample.
+    pub fn evaluate_at(&mut self, sample: &Sample, now: Instant) -> Vec<FiringAlert<'_>> {
+        self.update(now, |m| m.read(sample))
+    }
+
+    fn update(&mut self, now: Instant, read: impl Fn(Metric) -> Option<f64>) -> Vec<FiringAlert<'_>> {
+        let mut started = Vec::new();
+        for (i, (rule, state)) in self.rules.iter().zip(self.states.iter_mut()).enumerate() {
+            let value = read(rule.metric);
+            let breached = value.is_some_and(|v| rule.comparison.holds(v, rule.threshold));
+            state.value = value.unwrap_or(0.0);
+            if !breached {
+                state.breached_since = None;
+                state.firing = false;
+                continue;
+            }
+            let since = *state.breached_since.get_or_insert(now);
+            if !state.firing && now.saturating_duration_since(since) >= rule.duration {
+                state.firing = true;
+                started.push(i);
+            }
+        }
+        started
+            .into_iter()
+            .map(|i| FiringAlert { rule: &self.rules[i], value: self.states[i].value })
+            .collect()
+    }
+
+    /// Currently firing rules, most severe first.
+    pub fn firing(&self) -> Vec<FiringAlert<'_>> {
+        let mut firing: Vec<FiringAlert> = self
+            .rules
+            .iter()
+            .zip(&self.states)
+            .filter(|(_, s)| s.firing)
+            .map(|(rule, s)| FiringAlert { rule, value: s.value })
+            .collect();
+        firing.sort_by(|a, b| b.rule.severity.partial_cmp(&a.rule.severity).unwrap_or(std::cmp::Ordering::Equal));
+        firing
+    }
+}
+
+/// Best-effort desktop notification through `notify-send`.
+pub fn notify_desktop(alert: &FiringAlert) {
+    let urgency = match alert.rule.severity {
+        Severity::Warning => "normal",
+        Severity::Critical => "critical",
+    };
+    let summary = format!("{}: {}", alert.rule.severity.label(), alert.rule.name);
+    let body = format!("{} is {:.1}{} ({})", alert.rule.metric.key(), alert.value, alert.rule.metric.unit(), alert.rule);
+    let mut cmd = std::process::Command::new("notify-send");
+    cmd.args(["-a", "LCARS-OPS", "-u", urgency, &summary, &body]);
+    // Reap the child off the UI thread; a missing notify-send is not an error
+    std::thread::spawn(move || {
+        let _ = cmd.status();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules() {
+        let rule: AlertRule = "Hot CPU: cpu.temp>=90 for 30s warning".parse().unwrap();
+        assert_eq!(rule.name, "Hot CPU");
+        assert_eq!(rule.metric, Metric::CpuTemp);
+        assert_eq!(rule.comparison, Comparison::AtLeast);
+        assert_eq!(rule.threshold, 90.0);
+        assert_eq!(rule.duration, Duration::from_secs(30));
+        assert_eq!(rule.severity, Severity::Warning);
+
+        let rule: AlertRule = "battery.charge < 5%".parse().unwrap();
+        assert_eq!(rule.name, "battery.charge < 5%");
+        assert_eq!(rule.comparison, Comparison::Below);
+        assert_eq!(rule.duration, Duration::ZERO);
+        assert_eq!(rule.severity, Severity::Critical);
+
+        assert!("cpu.speed > 3".parse::<AlertRule>().is_err());
+        assert!("cpu.temp = 3".parse::<AlertRule>().is_err());
+        assert!("cpu.temp > hot".parse::<AlertRule>().is_err());
+        assert!("cpu.temp > 90 for ever".parse::<AlertRule>().is_err());
+        assert_eq!(AlertRule::defaults().len(), 4);
+    }
+
+    #[test]
+    fn fires_only_after_sustained_breach() {
+        let mut engine = AlertEngine::new(vec!["cpu.temp > 90 for 10s".parse().unwrap()]);
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+
+        assert!(engine.update(at(0), |_| Some(95.0)).is_empty());
+        assert!(engine.update(at(5), |_| Some(96.0)).is_empty());
+        // Dipping below resets the timer
+        assert!(engine.update(at(6), |_| Some(80.0)).is_empty());
+        assert!(engine.update(at(7), |_| Some(95.0)).is_empty());
+        assert!(engine.update(at(16), |_| Some(95.0)).is_empty());
+        assert_eq!(engine.update(at(17), |_| Some(97.0)).len(), 1);
+        // Still firing, but not newly started
+        assert!(engine.update(at(18), |_| Some(97.0)).is_empty());
+        assert_eq!(engine.firing().len(), 1);
+        assert_eq!(engine.firing()[0].value, 97.0);
+
+        // Missing hardware clears the alert
+        engine.update(at(19), |_| None);
+        assert!(engine.firing().is_empty());
+    }
+
+    #[test]
+    fn orders_firing_by_severity() {
+        let mut engine = AlertEngine::new(vec![
+            "memory.usage > 50 warning".parse().unwrap(),
+            "cpu.usage > 50 critical".parse().unwrap(),
+        ]);
+        engine.update(Instant::now(), |_| Some(99.0));
+        let firing = engine.firing();
+        assert_eq!(firing[0].rule.severity, Severity::Critical);
+        assert_eq!(firing[1].rule.severity, Severity::Warning);
+    }
+}
@@ -1,11 +1,27 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
+
+use super::history::{History, HISTORY_RETENTION};
+use super::sysfs::{self, Fs, SysFs};
+
+/// Default; overridden by `refresh.battery` in the config file.
 const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
 
+/// Energy readings the charge/discharge rate is fitted over. Fuel gauges
+/// update in coarse steps, so anything much shorter follows the steps.
+const RATE_WINDOW: Duration = Duration::from_secs(300);
+/// Span of readings needed before the fitted rate replaces `power_now`.
+const MIN_RATE_SPAN: Duration = Duration::from_secs(30);
+
+/// Combined state of the system batteries, plus every entry under
+/// `/sys/class/power_supply`. The identity fields (`technology` through
+/// `model_name`) and `cycle_count` are those of the first battery.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BatteryInfo {
-    pub device: String,
+    pub device: String, // every system battery, e.g. "BAT0 + BAT1"
     pub capacity: u32,
     pub status: String,
     pub health: f32,
@@ -16,34 +32,264 @@ pub struct BatteryInfo {
     pub manufacturer: String,
     pub model_name: String,
     pub available: bool,
+    pub power_history: History, // W
+    #[serde(default)]
+    pub supplies: Vec<PowerSupply>,
+    #[serde(default)]
+    pub energy_now: f64, // Wh
+    #[serde(default)]
+    pub energy_full: f64, // Wh
+    #[serde(default)]
+    pub time_to_empty: Option<u64>, // seconds, while discharging
+    #[serde(default)]
+    pub time_to_full: Option<u64>, // seconds, while charging
+    // Collector state, not recorded; a replayed battery is never refreshed
+    #[serde(skip, default = "RateEstimator::new")]
+    rate: RateEstimator,
+    #[serde(skip, default = "sysfs::host")]
+    fs: Fs,
+    #[serde(skip)]
+    interval: Duration,
+    #[serde(skip)]
+    retention: Duration,
+    #[serde(skip, default = "Instant::now")]
     last_refresh: Instant,
 }
 
-fn read_sysfs(base: &PathBuf, name: &str) -> Option<String> {
-    fs::read_to_string(base.join(name))
-        .ok()
-        .map(|s| s.trim().to_string())
+/// The power supply class `type`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SupplyKind {
+    Battery,
+    Ups,
+    Mains,
+    Usb,
+    Wireless,
+    Other,
 }
 
-fn read_u64(base: &PathBuf, name: &str) -> Option<u64> {
-    read_sysfs(base, name)?.parse().ok()
+impl SupplyKind {
+    fn from_type(kind: &str) -> Self {
+        match kind {
+            "Battery" => SupplyKind::Battery,
+            "UPS" => SupplyKind::Ups,
+            "Mains" => SupplyKind::Mains,
+            // USB_PD and friends predate the `usb_type` attribute
+            k if k.starts_with("USB") => SupplyKind::Usb,
+            "Wireless" => SupplyKind::Wireless,
+            _ => SupplyKind::Other,
+        }
+    }
 }
 
-fn find_battery() -> Option<PathBuf> {
-    let dir = PathBuf::from("/sys/class/power_supply");
-    fs::read_dir(&dir).ok()?.find_map(|entry| {
-        let path = entry.ok()?.path();
-        let type_str = fs::read_to_string(path.join("type")).ok()?;
-        if type_str.trim() == "Battery" {
-            Some(path)
-        } else {
-            None
+/// One entry under `/sys/class/power_supply`. Attributes a supply doesn't
+/// have stay zero or empty.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PowerSupply {
+    pub name: String, // BAT0, AC, ucsi-source-psy-USBC000:001, hid-<addr>-battery
+    pub kind: SupplyKind,
+    pub peripheral: bool,     // powers a device (mouse, keyboard, controller) rather than the system
+    pub online: Option<bool>, // adapters and wireless peripherals
+    pub usb_type: String,     // active USB charging mode, e.g. PD
+    pub capacity: Option<u32>,
+    pub capacity_level: String, // Normal, Low, Critical, Full; all some peripherals report
+    pub status: String,
+    pub energy_now: f64,  // Wh
+    pub energy_full: f64, // Wh
+    pub energy_full_design: f64,
+    pub power_now: f64, // W
+    pub cycle_count: u32,
+    pub technology: String,
+    pub manufacturer: String,
+    pub model_name: String,
+    #[serde(default)]
+    pub time_to_empty_now: Option<u64>, // seconds, where the driver estimates it
+    #[serde(default)]
+    pub time_to_full_now: Option<u64>,
+}
+
+impl PowerSupply {
+    /// Full capacity as a percentage of design capacity.
+    pub fn health(&self) -> Option<f32> {
+        (self.energy_full_design > 0.0).then(|| (self.energy_full / self.energy_full_design * 100.0) as f32)
+    }
+
+    fn is_system_battery(&self) -> bool {
+        self.kind == SupplyKind::Battery && !self.peripheral
+    }
+
+    /// "AC", "USB-PD", "UPS"; the name for batteries.
+    pub fn label(&self) -> String {
+        match self.kind {
+            SupplyKind::Mains => "AC".to_string(),
+            SupplyKind::Usb if self.usb_type.is_empty() => "USB".to_string(),
+            SupplyKind::Usb => format!("USB-{}", self.usb_type),
+            SupplyKind::Ups => "UPS".to_string(),
+            SupplyKind::Wireless => "WIRELESS".to_string(),
+            SupplyKind::Battery | SupplyKind::Other => self.name.clone(),
         }
-    })
+    }
+}
+
+fn read_sysfs(fs: &dyn SysFs, base: &Path, name: &str) -> Option<String> {
+    fs.read(&base.join(name))
+}
+
+fn read_u64(fs: &dyn SysFs, base: &Path, name: &str) -> Option<u64> {
+    read_sysfs(fs, base, name)?.parse().ok()
+}
+
+fn read_supplies(fs: &dyn SysFs) -> Vec<PowerSupply> {
+    let mut entries = fs.read_dir(Path::new("/sys/class/power_supply")).unwrap_or_default();
+    entries.sort();
+    entries.iter().map(|path| read_supply(fs, path)).collect()
+}
+
+fn read_supply(fs: &dyn SysFs, path: &Path) -> PowerSupply {
+    let name = sysfs::file_name(path);
+    let text = |attr: &str| read_sysfs(fs, path, attr).unwrap_or_else(|| "Unknown".to_string());
+    let voltage_now = read_u64(fs, path, "voltage_now").unwrap_or(0) as f64; // µV
+
+    // Energy values in µWh; fall back to charge (µAh) * voltage (µV) / 1e12
+    let energy = |which: &str| {
+        read_u64(fs, path, &format!("energy_{}", which))
+            .map(|v| v as f64 / 1_000_000.0)
+            .or_else(|| {
+                let c = read_u64(fs, path, &format!("charge_{}", which))? as f64;
+                Some(c * voltage_now / 1_000_000_000_000.0)
+            })
+            .unwrap_or(0.0)
+    };
+
+    PowerSupply {
+        kind: SupplyKind::from_type(&read_sysfs(fs, path, "type").unwrap_or_default()),
+        // Older kernels give HID batteries no scope
+        peripheral: read_sysfs(fs, path, "scope").as_deref() == Some("Device") || name.starts_with("hid-"),
+        online: read_u64(fs, path, "online").map(|o| o != 0),
+        // "C [PD] PD_PPS": the active mode is bracketed
+        usb_type: read_sysfs(fs, path, "usb_type")
+            .and_then(|t| Some(t.split_once('[')?.1.split_once(']')?.0.to_string()))
+            .unwrap_or_default(),
+        capacity: read_u64(fs, path, "capacity").map(|c| c as u32),
+        capacity_level: read_sysfs(fs, path, "capacity_level").unwrap_or_default(),
+        status: text("status"),
+        energy_now: energy("now"),
+        energy_full: energy("full"),
+        energy_full_design: energy("full_design"),
+        // Power in µW; fall back to current (µA) * voltage (µV) / 1e12
+        power_now: read_u64(fs, path, "power_now")
+            .map(|v| v as f64 / 1_000_000.0)
+            .or_else(|| {
+                let i = read_u64(fs, path, "current_now")? as f64;
+                Some(i * voltage_now / 1_000_000_000_000.0)
+            })
+            .unwrap_or(0.0),
+        cycle_count: read_u64(fs, path, "cycle_count").unwrap_or(0) as u32,
+        technology: text("technology"),
+        manufacturer: text("manufacturer"),
+        model_name: text("model_name"),
+        time_to_empty_now: read_u64(fs, path, "time_to_empty_now"),
+        time_to_full_now: read_u64(fs, path, "time_to_full_now"),
+        name,
+    }
+}
+
+/// Fits a line through the energy readings of the last `RATE_WINDOW`;
+/// its slope is the charge (positive) or discharge (negative) rate.
+#[derive(Clone)]
+struct RateEstimator {
+    readings: VecDeque<(Instant, f64)>, // (taken at, Wh)
+    status: String,                     // readings are dropped when this changes
+}
+
+impl RateEstimator {
+    fn new() -> Self {
+        Self { readings: VecDeque::new(), status: String::new() }
+    }
+
+    fn push(&mut self, at: Instant, status: &str, energy: f64) {
+        if status != self.status {
+            self.readings.clear();
+            self.status = status.to_string();
+        }
+        self.readings.push_back((at, energy));
+        while self.readings.front().is_some_and(|(t, _)| at.saturating_duration_since(*t) > RATE_WINDOW) {
+            self.readings.pop_front();
+        }
+    }
+
+    /// Least-squares slope in W, once the readings span `MIN_RATE_SPAN`.
+    fn rate(&self) -> Option<f64> {
+        let (first, _) = *self.readings.front()?;
+        let (last, _) = *self.readings.back()?;
+        if last.saturating_duration_since(first) < MIN_RATE_SPAN {
+            return None;
+        }
+        let n = self.readings.len() as f64;
+        let hours = |t: Instant| t.saturating_duration_since(first).as_secs_f64() / 3600.0;
+        let mean_t = self.readings.iter().map(|(t, _)| hours(*t)).sum::<f64>() / n;
+        let mean_e = self.readings.iter().map(|(_, e)| e).sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (t, e) in &self.readings {
+            let dt = hours(*t) - mean_t;
+            cov += dt * (e - mean_e);
+            var += dt * dt;
+        }
+        (var > 0.0).then(|| cov / var)
+    }
+}
+
+/// Seconds until empty while discharging, or until full while charging.
+/// `rate` is W, negative when discharging; `power_now` stands in for it
+/// before enough readings have been fitted, or while the fit still points
+/// the wrong way.
+fn estimate(
+    status: &str,
+    energy_now: f64,
+    energy_full: f64,
+    rate: Option<f64>,
+    power_now: f64,
+) -> (Option<u64>, Option<u64>) {
+    let secs = |wh: f64, w: f64| (w > 0.0 && wh >= 0.0).then(|| (wh / w * 3600.0).round() as u64);
+    match status {
+        "Discharging" => (secs(energy_now, rate.map(|r| -r).filter(|w| *w > 0.0).unwrap_or(power_now)), None),
+        "Charging" => (None, secs(energy_full - energy_now, rate.filter(|w| *w > 0.0).unwrap_or(power_now))),
+        _ => (None, None),
+    }
+}
+
+/// Status of several batteries as one: dual-battery laptops drain one at a
+/// time, leaving the other "Not charging".
+fn combined_status(batteries: &[&PowerSupply]) -> String {
+    let any = |status: &str| batteries.iter().any(|b| b.status == status);
+    if any("Discharging") {
+        "Discharging".to_string()
+    } else if any("Charging") {
+        "Charging".to_string()
+    } else if batteries.iter().all(|b| b.status == "Full") {
+        "Full".to_string()
+    } else {
+        batteries[0].status.clone()
+    }
+}
+
+/// Charge of several batteries as one, weighted by their full energy; a
+/// plain average when some report no energy.
+fn combined_capacity(batteries: &[&PowerSupply]) -> u32 {
+    if let [only] = batteries {
+        return only.capacity.unwrap_or(0);
+    }
+    let full: f64 = batteries.iter().map(|b| b.energy_full).sum();
+    if batteries.iter().all(|b| b.energy_full > 0.0) {
+        let now: f64 = batteries.iter().map(|b| b.energy_now).sum();
+        return (now / full * 100.0).round() as u32;
+    }
+    let sum: u32 = batteries.iter().map(|b| b.capacity.unwrap_or(0)).sum();
+    sum / batteries.len() as u32
 }
 
 impl BatteryInfo {
-    pub fn new() -> Self {
+    pub fn new(fs: Fs) -> Self {
         let mut info = Self {
             device: String::new(),
             capacity: 0,
@@ -56,71 +302,236 @@ impl BatteryInfo {
             manufacturer: "Unknown".to_string(),
             model_name: "Unknown".to_string(),
             available: false,
+            power_history: History::with_retention(HISTORY_RETENTION, REFRESH_INTERVAL),
+            supplies: Vec::new(),
+            energy_now: 0.0,
+            energy_full: 0.0,
+            time_to_empty: None,
+            time_to_full: None,
+            rate: RateEstimator::new(),
+            fs,
+            interval: REFRESH_INTERVAL,
+            retention: HISTORY_RETENTION,
             last_refresh: Instant::now() - REFRESH_INTERVAL,
         };
         info.refresh();
         info
     }
 
-    pub fn refresh_if_needed(&mut self) {
-        if self.last_refresh.elapsed() >= REFRESH_INTERVAL {
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self.restart_history();
+        self
+    }
+
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self.restart_history();
+        self
+    }
+
+    fn restart_history(&mut self) {
+        self.power_history = History::with_retention(self.retention, self.interval);
+        self.power_history.push(self.power_now as f32);
+    }
+
+    pub fn refresh_if_needed(&mut self) -> bool {
+        let due = self.last_refresh.elapsed() >= self.interval;
+        if due {
             self.refresh();
         }
+        due
     }
 
-    fn refresh(&mut self) {
-        self.last_refresh = Instant::now();
+    /// Batteries powering the system, not peripherals.
+    pub fn batteries(&self) -> impl Iterator<Item = &PowerSupply> {
+        self.supplies.iter().filter(|s| s.is_system_battery())
+    }
+
+    /// AC adapters, USB and wireless chargers and UPSes.
+    pub fn sources(&self) -> impl Iterator<Item = &PowerSupply> {
+        self.supplies.iter().filter(|s| s.kind != SupplyKind::Battery && !s.peripheral)
+    }
+
+    /// Batteries of mice, keyboards, controllers and headsets.
+    pub fn peripherals(&self) -> impl Iterator<Item = &PowerSupply> {
+        self.supplies.iter().filter(|s| s.peripheral)
+    }
 
-        let Some(path) = find_battery() else {
+    /// Read now, whether or not the interval has passed.
+    pub fn refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        let supplies = read_supplies(self.fs.as_ref());
+        let batteries: Vec<&PowerSupply> = supplies.iter().filter(|s| s.is_system_battery()).collect();
+        let Some(first) = batteries.first() else {
             self.available = false;
+            self.supplies = supplies;
             return;
         };
 
+        self.device = batteries.iter().map(|b| b.name.as_str()).collect::<Vec<_>>().join(" + ");
+        self.capacity = combined_capacity(&batteries);
+        self.status = combined_status(&batteries);
+        self.technology = first.technology.clone();
+        self.manufacturer = first.manufacturer.clone();
+        self.model_name = first.model_name.clone();
+        self.cycle_count = first.cycle_count;
+
+        self.energy_now = batteries.iter().map(|b| b.energy_now).sum();
+        self.energy_full = batteries.iter().map(|b| b.energy_full).sum();
+        self.energy_full_design = batteries.iter().map(|b| b.energy_full_design).sum();
+        if self.energy_full_design > 0.0 {
+            self.health = (self.energy_full / self.energy_full_design * 100.0) as f32;
+        }
+        self.power_now = batteries.iter().map(|b| b.power_now).sum();
+
+        self.rate.push(self.last_refresh, &self.status, self.energy_now);
+        let (to_empty, to_full) =
+            estimate(&self.status, self.energy_now, self.energy_full, self.rate.rate(), self.power_now);
+        // The driver's own estimate covers its battery only, and some
+        // drivers leave a stale or zero value for the other direction
+        let (kernel_empty, kernel_full) = match batteries.as_slice() {
+            [only] => (
+                only.time_to_empty_now.filter(|s| *s > 0 && self.status == "Discharging"),
+                only.time_to_full_now.filter(|s| *s > 0 && self.status == "Charging"),
+            ),
+            _ => (None, None),
+        };
+        self.time_to_empty = kernel_empty.or(to_empty);
+        self.time_to_full = kernel_full.or(to_full);
         self.available = true;
-        self.device = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        self.capacity = read_u64(&path, "capacity").unwrap_or(0) as u32;
-        self.status = read_sysfs(&path, "status").unwrap_or_else(|| "Unknown".to_string());
-        self.technology = read_sysfs(&path, "technology").unwrap_or_else(|| "Unknown".to_string());
-        self.manufacturer = read_sysfs(&path, "manufacturer").unwrap_or_else(|| "Unknown".to_string());
-        self.model_name = read_sysfs(&path, "model_name").unwrap_or_else(|| "Unknown".to_string());
-        self.cycle_count = read_u64(&path, "cycle_count").unwrap_or(0) as u32;
-
-        let voltage_now = read_u64(&path, "voltage_now").unwrap_or(0) as f64; // µV
-
-        // Energy values in µWh; fall back to charge (µAh) * voltage (µV) / 1e12
-        let energy_full = read_u64(&path, "energy_full")
-            .map(|v| v as f64 / 1_000_000.0)
-            .or_else(|| {
-                let c = read_u64(&path, "charge_full")? as f64;
-                Some(c * voltage_now / 1_000_000_000_000.0)
-            })
-            .unwrap_or(0.0);
+        self.supplies = supplies;
 
-        let energy_full_design = read_u64(&path, "energy_full_design")
-            .map(|v| v as f64 / 1_000_000.0)
-            .or_else(|| {
-                let c = read_u64(&path, "charge_full_design")? as f64;
-                Some(c * voltage_now / 1_000_000_000_000.0)
-            })
-            .unwrap_or(0.0);
+        self.power_history.push(self.power_now as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn reads_intel_laptop_battery() {
+        let bat = BatteryInfo::new(fixture("intel-laptop"));
+        assert!(bat.available);
+        assert_eq!(bat.device, "BAT0");
+        assert_eq!(bat.capacity, 76);
+        assert_eq!(bat.status, "Discharging");
+        assert_eq!(bat.cycle_count, 142);
+        assert!((bat.health - 90.0).abs() < 0.01);
+        assert!((bat.energy_full_design - 57.0).abs() < 1e-9);
+        assert!((bat.power_now - 8.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn history_spans_the_retention() {
+        let bat = BatteryInfo::new(fixture("intel-laptop"))
+            .with_retention(Duration::from_secs(600))
+            .with_interval(Duration::from_secs(5));
+        assert_eq!(bat.power_history.capacity(), 120);
+    }
+
+    #[test]
+    fn desktop_has_no_battery() {
+        let bat = BatteryInfo::new(fixture("desktop-no-battery"));
+        assert!(!bat.available);
+    }
+
+    #[test]
+    fn combines_batteries_and_lists_every_supply() {
+        let bat = BatteryInfo::new(fixture("dual-battery"));
+        assert_eq!(bat.device, "BAT0 + BAT1");
+        assert_eq!(bat.status, "Discharging", "BAT0 waits while BAT1 drains");
+        assert_eq!(bat.capacity, 56, "44.24 of 78.7 Wh");
+        assert!((bat.power_now - 8.88).abs() < 1e-9);
+        assert_eq!(bat.cycle_count, 311);
 
-        self.energy_full_design = energy_full_design;
+        let bat1 = bat.batteries().nth(1).unwrap();
+        assert!((bat1.energy_now - 22.2).abs() < 1e-9, "charge_now times voltage_now");
+        assert_eq!(bat1.capacity, Some(40));
 
-        if energy_full_design > 0.0 {
-            self.health = (energy_full / energy_full_design * 100.0) as f32;
+        let sources: Vec<(String, Option<bool>)> = bat.sources().map(|s| (s.label(), s.online)).collect();
+        assert_eq!(sources, [("AC".to_string(), Some(false)), ("USB-PD".to_string(), Some(false))]);
+
+        let peripherals: Vec<&PowerSupply> = bat.peripherals().collect();
+        assert_eq!(peripherals.len(), 2);
+        assert_eq!((peripherals[0].model_name.as_str(), peripherals[0].capacity), ("MX Master 3", Some(65)));
+        assert_eq!((peripherals[1].capacity, peripherals[1].capacity_level.as_str()), (None, "Low"));
+    }
+
+    /// Readings every `step` seconds from `start` Wh, changing by `watts`
+    /// (negative when discharging), with the fuel gauge's 0.1 Wh steps.
+    fn curve(rate: &mut RateEstimator, status: &str, start: f64, watts: f64, step: u64, count: u64) -> f64 {
+        let t0 = Instant::now();
+        let mut energy = start;
+        for i in 0..count {
+            let secs = i * step;
+            energy = ((start + watts * secs as f64 / 3600.0) * 10.0).floor() / 10.0;
+            rate.push(t0 + Duration::from_secs(secs), status, energy);
         }
+        energy
+    }
 
-        // Power in µW; fall back to current (µA) * voltage (µV) / 1e12
-        self.power_now = read_u64(&path, "power_now")
-            .map(|v| v as f64 / 1_000_000.0)
-            .or_else(|| {
-                let i = read_u64(&path, "current_now")? as f64;
-                Some(i * voltage_now / 1_000_000_000_000.0)
-            })
-            .unwrap_or(0.0);
+    #[test]
+    fn smooths_a_stepped_discharge_curve() {
+        let mut rate = RateEstimator::new();
+        let energy = curve(&mut rate, "Discharging", 40.0, -12.0, 5, 120);
+        let fitted = rate.rate().unwrap();
+        assert!((fitted + 12.0).abs() < 0.3, "fitted {} W", fitted);
+
+        // 38.1 Wh at about 12 W is a little over three hours, whatever power_now says
+        let (to_empty, to_full) = estimate("Discharging", energy, 50.0, Some(fitted), 30.0);
+        assert!((to_empty.unwrap() as f64 - energy / 12.0 * 3600.0).abs() < 300.0);
+        assert_eq!(to_full, None);
+        assert_eq!(rate.readings.len(), 61, "only the last five minutes are kept");
+    }
+
+    #[test]
+    fn estimates_time_to_full_and_restarts_on_status_change() {
+        let mut rate = RateEstimator::new();
+        curve(&mut rate, "Discharging", 40.0, -12.0, 5, 60);
+        let energy = curve(&mut rate, "Charging", 20.0, 45.0, 5, 24);
+        assert_eq!(rate.readings.len(), 24, "discharge readings are dropped");
+        let fitted = rate.rate().unwrap();
+        assert!((fitted - 45.0).abs() < 1.0, "fitted {} W", fitted);
+        let (_, to_full) = estimate("Charging", energy, 50.0, Some(fitted), 0.0);
+        assert!((to_full.unwrap() as f64 - (50.0 - energy) / 45.0 * 3600.0).abs() < 120.0);
+    }
+
+    #[test]
+    fn falls_back_to_power_now_and_the_kernel() {
+        let mut rate = RateEstimator::new();
+        curve(&mut rate, "Discharging", 40.0, -12.0, 5, 3);
+        assert_eq!(rate.rate(), None, "ten seconds is too short to fit");
+        assert_eq!(estimate("Discharging", 40.0, 50.0, None, 8.0), (Some(18000), None));
+        assert_eq!(estimate("Discharging", 40.0, 50.0, None, 0.0), (None, None));
+        assert_eq!(estimate("Full", 50.0, 50.0, Some(-1.0), 1.0), (None, None));
+        assert_eq!(estimate("Discharging", 40.0, 50.0, Some(0.5), 8.0), (Some(18000), None), "fit still rising");
+        assert_eq!(estimate("Charging", 40.0, 50.0, Some(-0.5), 10.0), (None, Some(3600)), "fit still falling");
+
+        let bat = BatteryInfo::new(fixture("intel-laptop"));
+        assert_eq!(bat.time_to_empty, Some(14400), "the driver's estimate wins over 39 Wh at 8.5 W");
+        let bat = BatteryInfo::new(fixture("dual-battery"));
+        assert_eq!(bat.supplies[2].time_to_empty_now, Some(9000));
+        assert_eq!(bat.time_to_empty, Some(17935), "two batteries: 44.24 Wh at 8.88 W");
+    }
+
+    #[test]
+    fn ignores_kernel_estimates_for_the_other_direction() {
+        // Charging, with a stale time_to_empty_now and a zero time_to_full_now
+        let bat = BatteryInfo::new(fixture("charging-laptop"));
+        assert_eq!(bat.status, "Charging");
+        assert_eq!(bat.supplies[1].time_to_empty_now, Some(5400));
+        assert_eq!(bat.time_to_empty, None);
+        assert_eq!(bat.time_to_full, Some(3600), "20 Wh to go at 20 W");
+    }
+
+    #[test]
+    fn single_battery_uses_its_own_capacity() {
+        let bat = BatteryInfo::new(fixture("intel-laptop"));
+        assert_eq!(bat.supplies.len(), 2);
+        assert_eq!(bat.sources().next().unwrap().online, Some(false));
+        assert_eq!(bat.peripherals().count(), 0);
     }
 }
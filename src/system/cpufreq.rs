@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::sysfs::{self, Fs, SysFs};
+
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+
+/// Frequency scaling and idle state of every core.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CpuFreq {
+    pub driver: String,   // intel_pstate, amd-pstate-epp, acpi-cpufreq
+    pub governor: String, // the one most cores use
+    pub epp: String,      // energy_performance_preference most cores use; empty without EPP
+    pub boost: Option<bool>,
+    pub cores: Vec<CoreFreq>, // by CPU number, online cores only
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CoreFreq {
+    pub cpu: usize,
+    pub mhz: Option<f32>,
+    pub governor: String,
+    pub epp: String,
+    pub idle: Vec<IdleState>,
+}
+
+/// One cpuidle state and the share of the last refresh spent in it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IdleState {
+    pub name: String, // POLL, C1, C6
+    pub residency: f32,
+}
+
+impl CoreFreq {
+    /// The idle state the core spent most time in, if it idled at all.
+    pub fn dominant_idle(&self) -> Option<&IdleState> {
+        self.idle
+            .iter()
+            .filter(|s| s.residency > 0.0)
+            .max_by(|a, b| a.residency.total_cmp(&b.residency))
+    }
+}
+
+/// Samples `cpufreq` and `cpuidle` under `/sys/devices/system/cpu`;
+/// residency is the growth of each state's `time` between refreshes.
+pub struct CpuFreqSampler {
+    fs: Fs,
+    idle_us: HashMap<(usize, String), u64>, // (cpu, stateN) -> `time` at the last refresh
+    last: Option<Instant>,
+    freq: CpuFreq,
+}
+
+impl CpuFreqSampler {
+    pub fn new(fs: Fs) -> Self {
+        Self {
+            fs,
+            idle_us: HashMap::new(),
+            last: None,
+            freq: CpuFreq::default(),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let secs = self.last.map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f64());
+        let fs = self.fs.as_ref();
+        let (cores, idle_us) = read_cores(fs, &self.idle_us, secs);
+        let dir = Path::new(CPU_DIR);
+        let boost = fs
+            .read_u64(&dir.join("cpufreq/boost"))
+            .map(|b| b == 1)
+            // intel_pstate has the inverse switch
+            .or_else(|| fs.read_u64(&dir.join("intel_pstate/no_turbo")).map(|n| n == 0));
+
+        self.freq = CpuFreq {
+            driver: fs.read(&dir.join("cpu0/cpufreq/scaling_driver")).unwrap_or_default(),
+            governor: most_common(cores.iter().map(|c| &c.governor)),
+            epp: most_common(cores.iter().map(|c| &c.epp)),
+            boost,
+            cores,
+        };
+        self.idle_us = idle_us;
+        self.last = Some(now);
+    }
+
+    pub fn freq(&self) -> &CpuFreq {
+        &self.freq
+    }
+}
+
+/// Every online core's readings, and the idle counters to diff against
+/// next time. Residency stays zero when `secs` is.
+fn read_cores(
+    fs: &dyn SysFs,
+    prev: &HashMap<(usize, String), u64>,
+    secs: f64,
+) -> (Vec<CoreFreq>, HashMap<(usize, String), u64>) {
+    let mut cpus: Vec<(usize, std::path::PathBuf)> = fs
+        .read_dir(Path::new(CPU_DIR))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|p| Some((sysfs::file_name(&p).strip_prefix("cpu")?.parse().ok()?, p)))
+        .collect();
+    cpus.sort_by_key(|(n, _)| *n);
+
+    let mut idle_us = HashMap::new();
+    let mut cores = Vec::new();
+    for (cpu, dir) in cpus {
+        // Offline cores have neither
+        let freq = dir.join("cpufreq");
+        let mut states = fs.read_dir(&dir.join("cpuidle")).unwrap_or_default();
+        if !fs.exists(&freq) && states.is_empty() {
+            continue;
+        }
+        states.sort_by_key(|s| sysfs::file_name(s).trim_start_matches("state").parse::<u32>().unwrap_or(u32::MAX));
+
+        let mut idle = Vec::new();
+        for state in states {
+            let Some(time) = fs.read_u64(&state.join("time")) else { continue };
+            let key = (cpu, sysfs::file_name(&state));
+            let residency = match prev.get(&key) {
+                Some(&old) if secs > 0.0 => (time.saturating_sub(old) as f64 / (secs * 1e6)).min(1.0) as f32,
+                _ => 0.0,
+            };
+            idle.push(IdleState { name: fs.read(&state.join("name")).unwrap_or_else(|| key.1.clone()), residency });
+            idle_us.insert(key, time);
+        }
+
+        cores.push(CoreFreq {
+            cpu,
+            mhz: fs.read_u64(&freq.join("scaling_cur_freq")).map(|khz| khz as f32 / 1000.0),
+            governor: fs.read(&freq.join("scaling_governor")).unwrap_or_default(),
+            epp: fs.read(&freq.join("energy_performance_preference")).unwrap_or_default(),
+            idle,
+        });
+    }
+    (cores, idle_us)
+}
+
+/// The most frequent non-empty value, or empty.
+fn most_common<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    let mut counts: HashMap<&String, usize> = HashMap::new();
+    for v in values.filter(|v| !v.is_empty()) {
+        *counts.entry(v).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(v, _)| v.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn reads_scaling_and_idle_state() {
+        let fs = fixture("intel-laptop");
+        let mut sampler = CpuFreqSampler::new(fs.clone());
+        sampler.refresh();
+        let freq = sampler.freq();
+        assert_eq!(freq.driver, "intel_pstate");
+        assert_eq!((freq.governor.as_str(), freq.epp.as_str()), ("powersave", "balance_performance"));
+        assert_eq!(freq.boost, Some(true));
+        assert_eq!(freq.cores.len(), 3, "cores without cpufreq or cpuidle are offline");
+        assert_eq!(freq.cores[2].governor, "performance", "outvoted by the other two");
+        assert_eq!(freq.cores[1].mhz, Some(3412.5));
+        assert_eq!(freq.cores[1].epp, "power");
+        let names: Vec<&str> = freq.cores[0].idle.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["POLL", "C1", "C6", "C10"], "state10 sorts last");
+
+        // Two seconds in which cpu0 sat in C6 for 1.5 s
+        let (_, mut prev) = read_cores(fs.as_ref(), &HashMap::new(), 0.0);
+        *prev.get_mut(&(0, "state2".to_string())).unwrap() -= 1_500_000;
+        let (cores, _) = read_cores(fs.as_ref(), &prev, 2.0);
+        let c6 = cores[0].dominant_idle().unwrap();
+        assert_eq!((c6.name.as_str(), c6.residency), ("C6", 0.75));
+        assert!(cores[1].dominant_idle().is_none());
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::history::History;
+use super::process::ProcessInfo;
+use super::sysfs::{self, Fs, SysFs};
+
+/// Open descriptors listed individually; the count covers all of them.
+const MAX_LISTED_FDS: usize = 512;
+
+/// Everything `/proc/<pid>` reveals about one process, for the detail view.
+/// Fields that need ptrace access to another user's process are `None` when
+/// the kernel refuses the read.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProcessDetail {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cmdline: Vec<String>,
+    pub exe: Option<String>,
+    pub cwd: Option<String>,
+    // Often holds tokens and keys, so it never reaches a recording
+    #[serde(skip_serializing, default)]
+    pub environ: Option<Vec<String>>,
+    pub start_time: Option<i64>, // Unix seconds
+    start_ticks: u64,            // identifies this process if the PID is reused
+    pub state: String,
+    pub threads: u32,
+    pub fd_count: Option<usize>,
+    pub fds: Vec<(u32, String)>, // fd -> target, first MAX_LISTED_FDS
+    pub memory: Option<MemoryRollup>,
+    pub cgroup: String,
+    pub namespaces: Option<Vec<(String, String)>>, // kind -> inode, e.g. ("net", "4026531840")
+    pub io: Option<IoCounters>,
+    pub cpu_usage: f32,
+    pub resident: u64,
+    pub cpu_history: History, // percent
+    pub mem_history: History, // bytes
+}
+
+/// Totals from `smaps_rollup`, in bytes.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MemoryRollup {
+    pub rss: u64,
+    pub pss: u64,
+    pub uss: u64, // Private_Clean + Private_Dirty
+    pub swap: u64,
+}
+
+/// Cumulative counters from `/proc/<pid>/io`.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct IoCounters {
+    pub rchar: u64,
+    pub wchar: u64,
+    pub syscr: u64,
+    pub syscw: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub cancelled_write_bytes: u64,
+}
+
+/// Re-reads the selected process on every system refresh and keeps its
+/// CPU and memory history while the same process stays selected.
+pub struct ProcessInspector {
+    fs: Fs,
+    interval: Duration,
+    retention: Duration,
+    detail: Option<ProcessDetail>,
+}
+
+impl ProcessDetail {
+    /// Both readings are of one process, not of two that shared a PID.
+    pub fn is_same_process(&self, other: &ProcessDetail) -> bool {
+        self.pid == other.pid && self.start_ticks == other.start_ticks
+    }
+}
+
+impl ProcessInspector {
+    pub fn new(fs: Fs, interval: Duration, retention: Duration) -> Self {
+        Self { fs, interval, retention, detail: None }
+    }
+
+    pub fn detail(&self) -> Option<&ProcessDetail> {
+        self.detail.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        self.detail = None;
+    }
+
+    /// Read `pid`; `procs` supplies the CPU and memory figures sysinfo
+    /// already computed. Leaves `None` once the process has exited.
+    pub fn inspect(&mut self, pid: u32, procs: &[ProcessInfo]) {
+        let Some(mut detail) = read_detail(self.fs.as_ref(), pid) else {
+            self.detail = None;
+            return;
+        };
+        if let Some(info) = procs.iter().find(|p| p.pid == pid) {
+            detail.user = info.user.clone();
+            detail.cpu_usage = info.cpu_usage;
+            detail.resident = info.memory;
+        }
+        match self.detail.take() {
+            Some(prev) if prev.is_same_process(&detail) => {
+                detail.cpu_history = prev.cpu_history;
+                detail.mem_history = prev.mem_history;
+            }
+            _ => {
+                detail.cpu_history = History::with_retention(self.retention, self.interval);
+                detail.mem_history = History::with_retention(self.retention, self.interval);
+            }
+        }
+        detail.cpu_history.push(detail.cpu_usage);
+        detail.mem_history.push(detail.resident as f32);
+        self.detail = Some(detail);
+    }
+}
+
+fn read_detail(fs: &dyn SysFs, pid: u32) -> Option<ProcessDetail> {
+    let dir = PathBuf::from(format!("/proc/{}", pid));
+    let stat = fs.read_to_string(&dir.join("stat")).ok()?;
+    let (name, start_ticks) = parse_stat(&stat)?;
+    let status = fs.read_to_string(&dir.join("status")).unwrap_or_default();
+    let field = |key: &str| {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(':'))
+            .map(|v| v.trim().to_string())
+    };
+
+    let fd_entries = read_fds(fs, &dir);
+    Some(ProcessDetail {
+        pid,
+        name,
+        user: "?".to_string(),
+        cmdline: read_nul_list(fs, &dir.join("cmdline")).unwrap_or_default(),
+        exe: read_link(fs, &dir.join("exe")),
+        cwd: read_link(fs, &dir.join("cwd")),
+        environ: read_nul_list(fs, &dir.join("environ")),
+        start_time: boot_time(fs).map(|btime| btime + (start_ticks / clock_ticks()) as i64),
+        start_ticks,
+        state: field("State").unwrap_or_else(|| "?".to_string()),
+        threads: field("Threads").and_then(|t| t.parse().ok()).unwrap_or(0),
+        fd_count: fd_entries.as_ref().map(|fds| fds.len()),
+        fds: fd_entries.unwrap_or_default().into_iter().take(MAX_LISTED_FDS).collect(),
+        memory: fs.read_to_string(&dir.join("smaps_rollup")).ok().map(|t| parse_smaps_rollup(&t)),
+        cgroup: fs
+            .read_to_string(&dir.join("cgroup"))
+            .map(|t| parse_cgroup(&t))
+            .unwrap_or_default(),
+        namespaces: read_namespaces(fs, &dir),
+        io: fs.read_to_string(&dir.join("io")).ok().map(|t| parse_io(&t)),
+        cpu_usage: 0.0,
+        resident: 0,
+        cpu_history: History::new(2),
+        mem_history: History::new(2),
+    })
+}
+
+/// `comm` and `starttime` (field 22, clock ticks after boot). The name is
+/// parenthesised and may itself contain spaces or parentheses.
+fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    // Fields after the name start at 3 (state)
+    let start = stat[close + 1..].split_whitespace().nth(22 - 3)?.parse().ok()?;
+    Some((name, start))
+}
+
+fn parse_smaps_rollup(text: &str) -> MemoryRollup {
+    let mut m = MemoryRollup::default();
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(kb)) = (parts.next(), parts.next().and_then(|v| v.parse::<u64>().ok())) else {
+            continue;
+        };
+        let bytes = kb * 1024;
+        match key {
+            "Rss:" => m.rss = bytes,
+            "Pss:" => m.pss = bytes,
+            "Private_Clean:" | "Private_Dirty:" => m.uss += bytes,
+            "Swap:" => m.swap = bytes,
+            _ => {}
+        }
+    }
+    m
+}
+
+fn parse_io(text: &str) -> IoCounters {
+    let mut io = IoCounters::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value = value.trim().parse().unwrap_or(0);
+        match key {
+            "rchar" => io.rchar = value,
+            "wchar" => io.wchar = value,
+            "syscr" => io.syscr = value,
+            "syscw" => io.syscw = value,
+            "read_bytes" => io.read_bytes = value,
+            "write_bytes" => io.write_bytes = value,
+            "cancelled_write_bytes" => io.cancelled_write_bytes = value,
+            _ => {}
+        }
+    }
+    io
+}
+
+/// The unified (v2) path, or `controllers:path` pairs on a v1 hierarchy.
+pub(super) fn parse_cgroup(text: &str) -> String {
+    if let Some(unified) = text.lines().find_map(|l| l.strip_prefix("0::")) {
+        return unified.to_string();
+    }
+    text.lines()
+        .filter_map(|l| l.split_once(':').map(|(_, rest)| rest))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn read_nul_list(fs: &dyn SysFs, path: &Path) -> Option<Vec<String>> {
+    let text = fs.read_to_string(path).ok()?;
+    Some(text.split('\0').filter(|s| !s.is_empty()).map(str::to_string).collect())
+}
+
+fn read_link(fs: &dyn SysFs, path: &Path) -> Option<String> {
+    fs.read_link(path).ok().map(|p| p.to_string_lossy().to_string())
+}
+
+fn read_fds(fs: &dyn SysFs, dir: &Path) -> Option<Vec<(u32, String)>> {
+    let mut fds: Vec<(u32, String)> = fs
+        .read_dir(&dir.join("fd"))
+        .ok()?
+        .into_iter()
+        .filter_map(|path| {
+            let fd = sysfs::file_name(&path).parse().ok()?;
+            Some((fd, read_link(fs, &path).unwrap_or_default()))
+        })
+        .collect();
+    fds.sort_by_key(|(fd, _)| *fd);
+    Some(fds)
+}
+
+fn read_namespaces(fs: &dyn SysFs, dir: &Path) -> Option<Vec<(String, String)>> {
+    let mut namespaces: Vec<(String, String)> = fs
+        .read_dir(&dir.join("ns"))
+        .ok()?
+        .into_iter()
+        .filter_map(|path| {
+            // Links read as `net:[4026531840]`
+            let target = read_link(fs, &path)?;
+            let inode = target.split_once('[')?.1.trim_end_matches(']').to_string();
+            Some((sysfs::file_name(&path), inode))
+        })
+        .collect();
+    namespaces.sort();
+    Some(namespaces)
+}
+
+fn boot_time(fs: &dyn SysFs) -> Option<i64> {
+    let stat = fs.read_to_string(Path::new("/proc/stat")).ok()?;
+    stat.lines().find_map(|l| l.strip_prefix("btime "))?.trim().parse().ok()
+}
+
+fn clock_ticks() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 { ticks as u64 } else { 100 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::history::HISTORY_RETENTION;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn reads_process_from_procfs() {
+        let fs = fixture("process");
+        let mut inspector = ProcessInspector::new(fs, Duration::from_secs(1), HISTORY_RETENTION);
+        inspector.inspect(4242, &[]);
+        let d = inspector.detail().unwrap();
+
+        assert_eq!(d.name, "warp core");
+        assert_eq!(d.cmdline, ["/usr/bin/warp-core", "--antimatter", "--dilithium=crystal"]);
+        assert_eq!(d.exe.as_deref(), Some("/usr/bin/warp-core"));
+        assert_eq!(d.cwd.as_deref(), Some("/home/kirk"));
+        assert_eq!(d.environ.as_ref().unwrap()[1], "SHIP=NCC-1701");
+        assert_eq!(d.start_time, Some(1760659200 + 360000 / clock_ticks() as i64));
+        assert_eq!(d.state, "S (sleeping)");
+        assert_eq!(d.threads, 12);
+        assert_eq!(d.fd_count, Some(3));
+        assert_eq!(d.fds[2], (10, "socket:[47]".to_string()));
+        let m = d.memory.unwrap();
+        assert_eq!((m.rss, m.pss, m.uss, m.swap), (40960 * 1024, 30720 * 1024, 28672 * 1024, 1024 * 1024));
+        assert_eq!(d.cgroup, "/user.slice/user-1000.slice/session-2.scope");
+        assert_eq!(d.namespaces.as_ref().unwrap()[0], ("net".to_string(), "4026531840".to_string()));
+        assert_eq!(d.io.unwrap().write_bytes, 32768000);
+
+        let json = serde_json::to_value(d).unwrap();
+        assert!(json.get("environ").is_none(), "the environment is not recorded");
+
+        inspector.inspect(4242, &[]);
+        assert_eq!(inspector.detail().unwrap().cpu_history.values().count(), 2, "history kept for the same process");
+        inspector.inspect(9999, &[]);
+        assert!(inspector.detail().is_none());
+    }
+
+    #[test]
+    fn parses_awkward_stat_names() {
+        let stat = "77 (a) b (c)) R 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 4242 0";
+        assert_eq!(parse_stat(stat), Some(("a) b (c)".to_string(), 4242)));
+        assert_eq!(parse_cgroup("12:cpu,cpuacct:/system.slice\n3:memory:/system.slice\n"), "cpu,cpuacct:/system.slice memory:/system.slice");
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::sysfs::{self, Fs, SysFs};
+
+/// `/proc/diskstats` counts 512-byte sectors whatever the device's block size.
+const SECTOR: f64 = 512.0;
+
+/// I/O activity of one physical disk over the last refresh.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BlockDevice {
+    pub name: String,        // sda, nvme0n1
+    pub mounts: Vec<String>, // filesystems stored on it, through partitions and device-mapper
+    pub read_rate: f64,      // bytes/s
+    pub write_rate: f64,     // bytes/s
+    pub read_iops: f64,
+    pub write_iops: f64,
+    pub queue_depth: f64, // average requests in flight
+    pub busy: f32,        // fraction of the time with any request in flight
+}
+
+/// Turns the cumulative counters in `/proc/diskstats` into per-disk rates.
+/// Only whole physical disks (those with a `device` in `/sys/block`) are
+/// listed; partitions, LVM and LUKS volumes are accounted to the disks
+/// beneath them, and loop and zram devices are left out.
+pub struct BlockIoSampler {
+    fs: Fs,
+    prev: HashMap<String, DiskStat>,
+    last: Option<Instant>,
+    devices: Vec<BlockDevice>,
+    disk_of_mount: HashMap<String, String>,
+}
+
+/// The `/proc/diskstats` fields used, all cumulative since boot.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct DiskStat {
+    reads: u64,
+    sectors_read: u64,
+    writes: u64,
+    sectors_written: u64,
+    io_ms: u64,    // time with requests in flight
+    queue_ms: u64, // in-flight time summed over requests
+}
+
+impl BlockIoSampler {
+    pub fn new(fs: Fs) -> Self {
+        Self {
+            fs,
+            prev: HashMap::new(),
+            last: None,
+            devices: Vec::new(),
+            disk_of_mount: HashMap::new(),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let secs = self.last.map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f64());
+        let stats = self
+            .fs
+            .read_to_string(Path::new("/proc/diskstats"))
+            .map(|s| parse_diskstats(&s))
+            .unwrap_or_default();
+
+        let mounts = mounts_by_disk(self.fs.as_ref());
+        self.disk_of_mount = mounts
+            .iter()
+            .flat_map(|(disk, mounts)| mounts.iter().map(move |m| (m.clone(), disk.clone())))
+            .collect();
+
+        let fs = self.fs.as_ref();
+        let mut devices: Vec<BlockDevice> = stats
+            .iter()
+            .filter(|(name, _)| fs.exists(&Path::new("/sys/block").join(name).join("device")))
+            .map(|(name, stat)| {
+                // Devices that appear mid-run start from their first reading
+                let old = self.prev.get(name).copied().unwrap_or(*stat);
+                BlockDevice {
+                    mounts: mounts.get(name).cloned().unwrap_or_default(),
+                    ..rates(name, &old, stat, secs)
+                }
+            })
+            .collect();
+        devices.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.devices = devices;
+        self.prev = stats;
+        self.last = Some(now);
+    }
+
+    pub fn devices(&self) -> &[BlockDevice] {
+        &self.devices
+    }
+
+    /// The disk holding the filesystem mounted at `mount`; the first one
+    /// for filesystems spanning several.
+    pub fn disk_of(&self, mount: &str) -> Option<&str> {
+        self.disk_of_mount.get(mount).map(String::as_str)
+    }
+}
+
+fn parse_diskstats(content: &str) -> HashMap<String, DiskStat> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            // major minor name, then at least the 11 fields every kernel since 2.6 has
+            if f.len() < 14 {
+                return None;
+            }
+            let n = |i: usize| f[i].parse::<u64>().unwrap_or(0);
+            let stat = DiskStat {
+                reads: n(3),
+                sectors_read: n(5),
+                writes: n(7),
+                sectors_written: n(9),
+                io_ms: n(12),
+                queue_ms: n(13),
+            };
+            Some((f[2].to_string(), stat))
+        })
+        .collect()
+}
+
+fn rates(name: &str, old: &DiskStat, new: &DiskStat, secs: f64) -> BlockDevice {
+    if secs <= 0.0 {
+        return BlockDevice { name: name.to_string(), ..Default::default() };
+    }
+    let per_sec = |new: u64, old: u64| new.saturating_sub(old) as f64 / secs;
+    let ms = secs * 1000.0;
+    BlockDevice {
+        name: name.to_string(),
+        mounts: Vec::new(),
+        read_rate: per_sec(new.sectors_read, old.sectors_read) * SECTOR,
+        write_rate: per_sec(new.sectors_written, old.sectors_written) * SECTOR,
+        read_iops: per_sec(new.reads, old.reads),
+        write_iops: per_sec(new.writes, old.writes),
+        queue_depth: new.queue_ms.saturating_sub(old.queue_ms) as f64 / ms,
+        busy: (new.io_ms.saturating_sub(old.io_ms) as f64 / ms).min(1.0) as f32,
+    }
+}
+
+/// Disk name -> mount points of whole filesystems on it (bind mounts of a
+/// subdirectory are skipped), from `/proc/self/mountinfo`.
+fn mounts_by_disk(fs: &dyn SysFs) -> HashMap<String, Vec<String>> {
+    let mut by_disk: HashMap<String, Vec<String>> = HashMap::new();
+    let Ok(content) = fs.read_to_string(Path::new("/proc/self/mountinfo")) else {
+        return by_disk;
+    };
+    for line in content.lines() {
+        // id parent major:minor root mount-point options... - type source super-options
+        let f: Vec<&str> = line.split_whitespace().collect();
+        let source = f.iter().position(|f| *f == "-").and_then(|i| f.get(i + 2));
+        let (Some(dev), Some(&"/"), Some(mount)) = (f.get(2), f.get(3), f.get(4)) else {
+            continue;
+        };
+        // btrfs reports an anonymous device number, so fall back to the source
+        let name = match fs.read_link(&Path::new("/sys/dev/block").join(dev)) {
+            Ok(target) => sysfs::file_name(&target),
+            Err(_) => match source.filter(|s| s.starts_with("/dev/")) {
+                Some(source) => sysfs::file_name(&fs.read_link(Path::new(source)).unwrap_or_else(|_| source.into())),
+                None => continue,
+            },
+        };
+        let mut disks = Vec::new();
+        whole_disks(fs, &name, &mut disks);
+        for disk in disks {
+            let mounts = by_disk.entry(disk).or_default();
+            let mount = unescape(mount);
+            if !mounts.contains(&mount) {
+                mounts.push(mount);
+            }
+        }
+    }
+    by_disk
+}
+
+/// The disks under block device `name`: a partition's parent, the slaves of
+/// a device-mapper or md volume (recursively), or the device itself.
+fn whole_disks(fs: &dyn SysFs, name: &str, out: &mut Vec<String>) {
+    let class = Path::new("/sys/class/block").join(name);
+    if fs.exists(&class.join("partition")) {
+        // A partition's directory sits inside its disk's
+        if let Some(disk) = fs.read_link(&class).ok().and_then(|t| t.parent().map(sysfs::file_name)) {
+            whole_disks(fs, &disk, out);
+        }
+        return;
+    }
+    let slaves = fs.read_dir(&Path::new("/sys/block").join(name).join("slaves")).unwrap_or_default();
+    if slaves.is_empty() && !out.iter().any(|d| d == name) {
+        out.push(name.to_string());
+    }
+    for slave in slaves {
+        whole_disks(fs, &sysfs::file_name(&slave), out);
+    }
+}
+
+/// mountinfo escapes space, tab, newline and backslash as octal.
+fn unescape(field: &str) -> String {
+    let mut out = String::new();
+    let mut rest = field;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        match rest.get(i + 1..i + 4).and_then(|o| u8::from_str_radix(o, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn computes_rates_between_readings() {
+        let stats = parse_diskstats(
+            "   8       0 sda 100 5 2000 40 50 3 4000 60 0 70 100\n 259       0 nvme0n1 1 0 8 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n",
+        );
+        let old = stats["sda"];
+        assert_eq!(old, DiskStat { reads: 100, sectors_read: 2000, writes: 50, sectors_written: 4000, io_ms: 70, queue_ms: 100 });
+        let new = DiskStat { reads: 300, sectors_read: 6000, writes: 150, sectors_written: 4000, io_ms: 570, queue_ms: 1600 };
+
+        let d = rates("sda", &old, &new, 2.0);
+        assert_eq!(d.read_rate, 1024000.0, "4000 sectors over 2 s");
+        assert_eq!(d.write_rate, 0.0);
+        assert_eq!((d.read_iops, d.write_iops), (100.0, 50.0));
+        assert_eq!(d.queue_depth, 0.75);
+        assert_eq!(d.busy, 0.25);
+        assert_eq!(rates("sda", &new, &old, 2.0).read_rate, 0.0, "counters going backwards read as idle");
+    }
+
+    #[test]
+    fn maps_mounts_to_physical_disks() {
+        let fs = fixture("diskio");
+        let by_disk = mounts_by_disk(fs.as_ref());
+        assert_eq!(by_disk["nvme0n1"], ["/"], "bind mount of a subdirectory is skipped");
+        assert_eq!(by_disk["sda"], ["/home", "/data archive"], "through dm-0, and btrfs by source");
+        assert_eq!(by_disk.len(), 2);
+
+        let mut sampler = BlockIoSampler::new(fs);
+        sampler.refresh();
+        let names: Vec<&str> = sampler.devices().iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["nvme0n1", "sda"], "no partitions, dm, loop or zram");
+        assert_eq!(sampler.disk_of("/home"), Some("sda"));
+        assert_eq!(sampler.devices()[0].busy, 0.0, "no rates from a single reading");
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::openfds::OpenFds;
+use super::sysfs::{Fs, SysFs};
+
+/// Per-process GPU usage derived from DRM fdinfo.
+#[derive(Clone, Copy, Default)]
+pub struct GpuProcUsage {
+    pub usage: f32,   // busiest engine, 0–100
+    pub memory: u64,  // bytes
+}
+
+/// One DRM client as reported by a single `/proc/<pid>/fdinfo/<fd>` file.
+#[derive(Clone, Default)]
+struct DrmClient {
+    engines: HashMap<String, u64>, // engine name -> busy ns
+    capacity: HashMap<String, u64>,
+    memory: u64,
+}
+
+/// Clients are shared between fds (dup, fork), so key by device + client id.
+type ClientKey = (String, u64);
+
+/// Client id -> engine name -> busy ns, for the clients of one device.
+pub type ClientEngines = HashMap<u64, HashMap<String, u64>>;
+
+/// Every DRM client seen by the latest refresh, shared with the GPU backends
+/// that derive device-wide busy time from fdinfo.
+#[derive(Default)]
+pub struct DrmClients {
+    taken: Option<Instant>,
+    clients: HashMap<ClientKey, DrmClient>,
+}
+
+impl DrmClients {
+    /// When these clients were read; `None` before the first refresh.
+    pub fn taken(&self) -> Option<Instant> {
+        self.taken
+    }
+
+    /// Engine busy time of each client of one device (`drm-pdev`) by client
+    /// id, scaled by engine capacity. Diff the clients of two readings one by
+    /// one: a client that exits takes its total with it.
+    pub fn device_clients(&self, pdev: &str) -> ClientEngines {
+        self.clients
+            .iter()
+            .filter(|((client_pdev, _), _)| client_pdev == pdev)
+            .map(|((_, id), client)| {
+                let engines = client
+                    .engines
+                    .iter()
+                    .map(|(name, ns)| (name.clone(), ns / client.capacity.get(name).copied().unwrap_or(1).max(1)))
+                    .collect();
+                (*id, engines)
+            })
+            .collect()
+    }
+
+    /// Clients of `(pdev, id, engine, busy ns)`, each engine with a capacity of one.
+    #[cfg(test)]
+    pub fn from_engines(taken: Instant, engines: &[(&str, u64, &str, u64)]) -> Self {
+        let mut clients: HashMap<ClientKey, DrmClient> = HashMap::new();
+        for (pdev, id, name, ns) in engines {
+            let client = clients.entry((pdev.to_string(), *id)).or_default();
+            client.engines.insert(name.to_string(), *ns);
+        }
+        Self { taken: Some(taken), clients }
+    }
+}
+
+/// Samples `drm-engine-*` / `drm-memory-*` from fdinfo and diffs engine time between refreshes.
+pub struct GpuProcessSampler {
+    fs: Fs,
+    prev: HashMap<u32, (Instant, HashMap<String, u64>)>,
+    usage: HashMap<u32, GpuProcUsage>,
+    clients: Arc<Mutex<DrmClients>>,
+}
+
+impl GpuProcessSampler {
+    pub fn new(fs: Fs) -> Self {
+        Self {
+            fs,
+            prev: HashMap::new(),
+            usage: HashMap::new(),
+            clients: Arc::default(),
+        }
+    }
+
+    /// Publish each refresh's clients to `clients` from now on.
+    pub fn share_clients(&mut self, clients: Arc<Mutex<DrmClients>>) {
+        self.clients = clients;
+    }
+
+    /// Read the fdinfo of the DRM candidates in `fds`.
+    pub fn refresh(&mut self, fds: &OpenFds) {
+        // Only the host's /proc matches the host process list these join; a
+        // sysroot's clients still count toward its devices
+        let per_process = self.fs.is_host();
+        let fs = self.fs.clone();
+        self.sample(fs.as_ref(), fds, Instant::now(), per_process);
+    }
+
+    pub fn get(&self, pid: u32) -> GpuProcUsage {
+        self.usage.get(&pid).copied().unwrap_or_default()
+    }
+
+    fn sample(&mut self, fs: &dyn SysFs, fds: &OpenFds, now: Instant, per_process: bool) {
+        let mut prev = HashMap::new();
+        let mut usage = HashMap::new();
+        let mut all = HashMap::new();
+
+        for (pid, fdinfos) in fds.drm() {
+            let clients = read_process_clients(fs, fdinfos);
+            if clients.is_empty() || !per_process {
+                all.extend(clients);
+                continue;
+            }
+
+            let mut engines: HashMap<String, u64> = HashMap::new();
+            let mut capacity: HashMap<String, u64> = HashMap::new();
+            let mut memory = 0;
+            for client in clients.values() {
+                for (name, ns) in &client.engines {
+                    *engines.entry(name.clone()).or_default() += ns;
+                }
+                for (name, cap) in &client.capacity {
+                    let c = capacity.entry(name.clone()).or_default();
+                    *c = (*c).max(*cap);
+                }
+                memory += client.memory;
+            }
+            all.extend(clients);
+
+            let mut busiest = 0.0_f32;
+            if let Some((then, old)) = self.prev.get(&pid) {
+                let wall_ns = now.saturating_duration_since(*then).as_nanos() as f64;
+                if wall_ns > 0.0 {
+                    for (name, ns) in &engines {
+                        let delta = ns.saturating_sub(old.get(name).copied().unwrap_or(*ns));
+                        let cap = capacity.get(name).copied().unwrap_or(1).max(1) as f64;
+                        let pct = (delta as f64 / wall_ns / cap * 100.0) as f32;
+                        busiest = busiest.max(pct);
+                    }
+                }
+            }
+
+            usage.insert(pid, GpuProcUsage { usage: busiest.min(100.0), memory });
+            prev.insert(pid, (now, engines));
+        }
+
+        self.prev = prev;
+        self.usage = usage;
+        if let Ok(mut shared) = self.clients.lock() {
+            *shared = DrmClients { taken: Some(now), clients: all };
+        }
+    }
+}
+
+// ── fdinfo parsing ───────────────────────────────────────────────────────────
+
+fn read_process_clients(fs: &dyn SysFs, fdinfos: &[PathBuf]) -> HashMap<ClientKey, DrmClient> {
+    let mut clients = HashMap::new();
+    for fdinfo in fdinfos {
+        let Ok(content) = fs.read_to_string(fdinfo) else {
+            continue;
+        };
+        if let Some((key, client)) = parse_fdinfo(&content) {
+            clients.insert(key, client);
+        }
+    }
+    clients
+}
+
+fn parse_fdinfo(content: &str) -> Option<(ClientKey, DrmClient)> {
+    let mut is_drm = false;
+    let mut pdev = String::new();
+    let mut client_id = None;
+    let mut client = DrmClient::default();
+    let mut resident = 0;
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if key == "drm-driver" {
+            is_drm = true;
+        } else if key == "drm-pdev" {
+            pdev = value.to_string();
+        } else if key == "drm-client-id" {
+            client_id = value.parse::<u64>().ok();
+        } else if let Some(name) = key.strip_prefix("drm-engine-capacity-") {
+            if let Ok(cap) = value.parse() {
+                client.capacity.insert(name.to_string(), cap);
+            }
+        } else if let Some(name) = key.strip_prefix("drm-engine-") {
+            if let Some(ns) = value.strip_suffix("ns").and_then(|v| v.trim().parse().ok()) {
+                client.engines.insert(name.to_string(), ns);
+            }
+        } else if key.starts_with("drm-memory-") {
+            client.memory += parse_memory(value);
+        } else if key.starts_with("drm-resident-") {
+            resident += parse_memory(value);
+        }
+    }
+
+    // Newer kernels report drm-resident-* instead of the legacy drm-memory-*
+    if client.memory == 0 {
+        client.memory = resident;
+    }
+
+    if !is_drm {
+        return None;
+    }
+    Some(((pdev, client_id?), client))
+}
+
+fn parse_memory(value: &str) -> u64 {
+    let mut parts = value.split_whitespace();
+    let Some(n) = parts.next().and_then(|n| n.parse::<u64>().ok()) else {
+        return 0;
+    };
+    match parts.next() {
+        Some("KiB") => n * 1024,
+        Some("MiB") => n * 1024 * 1024,
+        Some("GiB") => n * 1024 * 1024 * 1024,
+        _ => n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_amdgpu_fdinfo() {
+        let content = fixture("fdinfo/t0").read_to_string(std::path::Path::new("/proc/1234/fdinfo/5")).unwrap();
+        let ((pdev, id), client) = parse_fdinfo(&content).unwrap();
+        assert_eq!(pdev, "0000:03:00.0");
+        assert_eq!(id, 17);
+        assert_eq!(client.engines["gfx"], 1_000_000_000);
+        assert_eq!(client.memory, (65536 + 2048) * 1024);
+    }
+
+    #[test]
+    fn ignores_non_drm_fdinfo() {
+        assert!(parse_fdinfo("pos:\t0\nflags:\t02000002\nmnt_id:\t25\n").is_none());
+    }
+
+    fn sample(sampler: &mut GpuProcessSampler, fs: Fs, now: Instant) {
+        sampler.sample(fs.as_ref(), &OpenFds::read(fs.as_ref(), false), now, true);
+    }
+
+    #[test]
+    fn diffs_engine_time_between_samples() {
+        let mut sampler = GpuProcessSampler::new(fixture("fdinfo/t0"));
+        let t0 = Instant::now();
+        sample(&mut sampler, fixture("fdinfo/t0"), t0);
+        assert_eq!(sampler.get(1234).usage, 0.0);
+
+        sample(&mut sampler, fixture("fdinfo/t1"), t0 + Duration::from_secs(1));
+
+        // gfx advanced 250 ms over 1 s; duplicated fd 6 shares client 17
+        let amd = sampler.get(1234);
+        assert!((amd.usage - 25.0).abs() < 0.01);
+        assert_eq!(amd.memory, (65536 + 2048) * 1024);
+
+        // video advanced 1.6 s over 1 s, shared by an engine capacity of 2
+        let intel = sampler.get(4321);
+        assert!((intel.usage - 80.0).abs() < 0.01);
+        assert_eq!(intel.memory, 128 * 1024 * 1024);
+
+        assert_eq!(sampler.get(1).usage, 0.0);
+    }
+
+    #[test]
+    fn leaves_sysroot_processes_alone() {
+        let fs = fixture("fdinfo/t1");
+        let clients = Arc::default();
+        let mut sampler = GpuProcessSampler::new(fs.clone());
+        sampler.share_clients(Arc::clone(&clients));
+        sampler.refresh(&OpenFds::read(fs.as_ref(), false));
+        assert_eq!(sampler.get(1234).memory, 0, "PID 1234 of the capture is not the host's");
+        let clients = clients.lock().unwrap();
+        assert!(clients.taken().is_some());
+        assert_eq!(clients.device_clients("0000:03:00.0")[&17]["gfx"], 1_250_000_000, "its devices still count them");
+    }
+
+    #[test]
+    fn lists_clients_per_device() {
+        let clients = Arc::default();
+        let mut sampler = GpuProcessSampler::new(fixture("fdinfo/t1"));
+        sampler.share_clients(Arc::clone(&clients));
+        sample(&mut sampler, fixture("fdinfo/t1"), Instant::now());
+        let clients = clients.lock().unwrap();
+        let amd = clients.device_clients("0000:03:00.0");
+        assert_eq!(amd.len(), 1, "fd 6 is a dup of client 17");
+        assert_eq!(amd[&17]["gfx"], 1_250_000_000);
+        assert_eq!(clients.device_clients("0000:00:02.0").values().map(|c| c["video"]).sum::<u64>(), 1_800_000_000);
+        assert!(clients.device_clients("0000:09:00.0").is_empty());
+    }
+}
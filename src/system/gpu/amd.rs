@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use super::{read_at, read_hwmon, read_u64, GpuBackend, GpuReadings};
+use crate::system::sysfs::SysFs;
+
+/// amdgpu exposes everything directly on the PCI device.
+pub struct AmdBackend;
+
+impl GpuBackend for AmdBackend {
+    fn name(&self) -> &'static str {
+        "AMDGPU SYSFS"
+    }
+
+    fn read(&mut self, fs: &dyn SysFs, _card_dir: &Path, device: &Path) -> GpuReadings {
+        let mut r = GpuReadings {
+            gpu_usage: read_u64(fs, device, "gpu_busy_percent").unwrap_or(0) as u32,
+            vram_used: read_u64(fs, device, "mem_info_vram_used").unwrap_or(0),
+            vram_total: read_u64(fs, device, "mem_info_vram_total").unwrap_or(0),
+            gpu_freq_mhz: read_at(fs, device, "pp_dpm_sclk")
+                .as_deref()
+                .and_then(parse_active_clock_mhz),
+            mem_freq_mhz: read_at(fs, device, "pp_dpm_mclk")
+                .as_deref()
+                .and_then(parse_active_clock_mhz),
+            ..Default::default()
+        };
+        read_hwmon(fs, device, &mut r);
+        r
+    }
+}
+
+fn parse_active_clock_mhz(content: &str) -> Option<u32> {
+    // Lines like: "0: 500Mhz\n1: 800Mhz *\n"
+    for line in content.lines() {
+        if line.contains('*') {
+            let lower = line.to_lowercase();
+            if let Some(pos) = lower.find("mhz") {
+                let num_str = lower[..pos].split_whitespace().last()?;
+                return num_str.parse().ok();
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_active_dpm_level() {
+        assert_eq!(parse_active_clock_mhz("0: 500Mhz\n1: 800Mhz *\n2: 1200Mhz"), Some(800));
+        assert_eq!(parse_active_clock_mhz("0: 96MHz\n1: 456MHz *"), Some(456), "older kernels spell it MHz");
+        assert_eq!(parse_active_clock_mhz("0: 500Mhz\n1: 800Mhz"), None, "no level marked active");
+        assert_eq!(parse_active_clock_mhz(""), None);
+    }
+}
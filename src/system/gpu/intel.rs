@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::{find_hwmon, read_hwmon, read_u64, GpuBackend, GpuReadings};
+use crate::system::fdinfo::{ClientEngines, DrmClients};
+use crate::system::sysfs::SysFs;
+
+/// i915 and xe. Busy % comes from RC6 (GT idle) residency where the kernel
+/// exposes it, otherwise from the engine time reported in DRM fdinfo.
+pub struct IntelBackend {
+    pci_slot: String,
+    clients: Arc<Mutex<DrmClients>>, // every process's DRM clients, read by the system collector
+    prev_idle: Option<(Instant, u64)>, // idle residency, ms
+    prev_clients: Option<(Instant, ClientEngines, u32)>, // and the busy % they gave
+    prev_energy: Option<(Instant, u64)>, // µJ
+}
+
+impl IntelBackend {
+    pub fn new(pci_slot: &str, clients: Arc<Mutex<DrmClients>>) -> Self {
+        Self {
+            pci_slot: pci_slot.to_string(),
+            clients,
+            prev_idle: None,
+            prev_clients: None,
+            prev_energy: None,
+        }
+    }
+
+    fn busy_from_idle(&mut self, idle_ms: u64, now: Instant) -> u32 {
+        let busy = match self.prev_idle {
+            // A residency counter that went backwards was reset (GT reset, resume); start over
+            Some((then, prev)) if idle_ms >= prev => {
+                let wall_ms = now.saturating_duration_since(then).as_secs_f64() * 1000.0;
+                if wall_ms > 0.0 {
+                    let idle = idle_ms.saturating_sub(prev) as f64 / wall_ms;
+                    ((1.0 - idle) * 100.0).clamp(0.0, 100.0) as u32
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+        self.prev_idle = Some((now, idle_ms));
+        busy
+    }
+
+    /// Engine time from the clients the system collector last read; until
+    /// it reads them again the previous figure stands. Each client is diffed
+    /// against itself, so one that exits doesn't make the card read idle.
+    fn busy_from_fdinfo(&mut self) -> u32 {
+        let Ok(shared) = self.clients.lock() else {
+            return 0;
+        };
+        let Some(now) = shared.taken() else {
+            return 0;
+        };
+        let clients = shared.device_clients(&self.pci_slot);
+        drop(shared);
+
+        let mut busiest = 0.0_f64;
+        if let Some((then, prev, busy)) = &self.prev_clients {
+            if *then == now {
+                return *busy;
+            }
+            let wall_ns = now.saturating_duration_since(*then).as_nanos() as f64;
+            if wall_ns > 0.0 {
+                let mut engines: HashMap<&str, u64> = HashMap::new();
+                for (id, client) in &clients {
+                    // Clients opened since the last reading count from now on
+                    let Some(old) = prev.get(id) else { continue };
+                    for (name, ns) in client {
+                        let delta = ns.saturating_sub(old.get(name).copied().unwrap_or(*ns));
+                        *engines.entry(name).or_default() += delta;
+                    }
+                }
+                busiest = engines.values().map(|&ns| ns as f64 / wall_ns * 100.0).fold(0.0, f64::max);
+            }
+        }
+        let busy = busiest.min(100.0) as u32;
+        self.prev_clients = Some((now, clients, busy));
+        busy
+    }
+
+    /// Discrete cards report cumulative energy rather than instantaneous power.
+    fn power_from_energy(&mut self, energy_uj: u64, now: Instant) -> f64 {
+        let watts = match self.prev_energy {
+            Some((then, prev)) => {
+                let secs = now.saturating_duration_since(then).as_secs_f64();
+                if secs > 0.0 {
+                    energy_uj.saturating_sub(prev) as f64 / 1_000_000.0 / secs
+                } else {
+                    0.0
+                }
+            }
+            None => 0.0,
+        };
+        self.prev_energy = Some((now, energy_uj));
+        watts
+    }
+}
+
+impl GpuBackend for IntelBackend {
+    fn name(&self) -> &'static str {
+        "INTEL SYSFS"
+    }
+
+    fn read(&mut self, fs: &dyn SysFs, card_dir: &Path, device: &Path) -> GpuReadings {
+        let now = Instant::now();
+
+        let idle_ms = read_u64(fs, card_dir, "gt/gt0/rc6_residency_ms")
+            .or_else(|| read_u64(fs, card_dir, "power/rc6_residency_ms"))
+            .or_else(|| read_u64(fs, device, "tile0/gt0/gtidle/idle_residency_ms"));
+        let gpu_usage = match idle_ms {
+            Some(ms) => self.busy_from_idle(ms, now),
+            None => self.busy_from_fdinfo(),
+        };
+
+        let mut r = GpuReadings {
+            gpu_usage,
+            // i915 on the card directory, xe per GT tile on the device
+            gpu_freq_mhz: read_u64(fs, card_dir, "gt_cur_freq_mhz")
+                .or_else(|| read_u64(fs, card_dir, "gt/gt0/rps_cur_freq_mhz"))
+                .or_else(|| read_u64(fs, device, "tile0/gt0/freq0/cur_freq"))
+                .map(|f| f as u32),
+            ..Default::default()
+        };
+
+        read_hwmon(fs, device, &mut r);
+        if r.power_w == 0.0 {
+            if let Some(energy) = find_hwmon(fs, device).and_then(|h| read_u64(fs, &h, "energy1_input")) {
+                r.power_w = self.power_from_energy(energy, now);
+            }
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn busy_from_idle_residency() {
+        let mut intel = IntelBackend::new("0000:00:02.0", Arc::default());
+        let t0 = Instant::now();
+        assert_eq!(intel.busy_from_idle(50_000, t0), 0, "first reading");
+        assert_eq!(intel.busy_from_idle(50_250, t0 + Duration::from_secs(1)), 75);
+        assert_eq!(intel.busy_from_idle(50_250, t0 + Duration::from_secs(1)), 0, "no time passed");
+        assert_eq!(intel.busy_from_idle(2_000, t0 + Duration::from_secs(2)), 0, "counter reset");
+        assert_eq!(intel.busy_from_idle(3_000, t0 + Duration::from_secs(3)), 0, "idle all along");
+        assert_eq!(intel.busy_from_idle(5_000, t0 + Duration::from_secs(4)), 0, "idle time runs ahead of the clock");
+    }
+
+    #[test]
+    fn busy_from_fdinfo_per_client() {
+        let clients = Arc::new(Mutex::new(DrmClients::default()));
+        let mut intel = IntelBackend::new("0000:00:02.0", Arc::clone(&clients));
+        assert_eq!(intel.busy_from_fdinfo(), 0, "nothing read yet");
+
+        let t0 = Instant::now();
+        let s = 1_000_000_000;
+        let publish = |secs, engines: &[(&str, u64, &str, u64)]| {
+            *clients.lock().unwrap() = DrmClients::from_engines(t0 + Duration::from_secs(secs), engines);
+        };
+        publish(0, &[("0000:00:02.0", 1, "render", 10 * s), ("0000:00:02.0", 2, "render", 5 * s)]);
+        assert_eq!(intel.busy_from_fdinfo(), 0, "first reading");
+
+        publish(1, &[("0000:00:02.0", 2, "render", 5 * s + s / 2), ("0000:03:00.0", 2, "render", 90 * s)]);
+        assert_eq!(intel.busy_from_fdinfo(), 50, "client 1 exited; client 2 ran half the time");
+        assert_eq!(intel.busy_from_fdinfo(), 50, "no new reading");
+
+        publish(2, &[("0000:00:02.0", 2, "render", 5 * s + s / 2), ("0000:00:02.0", 3, "render", 7 * s)]);
+        assert_eq!(intel.busy_from_fdinfo(), 0, "a new client counts from its first reading");
+
+        publish(3, &[("0000:00:02.0", 2, "render", 5 * s + s), ("0000:00:02.0", 3, "render", 7 * s + s / 4)]);
+        assert_eq!(intel.busy_from_fdinfo(), 75, "clients on one engine add up");
+    }
+
+    #[test]
+    fn power_from_energy_counter() {
+        let mut intel = IntelBackend::new("0000:03:00.0", Arc::default());
+        let t0 = Instant::now();
+        assert_eq!(intel.power_from_energy(9_000_000, t0), 0.0, "first reading");
+        assert_eq!(intel.power_from_energy(39_000_000, t0 + Duration::from_secs(2)), 15.0);
+        assert_eq!(intel.power_from_energy(40_000_000, t0 + Duration::from_secs(2)), 0.0, "no time passed");
+        assert_eq!(intel.power_from_energy(1_000_000, t0 + Duration::from_secs(3)), 0.0, "counter reset");
+        assert_eq!(intel.power_from_energy(6_000_000, t0 + Duration::from_millis(3500)), 10.0);
+    }
+}
@@ -0,0 +1,431 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::fdinfo::DrmClients;
+use super::history::{History, HISTORY_RETENTION};
+use super::sysfs::{self, Fs, SysFs};
+
+mod amd;
+mod intel;
+mod nvidia;
+
+/// Default; overridden by `refresh.gpu` in the config file.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(1500);
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GpuKind {
+    Integrated,
+    Discrete,
+}
+
+impl GpuKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GpuKind::Integrated => "INTEGRATED",
+            GpuKind::Discrete => "DISCRETE",
+        }
+    }
+}
+
+/// All DRM cards, refreshed together.
+pub struct GpuMonitor {
+    pub gpus: Vec<GpuInfo>,
+    backends: Vec<Box<dyn GpuBackend>>, // parallel to `gpus`
+    fs: Fs,
+    drm_clients: Arc<Mutex<DrmClients>>, // published by the system collector's fdinfo sampler
+    interval: Duration,
+    retention: Duration,
+    last_refresh: Instant,
+}
+
+/// What a backend reports for one refresh; anything it can't read stays at the default.
+#[derive(Default)]
+struct GpuReadings {
+    gpu_usage: u32,
+    vram_used: u64,
+    vram_total: u64,
+    gpu_freq_mhz: Option<u32>,
+    mem_freq_mhz: Option<u32>,
+    power_w: f64,
+    power_cap_w: Option<f64>,
+    temp_celsius: Option<f32>,
+}
+
+/// Driver-specific source of utilisation, memory, clock and sensor data.
+trait GpuBackend: Send {
+    fn name(&self) -> &'static str;
+    /// `card_dir` is `/sys/class/drm/cardN`, `device` its PCI device directory.
+    fn read(&mut self, fs: &dyn SysFs, card_dir: &Path, device: &Path) -> GpuReadings;
+}
+
+/// Pick a backend from the kernel driver bound to the device.
+fn backend_for(fs: &dyn SysFs, driver: &str, pci_slot: &str, clients: &Arc<Mutex<DrmClients>>) -> Box<dyn GpuBackend> {
+    match driver {
+        "amdgpu" | "radeon" => Box::new(amd::AmdBackend),
+        "i915" | "xe" => Box::new(intel::IntelBackend::new(pci_slot, Arc::clone(clients))),
+        "nvidia" | "nouveau" => Box::new(nvidia::NvidiaBackend::new(pci_slot, driver, fs.is_host())),
+        _ => Box::new(HwmonBackend),
+    }
+}
+
+/// Fallback for unknown drivers: only what the generic hwmon interface offers.
+struct HwmonBackend;
+
+impl GpuBackend for HwmonBackend {
+    fn name(&self) -> &'static str {
+        "HWMON"
+    }
+
+    fn read(&mut self, fs: &dyn SysFs, _card_dir: &Path, device: &Path) -> GpuReadings {
+        let mut r = GpuReadings::default();
+        read_hwmon(fs, device, &mut r);
+        r
+    }
+}
+
+/// One DRM card (`/sys/class/drm/cardN`).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub card: String,
+    device: PathBuf,
+    pub backend_name: String,
+    pub kind: GpuKind,
+    pub gpu_usage: u32,
+    pub vram_used: u64,
+    pub vram_total: u64,
+    pub gpu_freq_mhz: Option<u32>,
+    pub mem_freq_mhz: Option<u32>,
+    pub power_w: f64,
+    pub power_cap_w: Option<f64>,
+    pub temp_celsius: f32,
+    pub temp_max: f32,
+    pub manufacturer: String,
+    pub pci_slot: String,
+    pub driver: String,
+    pub pcie_link: String,
+    pub usage_history: History, // percent
+    pub power_history: History, // W
+}
+
+fn read_at(fs: &dyn SysFs, base: &Path, name: &str) -> Option<String> {
+    fs.read(&base.join(name))
+}
+
+fn read_u64(fs: &dyn SysFs, base: &Path, name: &str) -> Option<u64> {
+    read_at(fs, base, name)?.parse().ok()
+}
+
+/// Every `cardN` under /sys/class/drm, in card order. Connector entries such
+/// as `card0-DP-1` and render nodes are skipped.
+fn find_gpu_devices(fs: &dyn SysFs) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs.read_dir(Path::new("/sys/class/drm")) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<(u32, String, PathBuf)> = entries
+        .into_iter()
+        .filter_map(|path| {
+            let name = sysfs::file_name(&path);
+            let index = name.strip_prefix("card")?.parse::<u32>().ok()?;
+            let device = path.join("device");
+            fs.exists(&device).then_some((index, name, device))
+        })
+        .collect();
+    cards.sort_by_key(|(index, _, _)| *index);
+    cards.into_iter().map(|(_, name, device)| (name, device)).collect()
+}
+
+/// Integrated GPUs sit on the root bus (Intel iGPUs at 00:02.0). AMD APUs
+/// can sit on any bus, so amdgpu is asked instead: an APU's VRAM is a carve-out
+/// of system memory, all of it CPU-visible, and it has no memory vendor (the
+/// driver hides `mem_info_vram_vendor`). Carve-out size says nothing; APUs
+/// range from 512 MiB to 8 GiB and more.
+fn classify(fs: &dyn SysFs, device: &Path, driver: &str, pci_slot: &str) -> GpuKind {
+    let on_root_bus = pci_slot.split(':').nth(1) == Some("00");
+    let apu = driver == "amdgpu" && !fs.exists(&device.join("mem_info_vram_vendor")) && {
+        let vram = read_u64(fs, device, "mem_info_vram_total");
+        vram.is_some() && vram == read_u64(fs, device, "mem_info_vis_vram_total")
+    };
+    if on_root_bus || apu {
+        GpuKind::Integrated
+    } else {
+        GpuKind::Discrete
+    }
+}
+
+fn find_hwmon(fs: &dyn SysFs, device: &Path) -> Option<PathBuf> {
+    let mut entries = fs.read_dir(&device.join("hwmon")).ok()?;
+    entries.sort();
+    entries.into_iter().next()
+}
+
+/// Temperature and power from the device's hwmon node, shared by most drivers.
+fn read_hwmon(fs: &dyn SysFs, device: &Path, r: &mut GpuReadings) {
+    let Some(hwmon) = find_hwmon(fs, device) else {
+        return;
+    };
+    r.temp_celsius = read_u64(fs, &hwmon, "temp1_input").map(|t| t as f32 / 1000.0);
+    // Newer amdgpu and nouveau expose power1_input instead of power1_average
+    r.power_w = read_u64(fs, &hwmon, "power1_average")
+        .or_else(|| read_u64(fs, &hwmon, "power1_input"))
+        .unwrap_or(0) as f64 / 1_000_000.0;
+    r.power_cap_w = read_u64(fs, &hwmon, "power1_cap")
+        .or_else(|| read_u64(fs, &hwmon, "power1_max"))
+        .map(|v| v as f64 / 1_000_000.0);
+}
+
+fn pcie_gen_from_speed(speed: &str) -> &'static str {
+    if speed.contains("2.5") { "1.0" }
+    else if speed.contains("5.0") { "2.0" }
+    else if speed.contains("8.0") { "3.0" }
+    else if speed.contains("16.0") { "4.0" }
+    else if speed.contains("32.0") { "5.0" }
+    else { "?" }
+}
+
+fn vendor_to_name(vendor: &str) -> String {
+    match vendor.trim().to_lowercase().as_str() {
+        "0x1002" => "Advanced Micro Devices, Inc. [AMD/ATI]".to_string(),
+        "0x10de" => "NVIDIA Corporation".to_string(),
+        "0x8086" => "Intel Corporation".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_uevent(fs: &dyn SysFs, device: &Path) -> (String, String) {
+    let mut driver = "Unknown".to_string();
+    let mut pci_slot = "N/A".to_string();
+    if let Some(content) = read_at(fs, device, "uevent") {
+        for line in content.lines() {
+            if let Some(v) = line.strip_prefix("DRIVER=") {
+                driver = v.to_string();
+            } else if let Some(v) = line.strip_prefix("PCI_SLOT_NAME=") {
+                pci_slot = v.to_string();
+            }
+        }
+    }
+    (driver, pci_slot)
+}
+
+impl GpuMonitor {
+    pub fn new(fs: Fs) -> Self {
+        let mut monitor = Self {
+            gpus: Vec::new(),
+            backends: Vec::new(),
+            fs,
+            drm_clients: Arc::default(),
+            interval: REFRESH_INTERVAL,
+            retention: HISTORY_RETENTION,
+            last_refresh: Instant::now() - REFRESH_INTERVAL,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self.restart_history();
+        self
+    }
+
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self.restart_history();
+        self
+    }
+
+    /// Where the system collector publishes DRM clients, so backends that
+    /// need fdinfo don't walk `/proc` again.
+    pub fn drm_clients(&self) -> Arc<Mutex<DrmClients>> {
+        self.drm_clients.clone()
+    }
+
+    fn restart_history(&mut self) {
+        for gpu in &mut self.gpus {
+            gpu.usage_history = History::with_retention(self.retention, self.interval);
+            gpu.power_history = History::with_retention(self.retention, self.interval);
+        }
+    }
+
+    pub fn refresh_if_needed(&mut self) -> bool {
+        let due = self.last_refresh.elapsed() >= self.interval;
+        if due {
+            self.refresh();
+        }
+        due
+    }
+
+    /// Read now, whether or not the interval has passed.
+    pub fn refresh(&mut self) {
+        self.last_refresh = Instant::now();
+
+        // Keep existing entries so peak temperature and history survive a rescan
+        let fs = self.fs.as_ref();
+        let mut previous: Vec<(GpuInfo, Box<dyn GpuBackend>)> =
+            std::mem::take(&mut self.gpus).into_iter().zip(std::mem::take(&mut self.backends)).collect();
+        for (card, device) in find_gpu_devices(fs) {
+            let (mut gpu, mut backend) = match previous.iter().position(|(g, _)| g.card == card && g.device == device) {
+                Some(i) => previous.swap_remove(i),
+                None => {
+                    let history = History::with_retention(self.retention, self.interval);
+                    GpuInfo::new(fs, card, device, history, &self.drm_clients)
+                }
+            };
+            gpu.refresh(fs, backend.as_mut());
+            self.gpus.push(gpu);
+            self.backends.push(backend);
+        }
+    }
+}
+
+impl GpuInfo {
+    fn new(
+        fs: &dyn SysFs,
+        card: String,
+        device: PathBuf,
+        history: History,
+        clients: &Arc<Mutex<DrmClients>>,
+    ) -> (Self, Box<dyn GpuBackend>) {
+        let (driver, pci_slot) = parse_uevent(fs, &device);
+        let backend = backend_for(fs, &driver, &pci_slot, clients);
+        let gpu = Self {
+            kind: classify(fs, &device, &driver, &pci_slot),
+            card,
+            device,
+            backend_name: backend.name().to_string(),
+            gpu_usage: 0,
+            vram_used: 0,
+            vram_total: 0,
+            gpu_freq_mhz: None,
+            mem_freq_mhz: None,
+            power_w: 0.0,
+            power_cap_w: None,
+            temp_celsius: 0.0,
+            temp_max: 0.0,
+            manufacturer: "Unknown".to_string(),
+            pci_slot,
+            driver,
+            pcie_link: "N/A".to_string(),
+            usage_history: history.clone(),
+            power_history: history,
+        };
+        (gpu, backend)
+    }
+
+    fn refresh(&mut self, fs: &dyn SysFs, backend: &mut dyn GpuBackend) {
+        let device = self.device.clone();
+        let card_dir = device.parent().unwrap_or(&device).to_path_buf();
+
+        let r = backend.read(fs, &card_dir, &device);
+        self.gpu_usage = r.gpu_usage.min(100);
+        self.vram_used = r.vram_used;
+        self.vram_total = r.vram_total;
+        self.gpu_freq_mhz = r.gpu_freq_mhz;
+        self.mem_freq_mhz = r.mem_freq_mhz;
+        self.power_w = r.power_w;
+        self.power_cap_w = r.power_cap_w;
+
+        let temp = r.temp_celsius.unwrap_or(0.0);
+        self.temp_celsius = temp;
+        if temp > self.temp_max {
+            self.temp_max = temp;
+        }
+
+        let speed = read_at(fs, &device, "current_link_speed").unwrap_or_default();
+        let width = read_at(fs, &device, "current_link_width").unwrap_or_default();
+        if !speed.is_empty() && !width.is_empty() {
+            self.pcie_link = format!("PCIe {} \u{00d7}{}", pcie_gen_from_speed(&speed), width);
+        } else {
+            self.pcie_link = "N/A".to_string();
+        }
+
+        self.manufacturer = read_at(fs, &device, "vendor")
+            .map(|v| vendor_to_name(&v))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        self.usage_history.push(self.gpu_usage as f32);
+        self.power_history.push(self.power_w as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn reads_amd_gpu_and_skips_connectors() {
+        let monitor = GpuMonitor::new(fixture("amd-gpu"));
+        assert_eq!(monitor.gpus.len(), 1);
+
+        let gpu = &monitor.gpus[0];
+        assert_eq!(gpu.card, "card1");
+        assert_eq!(gpu.driver, "amdgpu");
+        assert_eq!(gpu.backend_name, "AMDGPU SYSFS");
+        assert_eq!(gpu.pci_slot, "0000:03:00.0");
+        assert!(gpu.kind == GpuKind::Discrete);
+        assert_eq!(gpu.gpu_usage, 37);
+        assert_eq!(gpu.vram_used, 2_147_483_648);
+        assert_eq!(gpu.gpu_freq_mhz, Some(1800));
+        assert_eq!(gpu.mem_freq_mhz, Some(1000));
+        assert_eq!(gpu.temp_celsius, 54.0);
+        assert!((gpu.power_w - 112.0).abs() < 1e-9);
+        assert_eq!(gpu.power_cap_w, Some(255.0));
+        assert_eq!(gpu.pcie_link, "PCIe 4.0 \u{00d7}16");
+    }
+
+    #[test]
+    fn amd_apu_is_integrated_off_the_root_bus() {
+        let gpu = &GpuMonitor::new(fixture("amd-apu")).gpus[0];
+        assert_eq!(gpu.pci_slot, "0000:c1:00.0");
+        assert_eq!(gpu.vram_total, 2 << 30, "a 2 GiB UMA carve-out");
+        assert!(gpu.kind == GpuKind::Integrated);
+    }
+
+    #[test]
+    fn reads_intel_integrated_gpu() {
+        let monitor = GpuMonitor::new(fixture("intel-laptop"));
+        assert_eq!(monitor.gpus.len(), 1);
+
+        let gpu = &monitor.gpus[0];
+        assert_eq!(gpu.backend_name, "INTEL SYSFS");
+        assert!(gpu.kind == GpuKind::Integrated);
+        assert_eq!(gpu.gpu_freq_mhz, Some(650));
+        assert_eq!(gpu.manufacturer, "Intel Corporation");
+    }
+
+    #[test]
+    fn picks_a_backend_by_driver() {
+        let fs = fixture("desktop-no-battery");
+        let names: Vec<&str> = ["amdgpu", "radeon", "i915", "xe", "nvidia", "nouveau", "virtio-pci", "Unknown"]
+            .iter()
+            .map(|driver| backend_for(fs.as_ref(), driver, "0000:01:00.0", &Arc::default()).name())
+            .collect();
+        assert_eq!(
+            names,
+            ["AMDGPU SYSFS", "AMDGPU SYSFS", "INTEL SYSFS", "INTEL SYSFS", "NVIDIA SYSFS", "NOUVEAU SYSFS", "HWMON", "HWMON"]
+        );
+    }
+
+    #[test]
+    fn reads_nvidia_gpus_without_nvml() {
+        let gpu = &GpuMonitor::new(fixture("nvidia-desktop")).gpus[0];
+        assert_eq!((gpu.driver.as_str(), gpu.backend_name.as_str()), ("nvidia", "NVIDIA SYSFS"));
+        assert_eq!(gpu.manufacturer, "NVIDIA Corporation");
+        assert!(gpu.kind == GpuKind::Discrete);
+
+        let monitor = GpuMonitor::new(fixture("nouveau-desktop"));
+        assert_eq!(monitor.gpus.len(), 1, "connector skipped");
+        let gpu = &monitor.gpus[0];
+        assert_eq!(gpu.backend_name, "NOUVEAU SYSFS");
+        assert_eq!((gpu.temp_celsius, gpu.temp_max), (41.0, 41.0));
+        assert_eq!(gpu.pcie_link, "PCIe 3.0 \u{00d7}16");
+    }
+
+    #[test]
+    fn desktop_without_drm_cards() {
+        assert!(GpuMonitor::new(fixture("desktop-no-battery")).gpus.is_empty());
+    }
+}
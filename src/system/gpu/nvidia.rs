@@ -0,0 +1,179 @@
+use std::ffi::{c_char, c_int, c_uint, c_ulonglong, c_void, CString};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use libloading::Library;
+
+use super::{read_hwmon, GpuBackend, GpuReadings};
+use crate::system::sysfs::SysFs;
+
+type NvmlReturn = c_int;
+type NvmlDeviceHandle = *mut c_void;
+
+const NVML_SUCCESS: NvmlReturn = 0;
+const NVML_CLOCK_GRAPHICS: c_uint = 0;
+const NVML_CLOCK_MEM: c_uint = 2;
+const NVML_TEMPERATURE_GPU: c_uint = 0;
+
+#[repr(C)]
+#[derive(Default)]
+struct NvmlUtilization {
+    gpu: c_uint,
+    _memory: c_uint,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct NvmlMemory {
+    total: c_ulonglong,
+    _free: c_ulonglong,
+    used: c_ulonglong,
+}
+
+/// Function table for `libnvidia-ml.so.1`, loaded once on first use.
+struct Nvml {
+    _lib: Library,
+    device_by_pci: unsafe extern "C" fn(*const c_char, *mut NvmlDeviceHandle) -> NvmlReturn,
+    utilization: unsafe extern "C" fn(NvmlDeviceHandle, *mut NvmlUtilization) -> NvmlReturn,
+    memory: unsafe extern "C" fn(NvmlDeviceHandle, *mut NvmlMemory) -> NvmlReturn,
+    clock: unsafe extern "C" fn(NvmlDeviceHandle, c_uint, *mut c_uint) -> NvmlReturn,
+    power: unsafe extern "C" fn(NvmlDeviceHandle, *mut c_uint) -> NvmlReturn,
+    power_limit: unsafe extern "C" fn(NvmlDeviceHandle, *mut c_uint) -> NvmlReturn,
+    temperature: unsafe extern "C" fn(NvmlDeviceHandle, c_uint, *mut c_uint) -> NvmlReturn,
+}
+
+static NVML: OnceLock<Option<Nvml>> = OnceLock::new();
+
+fn nvml() -> Option<&'static Nvml> {
+    NVML.get_or_init(|| unsafe { Nvml::load() }).as_ref()
+}
+
+impl Nvml {
+    unsafe fn load() -> Option<Self> {
+        let lib = Library::new("libnvidia-ml.so.1").ok()?;
+        let init = *lib.get::<unsafe extern "C" fn() -> NvmlReturn>(b"nvmlInit_v2\0").ok()?;
+        if init() != NVML_SUCCESS {
+            return None;
+        }
+        Some(Self {
+            device_by_pci: *lib.get(b"nvmlDeviceGetHandleByPciBusId_v2\0").ok()?,
+            utilization: *lib.get(b"nvmlDeviceGetUtilizationRates\0").ok()?,
+            memory: *lib.get(b"nvmlDeviceGetMemoryInfo\0").ok()?,
+            clock: *lib.get(b"nvmlDeviceGetClockInfo\0").ok()?,
+            power: *lib.get(b"nvmlDeviceGetPowerUsage\0").ok()?,
+            power_limit: *lib.get(b"nvmlDeviceGetEnforcedPowerLimit\0").ok()?,
+            temperature: *lib.get(b"nvmlDeviceGetTemperature\0").ok()?,
+            _lib: lib,
+        })
+    }
+
+    fn device(&self, pci_slot: &str) -> Option<NvmlDevice> {
+        let bus_id = CString::new(pci_slot).ok()?;
+        let mut handle: NvmlDeviceHandle = std::ptr::null_mut();
+        let ret = unsafe { (self.device_by_pci)(bus_id.as_ptr(), &mut handle) };
+        (ret == NVML_SUCCESS && !handle.is_null()).then_some(NvmlDevice(handle))
+    }
+}
+
+/// NVML device handles are plain identifiers and valid from any thread.
+struct NvmlDevice(NvmlDeviceHandle);
+
+unsafe impl Send for NvmlDevice {}
+
+/// Proprietary driver via NVML when the library is present; nouveau (or a
+/// missing NVML, or a non-host sysroot) falls back to hwmon temperature and power.
+pub struct NvidiaBackend {
+    device: Option<NvmlDevice>,
+    nouveau: bool,
+}
+
+impl NvidiaBackend {
+    pub fn new(pci_slot: &str, driver: &str, host: bool) -> Self {
+        Self {
+            device: if host { nvml().and_then(|n| n.device(pci_slot)) } else { None },
+            nouveau: driver == "nouveau",
+        }
+    }
+}
+
+impl GpuBackend for NvidiaBackend {
+    fn name(&self) -> &'static str {
+        if self.device.is_some() {
+            "NVML"
+        } else if self.nouveau {
+            "NOUVEAU SYSFS"
+        } else {
+            "NVIDIA SYSFS"
+        }
+    }
+
+    fn read(&mut self, fs: &dyn SysFs, _card_dir: &Path, device: &Path) -> GpuReadings {
+        let mut r = GpuReadings::default();
+        let (Some(nvml), Some(dev)) = (nvml(), &self.device) else {
+            read_hwmon(fs, device, &mut r);
+            return r;
+        };
+        let h = dev.0;
+
+        unsafe {
+            let mut util = NvmlUtilization::default();
+            if (nvml.utilization)(h, &mut util) == NVML_SUCCESS {
+                r.gpu_usage = util.gpu;
+            }
+            let mut mem = NvmlMemory::default();
+            if (nvml.memory)(h, &mut mem) == NVML_SUCCESS {
+                r.vram_used = mem.used;
+                r.vram_total = mem.total;
+            }
+            let mut mhz: c_uint = 0;
+            if (nvml.clock)(h, NVML_CLOCK_GRAPHICS, &mut mhz) == NVML_SUCCESS {
+                r.gpu_freq_mhz = Some(mhz);
+            }
+            if (nvml.clock)(h, NVML_CLOCK_MEM, &mut mhz) == NVML_SUCCESS {
+                r.mem_freq_mhz = Some(mhz);
+            }
+            let mut mw: c_uint = 0;
+            if (nvml.power)(h, &mut mw) == NVML_SUCCESS {
+                r.power_w = mw as f64 / 1000.0;
+            }
+            if (nvml.power_limit)(h, &mut mw) == NVML_SUCCESS {
+                r.power_cap_w = Some(mw as f64 / 1000.0);
+            }
+            let mut celsius: c_uint = 0;
+            if (nvml.temperature)(h, NVML_TEMPERATURE_GPU, &mut celsius) == NVML_SUCCESS {
+                r.temp_celsius = Some(celsius as f32);
+            }
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    const DEVICE: &str = "/sys/class/drm/card0/device";
+
+    #[test]
+    fn names_the_driver_without_nvml() {
+        // Fixtures aren't the host, so NVML is never asked
+        assert_eq!(NvidiaBackend::new("0000:01:00.0", "nvidia", false).name(), "NVIDIA SYSFS");
+        assert_eq!(NvidiaBackend::new("0000:01:00.0", "nouveau", false).name(), "NOUVEAU SYSFS");
+    }
+
+    #[test]
+    fn falls_back_to_hwmon() {
+        let fs = fixture("nouveau-desktop");
+        let device = Path::new(DEVICE);
+        let r = NvidiaBackend::new("0000:01:00.0", "nouveau", false).read(fs.as_ref(), device.parent().unwrap(), device);
+        assert_eq!(r.temp_celsius, Some(41.0));
+        assert_eq!((r.power_w, r.power_cap_w), (23.5, Some(75.0)));
+        assert_eq!((r.gpu_usage, r.vram_total, r.gpu_freq_mhz), (0, 0, None), "nouveau exposes no load or memory");
+
+        // The proprietary driver has no hwmon node at all
+        let fs = fixture("nvidia-desktop");
+        let r = NvidiaBackend::new("0000:01:00.0", "nvidia", false).read(fs.as_ref(), device.parent().unwrap(), device);
+        assert_eq!((r.temp_celsius, r.power_w), (None, 0.0));
+    }
+}
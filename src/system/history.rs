@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How far back the sparklines look by default; overridden by
+/// `history.retention` in the config file.
+pub const HISTORY_RETENTION: Duration = Duration::from_secs(120);
+
+/// Bounded ring buffer of samples for one metric, oldest first. Only the
+/// capacity is serialized: recordings rebuild the samples on replay rather
+/// than storing the whole window in every record.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct History {
+    #[serde(skip_serializing, default)]
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(2);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Size the buffer to hold `retention` worth of samples taken every `interval`.
+    pub fn with_retention(retention: Duration, interval: Duration) -> Self {
+        let interval = interval.as_secs_f64().max(0.001);
+        Self::new((retention.as_secs_f64() / interval).ceil() as usize)
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value);
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn max(&self) -> f32 {
+        self.samples.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_oldest_when_full() {
+        let mut h = History::new(3);
+        for v in [1.0, 2.0, 3.0, 4.0] {
+            h.push(v);
+        }
+        assert_eq!(h.values().collect::<Vec<_>>(), vec![2.0, 3.0, 4.0]);
+        assert_eq!(h.max(), 4.0);
+    }
+
+    #[test]
+    fn capacity_follows_retention() {
+        let h = History::with_retention(Duration::from_secs(120), Duration::from_millis(1500));
+        assert_eq!(h.capacity(), 80);
+    }
+}
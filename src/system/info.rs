@@ -1,17 +1,52 @@
-use std::collections::HashSet;
-use std::fs;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use super::cpufreq::{CpuFreq, CpuFreqSampler};
+use super::diskio::{BlockDevice, BlockIoSampler};
+use super::fdinfo::{DrmClients, GpuProcessSampler};
+use super::history::{History, HISTORY_RETENTION};
+use super::openfds::OpenFds;
+use super::powercap::{PowerDomain, PowercapSampler};
+use super::process::{ProcFiles, ProcStat, ProcessInfo};
+use super::sockdiag::NetProcessSampler;
+use super::sysfs::{self, Fs, SysFs};
+use serde::{Deserialize, Serialize};
 use sysinfo::{Components, Disks, Networks, ProcessesToUpdate, System, Users};
 
-const REFRESH_INTERVAL: Duration = Duration::from_millis(1500);
+/// Default; overridden by `refresh.system` in the config file.
+pub const REFRESH_INTERVAL: Duration = Duration::from_millis(1500);
 
+/// Owns the sysinfo handles and turns each refresh into a `SystemInfo`.
+pub struct SystemCollector {
+    system: System,
+    networks: Networks,
+    disks: Disks,
+    components: Components,
+    users: Users,
+    fs: Fs,
+    gpu_procs: GpuProcessSampler,
+    net_procs: NetProcessSampler,
+    block_io: BlockIoSampler,
+    cpu_freq: CpuFreqSampler,
+    powercap: PowercapSampler,
+    info: SystemInfo,
+    interval: Duration,
+    retention: Duration, // span of the sparkline histories
+    last_refresh: Instant,
+    sample_secs: f64, // time covered by the latest network deltas
+    proc_files: Arc<Mutex<ProcFiles>>, // optional procfs reads, set by the process table
+}
+
+/// Plain data read from the collector; cheap to clone and safe to hand to
+/// another thread.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SystemInfo {
-    pub system: System,
-    pub networks: Networks,
-    pub disks: Disks,
-    pub components: Components,
-    pub users: Users,
+    // Rolling history for the dashboard sparklines
+    pub cpu_history: History,
+    pub mem_history: History,
+    pub net_history: HashMap<String, NetHistory>,
     // CPU sensor
     pub cpu_temp: f32,
     pub cpu_temp_max: f32,
@@ -22,11 +57,27 @@ pub struct SystemInfo {
     pub cpu_sockets: usize,
     pub cpu_virtualization: String,
     pub cpu_architecture: String,
-    last_refresh: Instant,
+    // Scaling and idle state, sampled with usage
+    #[serde(default)]
+    pub cpu_freq: CpuFreq,
+    // RAPL package, core, uncore and DRAM draw
+    #[serde(default)]
+    pub cpu_power: Vec<PowerDomain>,
+    cpu_total: f32,
+    cpu_per_core: Vec<f32>,
+    memory_used: u64,
+    memory_total: u64,
+    swap_used: u64,
+    swap_total: u64,
+    disks: Vec<DiskData>,
+    #[serde(default)]
+    block_devices: Vec<BlockDevice>,
+    networks: Vec<NetworkData>,
+    processes: Arc<[ProcessInfo]>, // shared by every sample until the next refresh
 }
 
-impl SystemInfo {
-    pub fn new() -> Self {
+impl SystemCollector {
+    pub fn new(fs: Fs) -> Self {
         let mut system = System::new_all();
         // Dual refresh for accurate initial CPU readings
         std::thread::sleep(Duration::from_millis(200));
@@ -36,123 +87,293 @@ impl SystemInfo {
         let disks = Disks::new_with_refreshed_list();
         let components = Components::new_with_refreshed_list();
         let users = Users::new_with_refreshed_list();
+        let gpu_procs = GpuProcessSampler::new(fs.clone());
+        let net_procs = NetProcessSampler::new(fs.clone());
+        let mut block_io = BlockIoSampler::new(fs.clone());
+        block_io.refresh();
+        let mut cpu_freq = CpuFreqSampler::new(fs.clone());
+        cpu_freq.refresh();
+        let mut powercap = PowercapSampler::new(fs.clone());
+        powercap.refresh();
 
         let cpu_temp = find_cpu_temp(&components);
-        let cpu_logical_cores = system.cpus().len();
-        let cpu_physical_cores = system.physical_core_count().unwrap_or(0);
-
-        Self {
+        let info = SystemInfo {
+            cpu_history: History::with_retention(HISTORY_RETENTION, REFRESH_INTERVAL),
+            mem_history: History::with_retention(HISTORY_RETENTION, REFRESH_INTERVAL),
+            net_history: HashMap::new(),
             cpu_temp,
             cpu_temp_max: cpu_temp,
-            cpu_max_freq_ghz: read_max_freq_ghz(),
-            cpu_logical_cores,
-            cpu_physical_cores,
-            cpu_sockets: count_sockets(),
-            cpu_virtualization: detect_virtualization(),
+            cpu_max_freq_ghz: read_max_freq_ghz(fs.as_ref()),
+            cpu_logical_cores: system.cpus().len(),
+            cpu_physical_cores: system.physical_core_count().unwrap_or(0),
+            cpu_sockets: count_sockets(fs.as_ref()),
+            cpu_virtualization: detect_virtualization(fs.as_ref()),
             cpu_architecture: std::env::consts::ARCH.to_string(),
+            cpu_freq: CpuFreq::default(),
+            cpu_power: Vec::new(),
+            cpu_total: 0.0,
+            cpu_per_core: Vec::new(),
+            memory_used: 0,
+            memory_total: 0,
+            swap_used: 0,
+            swap_total: 0,
+            disks: Vec::new(),
+            block_devices: Vec::new(),
+            networks: Vec::new(),
+            processes: Arc::new([]),
+        };
+
+        let mut collector = Self {
             system,
             networks,
             disks,
             components,
             users,
+            fs,
+            gpu_procs,
+            net_procs,
+            block_io,
+            cpu_freq,
+            powercap,
+            info,
+            interval: REFRESH_INTERVAL,
+            retention: HISTORY_RETENTION,
             last_refresh: Instant::now(),
-        }
+            sample_secs: REFRESH_INTERVAL.as_secs_f64(),
+            proc_files: Arc::new(Mutex::new(ProcFiles::ALL)),
+        };
+        collector.refresh_fds();
+        collector.update_info();
+        collector
     }
 
-    pub fn refresh_if_needed(&mut self) -> bool {
-        if self.last_refresh.elapsed() >= REFRESH_INTERVAL {
-            self.system.refresh_memory();
-            self.system.refresh_cpu_all();
-            self.system.refresh_processes(ProcessesToUpdate::All, true);
-            self.networks.refresh(true);
-            self.disks.refresh(true);
-            self.components.refresh(true);
-            let temp = find_cpu_temp(&self.components);
-            self.cpu_temp = temp;
-            if temp > self.cpu_temp_max {
-                self.cpu_temp_max = temp;
-            }
-            self.last_refresh = Instant::now();
-            true
-        } else {
-            false
-        }
+    /// Publish the DRM clients of each refresh to `clients`, for the GPU
+    /// backends that read device busy time from fdinfo. The first lands with
+    /// the next refresh.
+    pub fn with_drm_clients(mut self, clients: Arc<Mutex<DrmClients>>) -> Self {
+        self.gpu_procs.share_clients(clients);
+        self
     }
 
-    pub fn cpu_total(&self) -> f32 {
-        self.system.global_cpu_usage()
+    /// Refresh every `interval` instead of the default; history is restarted
+    /// so it still spans the retention.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self.restart_history();
+        self
     }
 
-    pub fn cpu_per_core(&self) -> Vec<f32> {
-        self.system.cpus().iter().map(|c| c.cpu_usage()).collect()
+    /// Keep `retention` of history instead of `HISTORY_RETENTION`.
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self.restart_history();
+        self
     }
 
-    pub fn memory_used(&self) -> u64 {
-        self.system.used_memory()
+    /// New buffers sized for the interval and retention, seeded with the
+    /// latest reading rather than a fresh pass over `/proc`.
+    fn restart_history(&mut self) {
+        self.info.cpu_history = History::with_retention(self.retention, self.interval);
+        self.info.mem_history = History::with_retention(self.retention, self.interval);
+        self.info.net_history.clear();
+        self.push_history();
     }
 
-    pub fn memory_total(&self) -> u64 {
-        self.system.total_memory()
+    pub fn interval(&self) -> Duration {
+        self.interval
     }
 
-    pub fn memory_fraction(&self) -> f32 {
-        let total = self.system.total_memory() as f64;
-        if total == 0.0 {
-            return 0.0;
+    /// Which optional procfs files each refresh reads; every one until the
+    /// process table says otherwise.
+    pub fn proc_files(&self) -> Arc<Mutex<ProcFiles>> {
+        self.proc_files.clone()
+    }
+
+    /// Latest readings.
+    pub fn info(&self) -> &SystemInfo {
+        &self.info
+    }
+
+    pub fn refresh_if_needed(&mut self) -> bool {
+        let due = self.last_refresh.elapsed() >= self.interval;
+        if due {
+            self.refresh();
         }
-        (self.system.used_memory() as f64 / total) as f32
+        due
     }
 
-    pub fn swap_fraction(&self) -> f32 {
-        let total = self.system.total_swap() as f64;
-        if total == 0.0 {
-            return 0.0;
+    /// Read everything now, whether or not the interval has passed.
+    pub fn refresh(&mut self) {
+        self.sample_secs = self.last_refresh.elapsed().as_secs_f64();
+        self.system.refresh_memory();
+        self.system.refresh_cpu_all();
+        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        self.refresh_fds();
+        self.networks.refresh(true);
+        self.disks.refresh(true);
+        self.block_io.refresh();
+        self.cpu_freq.refresh();
+        self.powercap.refresh();
+        self.components.refresh(true);
+        let temp = find_cpu_temp(&self.components);
+        self.info.cpu_temp = temp;
+        if temp > self.info.cpu_temp_max {
+            self.info.cpu_temp_max = temp;
         }
-        (self.system.used_swap() as f64 / total) as f32
+        self.update_info();
+        self.last_refresh = Instant::now();
     }
 
-    pub fn swap_used(&self) -> u64 {
-        self.system.used_swap()
+    /// The optional procfs reads; none of them under a sysroot, whose `/proc`
+    /// holds other processes than the host's list.
+    fn files(&self) -> ProcFiles {
+        match self.fs.is_host() {
+            true => self.proc_files.lock().map_or(ProcFiles::ALL, |f| *f),
+            false => ProcFiles::NONE,
+        }
     }
 
-    pub fn swap_total(&self) -> u64 {
-        self.system.total_swap()
+    /// Walk every process's fds once for both the GPU and network samplers;
+    /// sockets only while a network rate is shown.
+    fn refresh_fds(&mut self) {
+        let net = self.files().net;
+        let fds = OpenFds::read(self.fs.as_ref(), net);
+        self.gpu_procs.refresh(&fds);
+        match net {
+            true => self.net_procs.refresh(&fds),
+            false => self.net_procs.clear(),
+        }
     }
 
-    pub fn disk_info(&self) -> Vec<DiskData> {
-        self.disks
+    /// Copy the current sysinfo state into `info` and extend its history.
+    fn update_info(&mut self) {
+        let files = self.files();
+        let info = &mut self.info;
+        info.cpu_total = self.system.global_cpu_usage();
+        info.cpu_per_core = self.system.cpus().iter().map(|c| c.cpu_usage()).collect();
+        info.cpu_freq = self.cpu_freq.freq().clone();
+        info.cpu_power = self.powercap.domains().to_vec();
+        info.memory_used = self.system.used_memory();
+        info.memory_total = self.system.total_memory();
+        info.swap_used = self.system.used_swap();
+        info.swap_total = self.system.total_swap();
+        info.disks = self
+            .disks
             .iter()
             .filter(|d| d.total_space() > 0)
             .map(|d| {
                 let total = d.total_space();
                 let available = d.available_space();
                 let used = total.saturating_sub(available);
+                let mount = d.mount_point().to_string_lossy().to_string();
                 DiskData {
-                    mount: d.mount_point().to_string_lossy().to_string(),
+                    device: self.block_io.disk_of(&mount).unwrap_or_default().to_string(),
+                    mount,
                     used,
                     total,
                     fraction: used as f32 / total as f32,
                 }
             })
-            .collect()
-    }
-
-    pub fn network_info(&self) -> Vec<NetworkData> {
-        let interval = REFRESH_INTERVAL.as_secs_f64();
-        self.networks
+            .collect();
+        info.block_devices = self.block_io.devices().to_vec();
+        let interval = self.sample_secs;
+        info.networks = self
+            .networks
             .iter()
-            .map(|(name, data)| {
-                let rx_rate = data.received() as f64 / interval;
-                let tx_rate = data.transmitted() as f64 / interval;
-                NetworkData {
-                    name: name.clone(),
-                    rx_bytes: data.total_received(),
-                    tx_bytes: data.total_transmitted(),
-                    rx_rate,
-                    tx_rate,
-                }
+            .map(|(name, data)| NetworkData {
+                name: name.clone(),
+                rx_bytes: data.total_received(),
+                tx_bytes: data.total_transmitted(),
+                rx_rate: data.received() as f64 / interval,
+                tx_rate: data.transmitted() as f64 / interval,
             })
-            .collect()
+            .collect();
+        info.processes = self
+            .system
+            .processes()
+            .iter()
+            .map(|(pid, proc_info)| {
+                let stat = ProcStat::read(self.fs.as_ref(), pid.as_u32(), files);
+                ProcessInfo::new(*pid, proc_info, &self.users, stat, &self.gpu_procs, &self.net_procs, interval)
+            })
+            .collect();
+        self.push_history();
+    }
+
+    /// Append the current readings to the sparkline history.
+    fn push_history(&mut self) {
+        let info = &mut self.info;
+        info.cpu_history.push(info.cpu_total);
+        let memory = info.memory_fraction();
+        info.mem_history.push(memory);
+
+        info.net_history.retain(|name, _| self.networks.contains_key(name));
+        for net in &info.networks {
+            let h = info.net_history.entry(net.name.clone()).or_insert_with(|| NetHistory {
+                rx: History::with_retention(self.retention, self.interval),
+                tx: History::with_retention(self.retention, self.interval),
+            });
+            h.rx.push(net.rx_rate as f32);
+            h.tx.push(net.tx_rate as f32);
+        }
+    }
+}
+
+impl SystemInfo {
+    pub fn cpu_total(&self) -> f32 {
+        self.cpu_total
+    }
+
+    pub fn cpu_per_core(&self) -> &[f32] {
+        &self.cpu_per_core
+    }
+
+    pub fn memory_used(&self) -> u64 {
+        self.memory_used
+    }
+
+    pub fn memory_total(&self) -> u64 {
+        self.memory_total
+    }
+
+    pub fn memory_fraction(&self) -> f32 {
+        if self.memory_total == 0 {
+            return 0.0;
+        }
+        (self.memory_used as f64 / self.memory_total as f64) as f32
+    }
+
+    pub fn swap_fraction(&self) -> f32 {
+        if self.swap_total == 0 {
+            return 0.0;
+        }
+        (self.swap_used as f64 / self.swap_total as f64) as f32
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_used
+    }
+
+    pub fn swap_total(&self) -> u64 {
+        self.swap_total
+    }
+
+    pub fn disk_info(&self) -> &[DiskData] {
+        &self.disks
+    }
+
+    /// Physical disks and their I/O over the last refresh.
+    pub fn block_devices(&self) -> &[BlockDevice] {
+        &self.block_devices
+    }
+
+    pub fn network_info(&self) -> &[NetworkData] {
+        &self.networks
+    }
+
+    /// Every process and thread seen by the last refresh, unsorted.
+    pub fn processes(&self) -> &[ProcessInfo] {
+        &self.processes
     }
 }
 
@@ -173,22 +394,20 @@ fn find_cpu_temp(components: &Components) -> f32 {
         .unwrap_or(0.0)
 }
 
-fn read_max_freq_ghz() -> f32 {
-    fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
-        .ok()
-        .and_then(|s| s.trim().parse::<u64>().ok())
+fn read_max_freq_ghz(fs: &dyn SysFs) -> f32 {
+    fs.read_u64(Path::new("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq"))
         .map(|khz| khz as f32 / 1_000_000.0)
         .unwrap_or(0.0)
 }
 
-fn count_sockets() -> usize {
+fn count_sockets(fs: &dyn SysFs) -> usize {
     let mut ids = HashSet::new();
-    if let Ok(entries) = fs::read_dir("/sys/devices/system/cpu") {
-        for entry in entries.filter_map(|e| e.ok()) {
-            let name = entry.file_name().to_string_lossy().to_string();
+    if let Ok(entries) = fs.read_dir(Path::new("/sys/devices/system/cpu")) {
+        for path in entries {
+            let name = sysfs::file_name(&path);
             if name.starts_with("cpu") && name[3..].chars().all(|c| c.is_ascii_digit()) {
-                if let Ok(id) = fs::read_to_string(entry.path().join("topology/physical_package_id")) {
-                    ids.insert(id.trim().to_string());
+                if let Some(id) = fs.read(&path.join("topology/physical_package_id")) {
+                    ids.insert(id);
                 }
             }
         }
@@ -196,8 +415,8 @@ fn count_sockets() -> usize {
     ids.len().max(1)
 }
 
-fn detect_virtualization() -> String {
-    if let Ok(content) = fs::read_to_string("/proc/cpuinfo") {
+fn detect_virtualization(fs: &dyn SysFs) -> String {
+    if let Ok(content) = fs.read_to_string(Path::new("/proc/cpuinfo")) {
         if let Some(flags) = content.lines().find(|l| l.starts_with("flags")) {
             if flags.contains("svm") {
                 return "AMD-V".to_string();
@@ -211,13 +430,23 @@ fn detect_virtualization() -> String {
 
 // ── Shared utilities ─────────────────────────────────────────────────────────
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DiskData {
     pub mount: String,
+    #[serde(default)]
+    pub device: String, // disk holding the filesystem, empty when unknown
     pub used: u64,
     pub total: u64,
     pub fraction: f32,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NetHistory {
+    pub rx: History, // bytes/s
+    pub tx: History, // bytes/s
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NetworkData {
     pub name: String,
     pub rx_bytes: u64,
@@ -232,6 +461,10 @@ pub fn rate_fraction(rate: f64) -> f32 {
     (rate / MAX_RATE).clamp(0.0, 1.0) as f32
 }
 
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec as u64))
+}
+
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1_000;
     const MB: u64 = KB * 1_000;
@@ -257,3 +490,36 @@ pub fn format_uptime(seconds: u64) -> String {
     let s = seconds % 60;
     format!("{:02}:{:02}:{:02}", h, m, s)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn resizing_history_keeps_one_reading() {
+        let collector = SystemCollector::new(fixture("amd-gpu"))
+            .with_interval(Duration::from_secs(1))
+            .with_retention(Duration::from_secs(30));
+        let info = collector.info();
+        assert_eq!(info.cpu_history.capacity(), 30);
+        assert_eq!(info.cpu_history.values().count(), 1);
+        assert_eq!(info.mem_history.values().count(), 1);
+    }
+
+    #[test]
+    fn reads_cpu_properties_from_fixtures() {
+        let amd = fixture("amd-gpu");
+        assert_eq!(count_sockets(amd.as_ref()), 1);
+        assert_eq!(detect_virtualization(amd.as_ref()), "AMD-V");
+        assert!((read_max_freq_ghz(amd.as_ref()) - 4.95).abs() < 1e-6);
+
+        let laptop = fixture("intel-laptop");
+        assert_eq!(detect_virtualization(laptop.as_ref()), "Intel VT-x");
+
+        let desktop = fixture("desktop-no-battery");
+        assert_eq!(count_sockets(desktop.as_ref()), 2);
+        assert_eq!(detect_virtualization(desktop.as_ref()), "None");
+        assert_eq!(read_max_freq_ghz(desktop.as_ref()), 0.0);
+    }
+}
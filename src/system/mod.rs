@@ -1,4 +1,19 @@
+pub mod alerts;
 pub mod battery;
+pub mod cpufreq;
+pub mod detail;
+pub mod diskio;
+pub mod fdinfo;
 pub mod gpu;
+pub mod history;
 pub mod info;
+pub mod openfds;
+pub mod powercap;
 pub mod process;
+pub mod recording;
+pub mod sampler;
+pub mod sensors;
+pub mod signal;
+pub mod snapshot;
+pub mod sockdiag;
+pub mod sysfs;
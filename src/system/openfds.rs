@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::sysfs::{self, SysFs};
+
+/// The fds the samplers attribute to processes, from one pass over
+/// `/proc/<pid>/fdinfo` per refresh: a readlink per fd of every process is
+/// the bulk of the cost, so it is paid once for sockets and DRM together.
+#[derive(Default)]
+pub struct OpenFds {
+    sockets: HashMap<u64, u32>,      // socket inode -> PID of the first process found holding it
+    drm: HashMap<u32, Vec<PathBuf>>, // PID -> fdinfo of every fd that may be a DRM device
+}
+
+impl OpenFds {
+    /// Walk every process; socket inodes are only kept when `sockets` is set.
+    pub fn read(fs: &dyn SysFs, sockets: bool) -> Self {
+        let mut fds = Self::default();
+        let Ok(entries) = fs.read_dir(Path::new("/proc")) else {
+            return fds;
+        };
+        for pid_dir in entries {
+            let Ok(pid) = sysfs::file_name(&pid_dir).parse::<u32>() else {
+                continue;
+            };
+            let Ok(fdinfos) = fs.read_dir(&pid_dir.join("fdinfo")) else {
+                continue;
+            };
+            for fdinfo in fdinfos {
+                // An fd whose link can't be read may still be DRM; its fdinfo tells
+                let target = fs.read_link(&pid_dir.join("fd").join(sysfs::file_name(&fdinfo)));
+                match target {
+                    Ok(target) if !target.starts_with("/dev/dri") => {
+                        if let Some(inode) = sockets.then(|| socket_inode(&target)).flatten() {
+                            fds.sockets.entry(inode).or_insert(pid);
+                        }
+                    }
+                    _ => fds.drm.entry(pid).or_default().push(fdinfo),
+                }
+            }
+        }
+        fds
+    }
+
+    /// The process holding socket `inode`.
+    pub fn socket_owner(&self, inode: u64) -> Option<u32> {
+        self.sockets.get(&inode).copied()
+    }
+
+    /// Every process with a possible DRM fd, and the fdinfo of each such fd.
+    pub fn drm(&self) -> impl Iterator<Item = (u32, &[PathBuf])> {
+        self.drm.iter().map(|(pid, fdinfos)| (*pid, fdinfos.as_slice()))
+    }
+}
+
+/// Socket links read as `socket:[47]`.
+fn socket_inode(target: &Path) -> Option<u64> {
+    target
+        .to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn maps_socket_inodes_to_processes() {
+        let fds = OpenFds::read(fixture("process").as_ref(), true);
+        assert_eq!(fds.socket_owner(47), Some(4242));
+        assert_eq!(fds.sockets.len(), 1, "pipes are not sockets");
+        assert_eq!(fds.drm().count(), 0);
+        assert!(OpenFds::read(fixture("process").as_ref(), false).sockets.is_empty());
+    }
+
+    #[test]
+    fn keeps_fds_without_a_link_as_drm_candidates() {
+        let fds = OpenFds::read(fixture("fdinfo/t0").as_ref(), true);
+        let mut pids: Vec<u32> = fds.drm().map(|(pid, _)| pid).collect();
+        pids.sort();
+        assert_eq!(pids, vec![1, 1234, 4321]);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::sysfs::{self, Fs};
+
+/// Average draw of one RAPL domain over the last refresh.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PowerDomain {
+    pub zone: String,   // intel-rapl:0:1
+    pub name: String,   // package-0, core, uncore, dram, psys
+    pub parent: String, // the package zone of a subdomain; empty for top-level zones
+    pub watts: Option<f64>, // `None` until two readings, or when energy_uj is root-only
+    pub limit: Option<f64>, // long-term power limit (PL1), W
+    #[serde(default)]
+    pub denied: bool, // energy_uj is readable by root only on most kernels
+}
+
+impl PowerDomain {
+    pub fn is_package(&self) -> bool {
+        self.name.starts_with("package")
+    }
+}
+
+/// Reads the RAPL energy counters under `/sys/class/powercap`. The
+/// `intel_rapl` driver serves both Intel and AMD (Zen) CPUs under the same
+/// `intel-rapl:*` zone names. MMIO zones repeat the MSR package counter and
+/// are skipped so packages aren't counted twice.
+pub struct PowercapSampler {
+    fs: Fs,
+    prev: HashMap<String, u64>, // zone -> energy_uj at the last refresh
+    last: Option<Instant>,
+    domains: Vec<PowerDomain>,
+}
+
+impl PowercapSampler {
+    pub fn new(fs: Fs) -> Self {
+        Self {
+            fs,
+            prev: HashMap::new(),
+            last: None,
+            domains: Vec::new(),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let secs = self.last.map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f64());
+        let fs = self.fs.as_ref();
+        let mut zones = fs.read_dir(Path::new("/sys/class/powercap")).unwrap_or_default();
+        zones.sort();
+
+        let mut energy = HashMap::new();
+        let mut domains = Vec::new();
+        for dir in zones {
+            let zone = sysfs::file_name(&dir);
+            // Control types (`intel-rapl`) have no counter of their own
+            if !zone.contains(':') || zone.contains("mmio") || !fs.exists(&dir.join("max_energy_range_uj")) {
+                continue;
+            }
+            let now_uj = fs.read_u64(&dir.join("energy_uj"));
+            let watts = match (now_uj, self.prev.get(&zone)) {
+                (Some(new), Some(&old)) if secs > 0.0 => {
+                    let range = fs.read_u64(&dir.join("max_energy_range_uj")).unwrap_or(u64::MAX);
+                    Some(energy_delta(old, new, range) as f64 / 1e6 / secs)
+                }
+                _ => None,
+            };
+            if let Some(uj) = now_uj {
+                energy.insert(zone.clone(), uj);
+            }
+            domains.push(PowerDomain {
+                parent: zone.rsplit_once(':').map(|(p, _)| p).filter(|p| p.contains(':')).unwrap_or("").to_string(),
+                name: fs.read(&dir.join("name")).unwrap_or_else(|| zone.clone()),
+                watts,
+                denied: now_uj.is_none(),
+                // Subdomains usually report a limit of 0, meaning none
+                limit: fs
+                    .read_u64(&dir.join("constraint_0_power_limit_uw"))
+                    .filter(|uw| *uw > 0)
+                    .map(|uw| uw as f64 / 1e6),
+                zone,
+            });
+        }
+
+        self.domains = domains;
+        self.prev = energy;
+        self.last = Some(now);
+    }
+
+    /// Every zone, each package followed by its subdomains.
+    pub fn domains(&self) -> &[PowerDomain] {
+        &self.domains
+    }
+}
+
+/// Energy used between two counter readings; the counter wraps to zero
+/// after `max_energy_range_uj`.
+fn energy_delta(old: u64, new: u64, range: u64) -> u64 {
+    if new >= old {
+        new - old
+    } else {
+        range.saturating_sub(old) + new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn handles_counter_wraparound() {
+        assert_eq!(energy_delta(1_000, 6_000, 262_143_328_850), 5_000);
+        assert_eq!(energy_delta(262_143_000_000, 500_000, 262_143_328_850), 828_850);
+    }
+
+    #[test]
+    fn lists_rapl_domains() {
+        let mut sampler = PowercapSampler::new(fixture("intel-laptop"));
+        sampler.refresh();
+        let zones: Vec<(&str, &str, &str)> = sampler
+            .domains()
+            .iter()
+            .map(|d| (d.zone.as_str(), d.name.as_str(), d.parent.as_str()))
+            .collect();
+        assert_eq!(
+            zones,
+            [
+                ("intel-rapl:0", "package-0", ""),
+                ("intel-rapl:0:0", "core", "intel-rapl:0"),
+                ("intel-rapl:0:1", "uncore", "intel-rapl:0"),
+                ("intel-rapl:0:2", "dram", "intel-rapl:0"),
+                ("intel-rapl:1", "psys", ""),
+            ],
+            "no control type or MMIO zone"
+        );
+        assert!(sampler.domains()[0].is_package());
+        assert_eq!(sampler.domains()[0].limit, Some(15.0));
+        assert_eq!(sampler.domains()[1].limit, None, "a zero limit is no limit");
+        assert!(sampler.domains().iter().all(|d| d.watts.is_none()), "one reading gives no rate");
+
+        // A second reading a moment later: counters unchanged, so zero draw
+        sampler.last = Some(Instant::now() - std::time::Duration::from_secs(1));
+        sampler.refresh();
+        assert_eq!(sampler.domains()[1].watts, Some(0.0));
+    }
+}
@@ -1,25 +1,272 @@
-use std::collections::HashSet;
-use sysinfo::{Pid, System, Users};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, ThreadKind, Users};
 
-#[derive(Clone)]
+use super::detail::parse_cgroup;
+use super::fdinfo::GpuProcessSampler;
+use super::signal::{self, ProcessAction};
+use super::sockdiag::NetProcessSampler;
+use super::sysfs::SysFs;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ProcessInfo {
     pub pid: u32,
     pub name: String,
     pub user: String,
     pub cpu_usage: f32,
     pub memory: u64,
+    pub gpu_usage: f32,
+    pub gpu_memory: u64,
+    // Bytes per second over the last refresh
+    #[serde(default)]
+    pub disk_read: f64,
+    #[serde(default)]
+    pub disk_write: f64,
+    #[serde(default)]
+    pub net_rx: f64,
+    #[serde(default)]
+    pub net_tx: f64,
+    pub parent: Option<u32>,
+    pub uid: Option<u32>,
+    pub is_thread: bool, // kernel thread or userland task
+    #[serde(default)]
+    pub kernel_thread: bool,
+    #[serde(default)]
+    pub start_time: u64, // Unix seconds
+    pub cmd: String,
+    // Optional table columns; sysinfo lacks most, so they come from procfs
+    #[serde(default)]
+    pub state: String, // one-letter code, as in `ps`
+    #[serde(default)]
+    pub threads: u32,
+    #[serde(default)]
+    pub nice: i32,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub run_time: u64, // seconds
+    #[serde(default)]
+    pub virtual_memory: u64,
+    #[serde(default)]
+    pub shared_memory: u64,
+    #[serde(default)]
+    pub cgroup: String,
+}
+
+/// Identifies a process across refreshes; the start time tells a reused PID apart.
+pub type ProcessKey = (u32, u64);
+
+impl ProcessInfo {
+    /// `secs` is the time since the previous refresh, which sysinfo's disk deltas cover.
+    pub(super) fn new(
+        pid: Pid,
+        proc_info: &Process,
+        users: &Users,
+        stat: ProcStat,
+        gpu: &GpuProcessSampler,
+        net: &NetProcessSampler,
+        secs: f64,
+    ) -> Self {
+        let user = proc_info
+            .user_id()
+            .and_then(|uid| users.get_user_by_id(uid))
+            .map(|u| u.name().to_string())
+            .unwrap_or_else(|| "?".to_string());
+        let gpu_usage = gpu.get(pid.as_u32());
+        let net_usage = net.get(pid.as_u32());
+        let disk = proc_info.disk_usage();
+        Self {
+            pid: pid.as_u32(),
+            name: proc_info.name().to_string_lossy().to_string(),
+            user,
+            cpu_usage: proc_info.cpu_usage(),
+            memory: proc_info.memory(),
+            gpu_usage: gpu_usage.usage,
+            gpu_memory: gpu_usage.memory,
+            disk_read: disk.read_bytes as f64 / secs,
+            disk_write: disk.written_bytes as f64 / secs,
+            net_rx: net_usage.rx,
+            net_tx: net_usage.tx,
+            parent: proc_info.parent().map(|p| p.as_u32()),
+            uid: proc_info.user_id().map(|u| **u),
+            is_thread: proc_info.thread_kind().is_some(),
+            kernel_thread: proc_info.thread_kind() == Some(ThreadKind::Kernel),
+            start_time: proc_info.start_time(),
+            cmd: proc_info
+                .cmd()
+                .iter()
+                .map(|a| a.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" "),
+            state: stat.state,
+            threads: stat.threads,
+            nice: stat.nice,
+            priority: stat.priority,
+            run_time: proc_info.run_time(),
+            virtual_memory: proc_info.virtual_memory(),
+            shared_memory: stat.shared,
+            cgroup: stat.cgroup,
+        }
+    }
+
+    pub fn key(&self) -> ProcessKey {
+        (self.pid, self.start_time)
+    }
+}
+
+/// The optional `/proc/<pid>` files a refresh reads. They back only a few
+/// columns, and reading three files per process adds up, so the table asks
+/// for just the ones it shows.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ProcFiles {
+    pub stat: bool, // state, threads, nice, priority
+    pub statm: bool, // shared memory
+    pub cgroup: bool,
+    pub net: bool, // socket links under fd/, for network rates
+}
+
+impl ProcFiles {
+    pub const ALL: Self = Self { stat: true, statm: true, cgroup: true, net: true };
+    pub const NONE: Self = Self { stat: false, statm: false, cgroup: false, net: false };
+
+    /// Only the files behind `columns`.
+    pub fn for_columns(columns: &[Column]) -> Self {
+        let any = |wanted: &[Column]| columns.iter().any(|c| wanted.contains(c));
+        Self {
+            stat: any(&[Column::State, Column::Threads, Column::Nice, Column::Priority]),
+            statm: any(&[Column::Shared]),
+            cgroup: any(&[Column::Cgroup]),
+            net: any(&[Column::NetRx, Column::NetTx]),
+        }
+    }
+}
+
+/// The parts of `/proc/<pid>/stat`, `statm` and `cgroup` sysinfo doesn't expose.
+#[derive(Default)]
+pub(super) struct ProcStat {
+    state: String,
+    priority: i32,
+    nice: i32,
+    threads: u32,
+    shared: u64, // bytes
+    cgroup: String,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum SortColumn {
+impl ProcStat {
+    /// Read those of `files` that are wanted; the rest stay empty.
+    pub(super) fn read(fs: &dyn SysFs, pid: u32, files: ProcFiles) -> Self {
+        let dir = PathBuf::from(format!("/proc/{}", pid));
+        let mut stat = Self::default();
+        if files.stat {
+            if let Ok(text) = fs.read_to_string(&dir.join("stat")) {
+                stat.parse_stat(&text);
+            }
+        }
+        if files.statm {
+            let shared_pages: u64 = fs
+                .read_to_string(&dir.join("statm"))
+                .ok()
+                .and_then(|t| t.split_whitespace().nth(2)?.parse().ok())
+                .unwrap_or(0);
+            stat.shared = shared_pages * page_size();
+        }
+        if files.cgroup {
+            stat.cgroup = fs.read_to_string(&dir.join("cgroup")).map(|t| parse_cgroup(&t)).unwrap_or_default();
+        }
+        stat
+    }
+
+    fn parse_stat(&mut self, text: &str) {
+        let Some(end) = text.rfind(')') else { return };
+        // Fields after the parenthesised name start at 3 (state)
+        let fields: Vec<&str> = text[end + 1..].split_whitespace().collect();
+        let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse().ok()).unwrap_or(0);
+        self.state = fields.first().map(|s| s.to_string()).unwrap_or_default();
+        self.priority = field(18);
+        self.nice = field(19);
+        self.threads = field(20) as u32;
+    }
+}
+
+fn page_size() -> u64 {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 { size as u64 } else { 4096 }
+}
+
+/// One line of the process table. In tree mode `info` carries the CPU, GPU
+/// and memory totals of the whole subtree; `own_*` keep the process's own.
+pub struct ProcessRow {
+    pub info: ProcessInfo,
+    pub own_cpu: f32,
+    pub own_memory: u64,
+    pub depth: usize,
+    pub children: usize,
+    pub descendants: usize,
+    pub expanded: bool,
+}
+
+/// A process table column; every column can be shown, hidden and sorted by.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
     Pid,
     Name,
     User,
     Cpu,
     Memory,
+    Gpu,
+    DiskRead,
+    DiskWrite,
+    NetRx,
+    NetTx,
+    Ppid,
+    State,
+    Threads,
+    Nice,
+    Priority,
+    StartTime,
+    Elapsed,
+    Virtual,
+    Shared,
+    Command,
+    Cgroup,
+}
+
+impl Column {
+    pub const ALL: [Column; 21] = [
+        Column::Name,
+        Column::Pid,
+        Column::Ppid,
+        Column::User,
+        Column::State,
+        Column::Threads,
+        Column::Nice,
+        Column::Priority,
+        Column::StartTime,
+        Column::Elapsed,
+        Column::Memory,
+        Column::Virtual,
+        Column::Shared,
+        Column::Cpu,
+        Column::Gpu,
+        Column::DiskRead,
+        Column::DiskWrite,
+        Column::NetRx,
+        Column::NetTx,
+        Column::Command,
+        Column::Cgroup,
+    ];
+
+    /// Shown when the config doesn't list any.
+    pub const DEFAULT: [Column; 6] = [Column::Name, Column::Pid, Column::User, Column::Memory, Column::Cpu, Column::Gpu];
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortOrder {
     Ascending,
     Descending,
@@ -34,26 +281,154 @@ impl SortOrder {
     }
 }
 
+/// Incremental filter applied to the process table before truncation.
+pub struct ProcessFilter {
+    pub text: String,
+    pub regex_mode: bool,
+    pub mine_only: bool,
+    pub hide_system: bool,
+    uid_min: libc::uid_t, // first uid of a regular user; `hide_system` hides those below
+    regex: Option<Regex>,
+    pub error: Option<String>,
+}
+
+impl Default for ProcessFilter {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            regex_mode: false,
+            mine_only: false,
+            hide_system: false,
+            uid_min: login_uid_min(),
+            regex: None,
+            error: None,
+        }
+    }
+}
+
+/// Where `useradd` starts regular users: `UID_MIN` in the host's
+/// `/etc/login.defs`, read once, else 1000.
+fn login_uid_min() -> libc::uid_t {
+    static UID_MIN: OnceLock<libc::uid_t> = OnceLock::new();
+    *UID_MIN.get_or_init(|| {
+        std::fs::read_to_string("/etc/login.defs")
+            .ok()
+            .and_then(|text| parse_uid_min(&text))
+            .unwrap_or(1000)
+    })
+}
+
+fn parse_uid_min(login_defs: &str) -> Option<libc::uid_t> {
+    login_defs.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next() == Some("UID_MIN")).then(|| fields.next()?.parse().ok()).flatten()
+    })
+}
+
+impl ProcessFilter {
+    /// Recompile the pattern; call after editing `text` or toggling `regex_mode`.
+    pub fn update(&mut self) {
+        self.regex = None;
+        self.error = None;
+        if self.regex_mode && !self.text.is_empty() {
+            match RegexBuilder::new(&self.text).case_insensitive(true).build() {
+                Ok(re) => self.regex = Some(re),
+                Err(e) => self.error = Some(e.to_string().lines().last().unwrap_or("").to_uppercase()),
+            }
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty() || self.mine_only || self.hide_system
+    }
+
+    fn matches(&self, info: &ProcessInfo, my_uid: libc::uid_t) -> bool {
+        let uid = info.uid;
+        if self.mine_only && uid != Some(my_uid) {
+            return false;
+        }
+        // System accounts and kernel threads
+        if self.hide_system && (uid.unwrap_or(0) < self.uid_min || info.kernel_thread) {
+            return false;
+        }
+        if self.text.is_empty() || self.error.is_some() {
+            return true;
+        }
+
+        let pid = info.pid.to_string();
+        let fields = [info.name.as_str(), pid.as_str(), info.user.as_str(), info.cmd.as_str()];
+
+        match &self.regex {
+            Some(re) => fields.iter().any(|f| re.is_match(f)),
+            None => {
+                let needle = self.text.to_lowercase();
+                fields.iter().any(|f| f.to_lowercase().contains(&needle))
+            }
+        }
+    }
+}
+
+/// A signal or renice waiting for the user to confirm it in the action bar.
+pub struct PendingAction {
+    pub targets: Vec<ProcessKey>, // the chosen process first, then its subtree
+    pub name: String,
+    pub action: ProcessAction,
+}
+
 pub struct ProcessView {
-    pub sort_column: SortColumn,
+    pub sort_column: Column,
     pub sort_order: SortOrder,
     pub limit: usize,
-    pub expanded_pids: HashSet<u32>,
+    pub tree: bool,
+    pub columns: Vec<Column>,
+    pub widths: BTreeMap<Column, f32>, // only columns resized from their default
+    pub expanded: HashSet<ProcessKey>,
+    pub filter: ProcessFilter,
+    pub selected_pid: Option<u32>,
+    pub renice_value: i32,
+    pub pending_action: Option<PendingAction>,
+    pub action_status: Option<(String, bool)>, // (message, is_error)
+    pub inspect_pid: Option<u32>, // row asked to open the detail view
+    pub scroll_to_selected: bool,  // selection moved by keyboard
+    pub read_only: bool, // replayed processes; their PIDs mean nothing on this host
 }
 
 impl Default for ProcessView {
     fn default() -> Self {
         Self {
-            sort_column: SortColumn::Memory,
+            sort_column: Column::Memory,
             sort_order: SortOrder::Descending,
             limit: 50,
-            expanded_pids: HashSet::new(),
+            tree: false,
+            columns: Column::DEFAULT.to_vec(),
+            widths: BTreeMap::new(),
+            expanded: HashSet::new(),
+            filter: ProcessFilter::default(),
+            selected_pid: None,
+            renice_value: 0,
+            pending_action: None,
+            action_status: None,
+            inspect_pid: None,
+            scroll_to_selected: false,
+            read_only: false,
         }
     }
 }
 
 impl ProcessView {
-    pub fn toggle_sort(&mut self, column: SortColumn) {
+    pub fn new(sort_column: Column, sort_order: SortOrder, limit: usize) -> Self {
+        Self { sort_column, sort_order, limit, ..Self::default() }
+    }
+
+    /// The procfs files the table needs: those behind the shown columns and
+    /// the sort column. Filters only look at name, PID, user and command line.
+    pub fn proc_files(&self) -> ProcFiles {
+        let mut columns = self.columns.clone();
+        columns.push(self.sort_column);
+        ProcFiles::for_columns(&columns)
+    }
+
+    pub fn toggle_sort(&mut self, column: Column) {
         if self.sort_column == column {
             self.sort_order.toggle();
         } else {
@@ -62,79 +437,599 @@ impl ProcessView {
         }
     }
 
-    pub fn toggle_expanded(&mut self, pid: u32) {
-        if !self.expanded_pids.remove(&pid) {
-            self.expanded_pids.insert(pid);
+    /// Sort by the next shown column, keeping the direction.
+    pub fn cycle_sort(&mut self) {
+        let i = self.columns.iter().position(|c| *c == self.sort_column);
+        self.sort_column = self.columns[i.map_or(0, |i| (i + 1) % self.columns.len())];
+    }
+
+    /// Select the row `offset` places from the selected one, or the first
+    /// (moving down) or last (moving up) row when none is selected.
+    pub fn move_selection(&mut self, all: &[ProcessInfo], offset: isize) {
+        let rows = self.rows(all);
+        if rows.is_empty() {
+            return;
+        }
+        let last = rows.len() - 1;
+        let i = match rows.iter().position(|r| Some(r.info.pid) == self.selected_pid) {
+            Some(i) => i.saturating_add_signed(offset).min(last),
+            None if offset < 0 => last,
+            None => 0,
+        };
+        self.selected_pid = Some(rows[i].info.pid);
+        self.scroll_to_selected = true;
+    }
+
+    /// The selected process, if it is still running.
+    pub fn selected<'a>(&self, all: &'a [ProcessInfo]) -> Option<&'a ProcessInfo> {
+        all.iter().find(|p| Some(p.pid) == self.selected_pid)
+    }
+
+    /// Show `column` at the end of the table, or hide it. The name column stays.
+    pub fn toggle_column(&mut self, column: Column) {
+        if let Some(i) = self.columns.iter().position(|c| *c == column) {
+            if column != Column::Name {
+                self.columns.remove(i);
+            }
+        } else {
+            self.columns.push(column);
         }
     }
 
-    pub fn is_expanded(&self, pid: u32) -> bool {
-        self.expanded_pids.contains(&pid)
+    /// Move a shown column `offset` places left (negative) or right.
+    pub fn move_column(&mut self, column: Column, offset: isize) {
+        if let Some(i) = self.columns.iter().position(|c| *c == column) {
+            let j = i.saturating_add_signed(offset).min(self.columns.len() - 1);
+            let c = self.columns.remove(i);
+            self.columns.insert(j, c);
+        }
     }
 
-    pub fn get_processes(&self, system: &System, users: &Users) -> Vec<ProcessInfo> {
-        let mut procs: Vec<ProcessInfo> = system
-            .processes()
-            .iter()
-            .map(|(pid, proc_info)| {
-                let user = proc_info
-                    .user_id()
-                    .and_then(|uid| users.get_user_by_id(uid))
-                    .map(|u| u.name().to_string())
-                    .unwrap_or_else(|| "?".to_string());
-                ProcessInfo {
-                    pid: pid.as_u32(),
-                    name: proc_info.name().to_string_lossy().to_string(),
-                    user,
-                    cpu_usage: proc_info.cpu_usage(),
-                    memory: proc_info.memory(),
-                }
-            })
-            .collect();
+    pub fn toggle_expanded(&mut self, key: ProcessKey) {
+        if !self.expanded.remove(&key) {
+            self.expanded.insert(key);
+        }
+    }
 
-        match self.sort_column {
-            SortColumn::Pid => procs.sort_by(|a, b| a.pid.cmp(&b.pid)),
-            SortColumn::Name => procs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
-            SortColumn::User => procs.sort_by(|a, b| a.user.to_lowercase().cmp(&b.user.to_lowercase())),
-            SortColumn::Cpu => procs.sort_by(|a, b| a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(std::cmp::Ordering::Equal)),
-            SortColumn::Memory => procs.sort_by(|a, b| a.memory.cmp(&b.memory)),
+    pub fn is_expanded(&self, key: ProcessKey) -> bool {
+        self.expanded.contains(&key)
+    }
+
+    /// Expand every process that currently has children.
+    pub fn expand_all(&mut self, all: &[ProcessInfo]) {
+        let parents: HashSet<u32> = all.iter().filter_map(|p| p.parent).collect();
+        self.expanded.extend(all.iter().filter(|p| parents.contains(&p.pid)).map(ProcessInfo::key));
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.expanded.clear();
+    }
+
+    /// Drop expansion state of processes that have exited.
+    pub fn forget_exited(&mut self, all: &[ProcessInfo]) {
+        if self.expanded.is_empty() {
+            return;
+        }
+        let live: HashSet<ProcessKey> = all.iter().map(ProcessInfo::key).collect();
+        self.expanded.retain(|key| live.contains(key));
+    }
+
+    /// Queue `action` for confirmation; expanded processes take their whole subtree with them.
+    pub fn request_action(&mut self, pid: u32, name: String, action: ProcessAction, procs: &[ProcessInfo]) {
+        if self.read_only {
+            return;
+        }
+        let expanded = procs.iter().any(|p| p.pid == pid && self.is_expanded(p.key()));
+        let pids = if expanded {
+            subtree_pids(pid, procs)
+        } else {
+            vec![pid]
+        };
+        // Keyed by start time too, as confirming may come long after a PID was reused
+        let targets: Vec<ProcessKey> =
+            pids.iter().filter_map(|pid| procs.iter().find(|p| p.pid == *pid).map(ProcessInfo::key)).collect();
+        if targets.is_empty() {
+            return;
         }
+        self.action_status = None;
+        self.pending_action = Some(PendingAction { targets, name, action });
+    }
 
-        if self.sort_order == SortOrder::Descending {
-            procs.reverse();
+    /// Send the pending action to those of its targets still running.
+    pub fn confirm_action(&mut self) {
+        if self.read_only {
+            return;
+        }
+        if let Some(pending) = self.pending_action.take() {
+            self.action_status = Some(signal::apply_all(&pending.targets, pending.action));
         }
+    }
+
+    pub fn cancel_action(&mut self) {
+        self.pending_action = None;
+    }
 
+    /// Filtered, sorted and truncated copy of `all` for the table.
+    pub fn get_processes(&self, all: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        let my_uid = unsafe { libc::getuid() };
+        let mut procs: Vec<ProcessInfo> = all
+            .iter()
+            .filter(|info| self.filter.matches(info, my_uid))
+            .cloned()
+            .collect();
+
+        procs.sort_by(|a, b| self.compare(a, b));
         procs.truncate(self.limit);
         procs
     }
 
-    pub fn get_children(&self, parent_pid: u32, system: &System, users: &Users) -> Vec<ProcessInfo> {
-        let mut children: Vec<ProcessInfo> = system
-            .processes()
-            .iter()
-            .filter_map(|(pid, proc_info)| {
-                if proc_info.parent() == Some(Pid::from_u32(parent_pid)) {
-                    let user = proc_info
-                        .user_id()
-                        .and_then(|uid| users.get_user_by_id(uid))
-                        .map(|u| u.name().to_string())
-                        .unwrap_or_else(|| "?".to_string());
-                    Some(ProcessInfo {
-                        pid: pid.as_u32(),
-                        name: proc_info.name().to_string_lossy().to_string(),
-                        user,
-                        cpu_usage: proc_info.cpu_usage(),
-                        memory: proc_info.memory(),
-                    })
-                } else {
-                    None
-                }
+    /// Table order for the current sort column and direction.
+    fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        self.compare_with(a, &Totals::of(a), b, &Totals::of(b))
+    }
+
+    /// `compare`, with the usage columns taken from `at` and `bt` rather
+    /// than the processes themselves.
+    fn compare_with(&self, a: &ProcessInfo, at: &Totals, b: &ProcessInfo, bt: &Totals) -> Ordering {
+        let ordering = match self.sort_column {
+            Column::Pid => a.pid.cmp(&b.pid),
+            Column::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Column::User => a.user.to_lowercase().cmp(&b.user.to_lowercase()),
+            Column::Cpu => at.cpu_usage.partial_cmp(&bt.cpu_usage).unwrap_or(Ordering::Equal),
+            Column::Memory => at.memory.cmp(&bt.memory),
+            Column::Gpu => at.gpu_usage.partial_cmp(&bt.gpu_usage).unwrap_or(Ordering::Equal),
+            Column::DiskRead => at.disk_read.total_cmp(&bt.disk_read),
+            Column::DiskWrite => at.disk_write.total_cmp(&bt.disk_write),
+            Column::NetRx => at.net_rx.total_cmp(&bt.net_rx),
+            Column::NetTx => at.net_tx.total_cmp(&bt.net_tx),
+            Column::Ppid => a.parent.cmp(&b.parent),
+            Column::State => a.state.cmp(&b.state),
+            Column::Threads => a.threads.cmp(&b.threads),
+            Column::Nice => a.nice.cmp(&b.nice),
+            Column::Priority => a.priority.cmp(&b.priority),
+            Column::StartTime => a.start_time.cmp(&b.start_time),
+            Column::Elapsed => a.run_time.cmp(&b.run_time),
+            Column::Virtual => a.virtual_memory.cmp(&b.virtual_memory),
+            Column::Shared => a.shared_memory.cmp(&b.shared_memory),
+            Column::Command => a.cmd.cmp(&b.cmd),
+            Column::Cgroup => a.cgroup.cmp(&b.cgroup),
+        };
+        match self.sort_order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    }
+
+    /// Rows for the table: the sorted top `limit`, or in tree mode every
+    /// process nested under its parent, down to the collapsed nodes.
+    pub fn rows(&self, all: &[ProcessInfo]) -> Vec<ProcessRow> {
+        if self.tree {
+            return self.tree_rows(all);
+        }
+        self.get_processes(all)
+            .into_iter()
+            .map(|info| ProcessRow {
+                own_cpu: info.cpu_usage,
+                own_memory: info.memory,
+                depth: 0,
+                children: 0,
+                descendants: 0,
+                expanded: self.is_expanded(info.key()),
+                info,
             })
+            .collect()
+    }
+
+    /// The process forest from PID 1 and kthreadd down, siblings in table
+    /// order. While filtering, matches are shown with their ancestors even
+    /// under collapsed nodes.
+    fn tree_rows(&self, all: &[ProcessInfo]) -> Vec<ProcessRow> {
+        // Userland tasks are threads of their parent, not processes of their own
+        let nodes: Vec<&ProcessInfo> = all.iter().filter(|p| !p.is_thread || p.kernel_thread).collect();
+        let index: HashMap<u32, usize> = nodes.iter().enumerate().map(|(i, p)| (p.pid, i)).collect();
+        let mut children = vec![Vec::new(); nodes.len()];
+        let mut roots = Vec::new();
+        for (i, p) in nodes.iter().enumerate() {
+            match p.parent.and_then(|parent| index.get(&parent)) {
+                Some(&parent) if parent != i => children[parent].push(i),
+                _ => roots.push(i),
+            }
+        }
+
+        let my_uid = unsafe { libc::getuid() };
+        let filtering = self.filter.is_active();
+        let mut tree = Tree {
+            totals: nodes.iter().map(|p| Totals::of(p)).collect(),
+            descendants: vec![0; nodes.len()],
+            visible: nodes.iter().map(|p| !filtering || self.filter.matches(p, my_uid)).collect(),
+            children,
+        };
+        for &root in &roots {
+            tree.aggregate(root);
+        }
+        for list in tree.children.iter_mut().chain(std::iter::once(&mut roots)) {
+            list.sort_by(|&a, &b| self.compare_with(nodes[a], &tree.totals[a], nodes[b], &tree.totals[b]));
+        }
+
+        let mut rows = Vec::new();
+        // Depth-first, children pushed in reverse so they pop in order
+        let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&i| (i, 0)).collect();
+        while let Some((i, depth)) = stack.pop() {
+            if !tree.visible[i] {
+                continue;
+            }
+            let expanded = self.is_expanded(nodes[i].key());
+            if expanded || filtering {
+                stack.extend(tree.children[i].iter().rev().map(|&c| (c, depth + 1)));
+            }
+            let mut info = nodes[i].clone();
+            tree.totals[i].apply(&mut info);
+            rows.push(ProcessRow {
+                info,
+                own_cpu: nodes[i].cpu_usage,
+                own_memory: nodes[i].memory,
+                depth,
+                children: tree.children[i].len(),
+                descendants: tree.descendants[i],
+                expanded,
+            });
+        }
+        rows
+    }
+
+    pub fn get_children(&self, parent_pid: u32, all: &[ProcessInfo]) -> Vec<ProcessInfo> {
+        let mut children: Vec<ProcessInfo> = all
+            .iter()
+            .filter(|p| p.parent == Some(parent_pid))
+            .cloned()
             .collect();
 
-        children.sort_by(|a, b| {
-            b.memory.cmp(&a.memory)
-        });
+        children.sort_by_key(|c| std::cmp::Reverse(c.memory));
         children
     }
 }
+
+/// The usage columns a tree node sums over its subtree.
+#[derive(Clone, Copy)]
+struct Totals {
+    cpu_usage: f32,
+    memory: u64,
+    gpu_usage: f32,
+    gpu_memory: u64,
+    disk_read: f64,
+    disk_write: f64,
+    net_rx: f64,
+    net_tx: f64,
+}
+
+impl Totals {
+    fn of(p: &ProcessInfo) -> Self {
+        Self {
+            cpu_usage: p.cpu_usage,
+            memory: p.memory,
+            gpu_usage: p.gpu_usage,
+            gpu_memory: p.gpu_memory,
+            disk_read: p.disk_read,
+            disk_write: p.disk_write,
+            net_rx: p.net_rx,
+            net_tx: p.net_tx,
+        }
+    }
+
+    fn add(&mut self, t: &Totals) {
+        self.cpu_usage += t.cpu_usage;
+        self.memory += t.memory;
+        self.gpu_usage += t.gpu_usage;
+        self.gpu_memory += t.gpu_memory;
+        self.disk_read += t.disk_read;
+        self.disk_write += t.disk_write;
+        self.net_rx += t.net_rx;
+        self.net_tx += t.net_tx;
+    }
+
+    /// Show these totals in place of `info`'s own usage.
+    fn apply(&self, info: &mut ProcessInfo) {
+        info.cpu_usage = self.cpu_usage;
+        info.memory = self.memory;
+        info.gpu_usage = self.gpu_usage;
+        info.gpu_memory = self.gpu_memory;
+        info.disk_read = self.disk_read;
+        info.disk_write = self.disk_write;
+        info.net_rx = self.net_rx;
+        info.net_tx = self.net_tx;
+    }
+}
+
+/// Working state for `tree_rows`, indexed like its node list.
+struct Tree {
+    totals: Vec<Totals>,
+    descendants: Vec<usize>,
+    visible: Vec<bool>, // matches the filter or has a descendant that does
+    children: Vec<Vec<usize>>,
+}
+
+impl Tree {
+    /// Fold each subtree's usage into its root, post-order.
+    fn aggregate(&mut self, root: usize) {
+        let mut order = vec![root];
+        let mut i = 0;
+        while i < order.len() {
+            order.extend_from_slice(&self.children[order[i]]);
+            i += 1;
+        }
+        for &node in order.iter().rev() {
+            for c in 0..self.children[node].len() {
+                let child = self.children[node][c];
+                let t = self.totals[child];
+                self.totals[node].add(&t);
+                self.descendants[node] += 1 + self.descendants[child];
+                self.visible[node] |= self.visible[child];
+            }
+        }
+    }
+}
+
+/// `root` followed by all of its descendants, breadth-first. Each PID is
+/// listed once, so a parent loop in a torn read of `/proc` still ends.
+fn subtree_pids(root: u32, procs: &[ProcessInfo]) -> Vec<u32> {
+    let mut pids = vec![root];
+    let mut seen = HashSet::from([root]);
+    let mut i = 0;
+    while i < pids.len() {
+        let parent = pids[i];
+        for p in procs.iter().filter(|p| p.parent == Some(parent) && !p.is_thread) {
+            if seen.insert(p.pid) {
+                pids.push(p.pid);
+            }
+        }
+        i += 1;
+    }
+    pids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    fn proc(pid: u32, parent: Option<u32>, memory: u64, cpu: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: format!("proc{}", pid),
+            user: "kirk".to_string(),
+            cpu_usage: cpu,
+            memory,
+            gpu_usage: 0.0,
+            gpu_memory: 0,
+            disk_read: 0.0,
+            disk_write: 0.0,
+            net_rx: 0.0,
+            net_tx: 0.0,
+            parent,
+            uid: Some(1000),
+            is_thread: false,
+            kernel_thread: false,
+            start_time: 1000 + pid as u64,
+            cmd: String::new(),
+            state: "S".to_string(),
+            threads: 1,
+            nice: 0,
+            priority: 20,
+            run_time: 0,
+            virtual_memory: 0,
+            shared_memory: 0,
+            cgroup: String::new(),
+        }
+    }
+
+    #[test]
+    fn nests_processes_and_totals_subtrees() {
+        let mut procs = vec![
+            proc(1, None, 10, 1.0),
+            proc(2, None, 0, 0.0),
+            proc(100, Some(1), 100, 2.0),
+            proc(101, Some(100), 1000, 4.0),
+            proc(200, Some(1), 50, 0.5),
+        ];
+        let mut kworker = proc(3, Some(2), 0, 0.0);
+        (kworker.is_thread, kworker.kernel_thread) = (true, true);
+        let mut task = proc(102, Some(101), 1000, 4.0);
+        task.is_thread = true;
+        procs.extend([kworker, task]);
+
+        let mut pv = ProcessView { tree: true, ..ProcessView::default() };
+        let pids = |pv: &ProcessView, procs: &[ProcessInfo]| pv.rows(procs).iter().map(|r| r.info.pid).collect::<Vec<_>>();
+        assert_eq!(pids(&pv, &procs), [1, 2], "roots only while collapsed");
+
+        pv.expand_all(&procs);
+        let rows = pv.rows(&procs);
+        let order: Vec<(u32, usize)> = rows.iter().map(|r| (r.info.pid, r.depth)).collect();
+        assert_eq!(order, [(1, 0), (100, 1), (101, 2), (200, 1), (2, 0), (3, 1)], "userland task left out");
+        assert_eq!((rows[0].info.memory, rows[0].info.cpu_usage, rows[0].descendants), (1160, 7.5, 3));
+        assert_eq!((rows[0].own_memory, rows[0].children), (10, 2));
+
+        // A reused PID starts collapsed
+        procs[2].start_time += 1;
+        pv.forget_exited(&procs);
+        assert_eq!(pids(&pv, &procs), [1, 100, 200, 2, 3]);
+
+        // Matches appear under collapsed ancestors
+        pv.collapse_all();
+        pv.filter.text = "proc101".to_string();
+        assert_eq!(pids(&pv, &procs), [1, 100, 101]);
+    }
+
+    #[test]
+    fn filters_by_text_regex_and_owner() {
+        let mut sshd = proc(1, None, 0, 0.0);
+        (sshd.name, sshd.user, sshd.uid) = ("sshd".to_string(), "root".to_string(), Some(0));
+        let mut kworker = proc(2, None, 0, 0.0);
+        (kworker.name, kworker.user, kworker.uid) = ("kworker/0:1".to_string(), "root".to_string(), Some(0));
+        (kworker.is_thread, kworker.kernel_thread) = (true, true);
+        let mut vim = proc(300, None, 0, 0.0);
+        (vim.name, vim.cmd) = ("vim".to_string(), "vim /etc/Warp.conf".to_string());
+        let mut task = proc(301, Some(300), 0, 0.0);
+        task.is_thread = true;
+        let mut other = proc(400, None, 0, 0.0);
+        (other.user, other.uid) = ("spock".to_string(), Some(1001));
+        let procs = [sshd, kworker, vim, task, other];
+
+        let mut filter = ProcessFilter::default();
+        let pids = |f: &ProcessFilter, uid| procs.iter().filter(|p| f.matches(p, uid)).map(|p| p.pid).collect::<Vec<_>>();
+        assert_eq!(pids(&filter, 1000), [1, 2, 300, 301, 400]);
+
+        filter.text = "WARP".to_string();
+        filter.update();
+        assert_eq!(pids(&filter, 1000), [300], "substring, any case, matches the command line");
+        filter.text = "^proc30".to_string();
+        assert!(pids(&filter, 1000).is_empty(), "no regex outside regex mode");
+
+        filter.regex_mode = true;
+        filter.update();
+        assert_eq!(pids(&filter, 1000), [301]);
+        filter.text = "^(sshd|KWORKER)".to_string();
+        filter.update();
+        assert_eq!(pids(&filter, 1000), [1, 2]);
+
+        filter.text = "proc(".to_string();
+        filter.update();
+        assert!(filter.error.is_some());
+        assert_eq!(pids(&filter, 1000).len(), procs.len(), "a broken pattern hides nothing");
+        filter.regex_mode = false;
+        filter.update();
+        assert_eq!((filter.error.as_deref(), pids(&filter, 1000)), (None, vec![]));
+
+        filter.text.clear();
+        filter.mine_only = true;
+        assert_eq!(pids(&filter, 1000), [300, 301]);
+        assert_eq!(pids(&filter, 1001), [400]);
+
+        filter.mine_only = false;
+        filter.hide_system = true;
+        filter.uid_min = 1000;
+        assert_eq!(pids(&filter, 1000), [300, 301, 400], "userland tasks stay");
+        let mut stray = procs[1].clone();
+        stray.uid = Some(1000);
+        assert!(!filter.matches(&stray, 1000), "kernel threads go whatever their uid");
+        filter.uid_min = 1001;
+        assert_eq!(pids(&filter, 1000), [400], "the cut-off follows UID_MIN");
+    }
+
+    #[test]
+    fn reads_uid_min_from_login_defs() {
+        let defs = "# UID_MIN 2000\nMAIL_DIR /var/spool/mail\nUID_MIN\t\t\t 60000\n#SYS_UID_MIN 100\nSUB_UID_MIN 100000\n";
+        assert_eq!(parse_uid_min(defs), Some(60000));
+        assert_eq!(parse_uid_min("UID_MAX 60000\n"), None);
+    }
+
+    #[test]
+    fn reads_columns_sysinfo_lacks() {
+        let fs = fixture("process");
+        let stat = ProcStat::read(fs.as_ref(), 4242, ProcFiles::ALL);
+        assert_eq!((stat.state.as_str(), stat.priority, stat.nice, stat.threads), ("S", 20, 0, 12));
+        assert_eq!(stat.shared, 2048 * page_size());
+        assert_eq!(stat.cgroup, "/user.slice/user-1000.slice/session-2.scope");
+
+        let only_stat = ProcFiles { stat: true, ..ProcFiles::NONE };
+        let stat = ProcStat::read(fs.as_ref(), 4242, only_stat);
+        assert_eq!((stat.state.as_str(), stat.threads, stat.shared, stat.cgroup.as_str()), ("S", 12, 0, ""));
+    }
+
+    #[test]
+    fn reads_only_the_files_shown_columns_need() {
+        let mut pv = ProcessView::default();
+        let none = ProcFiles::NONE;
+        assert_eq!(pv.proc_files(), none, "the default columns come from sysinfo");
+
+        pv.toggle_column(Column::Threads);
+        assert_eq!(pv.proc_files(), ProcFiles { stat: true, ..none });
+        pv.toggle_column(Column::Threads);
+        pv.toggle_column(Column::Cgroup);
+        assert_eq!(pv.proc_files(), ProcFiles { cgroup: true, ..none });
+
+        pv.sort_column = Column::Shared;
+        assert_eq!(pv.proc_files(), ProcFiles { statm: true, cgroup: true, ..none }, "sorting by a hidden column");
+
+        pv.sort_column = Column::NetTx;
+        assert_eq!(pv.proc_files(), ProcFiles { cgroup: true, net: true, ..none });
+    }
+
+    #[test]
+    fn reorders_columns() {
+        let mut pv = ProcessView::default();
+        pv.toggle_column(Column::Threads);
+        pv.move_column(Column::Threads, -10);
+        pv.toggle_column(Column::Name);
+        pv.toggle_column(Column::Gpu);
+        assert_eq!(pv.columns, [Column::Threads, Column::Name, Column::Pid, Column::User, Column::Memory, Column::Cpu]);
+    }
+
+    #[test]
+    fn walks_subtrees_breadth_first() {
+        let mut thread = proc(12, Some(1), 0, 0.0);
+        thread.is_thread = true;
+        let procs = vec![
+            proc(1, None, 0, 0.0),
+            proc(10, Some(1), 0, 0.0),
+            proc(100, Some(10), 0, 0.0),
+            proc(11, Some(1), 0, 0.0),
+            proc(110, Some(11), 0, 0.0),
+            thread,
+            // A parent loop, as a torn read of /proc can produce
+            proc(500, Some(501), 0, 0.0),
+            proc(501, Some(500), 0, 0.0),
+            proc(600, Some(600), 0, 0.0),
+        ];
+        assert_eq!(subtree_pids(1, &procs), [1, 10, 11, 100, 110], "threads are signalled with their process");
+        assert_eq!(subtree_pids(500, &procs), [500, 501]);
+        assert_eq!(subtree_pids(600, &procs), [600]);
+    }
+
+    #[test]
+    fn confirms_only_against_the_same_process() {
+        let me = std::process::id();
+        let mut live = proc(me, None, 0, 0.0);
+        live.start_time = signal::start_time(me).unwrap();
+        let mut pv = ProcessView::default();
+
+        // The table's reading says the PID now belongs to a process started
+        // later; /proc is what counts when sending
+        let mut stale = live.clone();
+        stale.start_time += 60;
+        pv.request_action(me, "self".to_string(), ProcessAction::Continue, &[stale]);
+        assert_eq!(pv.pending_action.as_ref().unwrap().targets, [(me, live.start_time + 60)]);
+        pv.confirm_action();
+        let (message, failed) = pv.action_status.clone().unwrap();
+        assert!(failed);
+        assert_eq!(message, format!("SIGCONT FAILED FOR PID {}: NO SUCH PROCESS", me));
+
+        pv.request_action(me, "self".to_string(), ProcessAction::Continue, &[live]);
+        pv.confirm_action();
+        assert_eq!(pv.action_status, Some((format!("SIGCONT SENT TO PID {}", me), false)));
+    }
+
+    #[test]
+    fn replayed_processes_take_no_actions() {
+        let mut pv = ProcessView { read_only: true, ..ProcessView::default() };
+        pv.request_action(1, "init".to_string(), ProcessAction::Kill, &[proc(1, None, 0, 0.0)]);
+        assert!(pv.pending_action.is_none());
+    }
+
+    #[test]
+    fn moves_selection_through_rows() {
+        let procs = vec![proc(1, None, 30, 0.0), proc(2, None, 20, 0.0), proc(3, None, 10, 0.0)];
+        let mut pv = ProcessView::default();
+        pv.move_selection(&procs, -1);
+        assert_eq!(pv.selected_pid, Some(3), "up from nothing selects the last row");
+        pv.move_selection(&procs, -5);
+        assert_eq!(pv.selected_pid, Some(1));
+        pv.move_selection(&procs, 1);
+        assert_eq!(pv.selected_pid, Some(2));
+
+        pv.cycle_sort();
+        assert_eq!(pv.sort_column, Column::Cpu);
+        pv.sort_column = Column::Gpu;
+        pv.cycle_sort();
+        assert_eq!(pv.sort_column, Column::Name, "wraps to the first column");
+    }
+}
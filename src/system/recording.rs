@@ -0,0 +1,438 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use super::history::History;
+use super::sampler::Sample;
+
+/// File layout: `MAGIC`, a little-endian u32 `VERSION`, then one record per
+/// sample: u32 payload length, i64 Unix time in milliseconds, and the sample
+/// as deflate-compressed JSON. Each record stands alone, so a recording can
+/// be indexed without decompressing it and a cut-off tail loses one sample.
+/// Since version 2 records hold no sparkline history; `Player` rebuilds it.
+const MAGIC: &[u8; 8] = b"LCARSREC";
+const VERSION: u32 = 2;
+const FULL_HISTORY_VERSION: u32 = 1; // still replayed, never appended to
+const FILE_HEADER: u64 = 12;
+const RECORD_HEADER: u64 = 12;
+
+/// Playback rates offered in the footer.
+pub const SPEEDS: [f64; 9] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+/// Appends samples to a recording, continuing an existing one if present.
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn append(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        if file.metadata()?.len() == 0 {
+            file.write_all(MAGIC)?;
+            file.write_all(&VERSION.to_le_bytes())?;
+        } else {
+            let (version, records) = scan(&mut file)?;
+            if version != VERSION {
+                return Err(invalid(&format!("recording version {} can't be appended to", version)));
+            }
+            // Drop a record cut short by a crash so new ones stay readable
+            let end = records.last().map_or(FILE_HEADER, |r| r.end);
+            file.set_len(end)?;
+        }
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self { out: BufWriter::new(file) })
+    }
+
+    pub fn write(&mut self, sample: &Sample) -> io::Result<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, sample)?;
+        let payload = encoder.finish()?;
+        let len = u32::try_from(payload.len()).map_err(|_| io::Error::other("sample too large"))?;
+
+        self.out.write_all(&len.to_le_bytes())?;
+        self.out.write_all(&chrono::Utc::now().timestamp_millis().to_le_bytes())?;
+        self.out.write_all(&payload)?;
+        // Flush per sample so an interrupted session keeps everything up to now
+        self.out.flush()
+    }
+}
+
+struct Record {
+    offset: u64, // start of the payload
+    len: u32,
+    time_ms: i64,
+    end: u64,
+}
+
+/// Index every complete record after checking the file header.
+fn scan(file: &mut File) -> io::Result<(u32, Vec<Record>)> {
+    let size = file.metadata()?.len();
+    let mut reader = BufReader::new(&mut *file);
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut header = [0u8; FILE_HEADER as usize];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| invalid("not an lcars-ops recording"))?;
+    if &header[..8] != MAGIC {
+        return Err(invalid("not an lcars-ops recording"));
+    }
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != VERSION && version != FULL_HISTORY_VERSION {
+        return Err(invalid(&format!("unsupported recording version {}", version)));
+    }
+
+    let mut records = Vec::new();
+    let mut pos = FILE_HEADER;
+    let mut head = [0u8; RECORD_HEADER as usize];
+    while pos + RECORD_HEADER <= size {
+        reader.read_exact(&mut head)?;
+        let len = u32::from_le_bytes(head[..4].try_into().unwrap());
+        let time_ms = i64::from_le_bytes(head[4..].try_into().unwrap());
+        let offset = pos + RECORD_HEADER;
+        let end = offset + len as u64;
+        if end > size {
+            break;
+        }
+        records.push(Record { offset, len, time_ms, end });
+        reader.seek_relative(len as i64)?;
+        pos = end;
+    }
+    Ok((version, records))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Read-only access to a recording; samples are decoded on demand.
+pub struct Recording {
+    file: File,
+    version: u32,
+    records: Vec<Record>,
+}
+
+impl Recording {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let (version, records) = scan(&mut file)?;
+        if records.is_empty() {
+            return Err(invalid("recording contains no samples"));
+        }
+        Ok(Self { file, version, records })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Local wall-clock time sample `index` was taken.
+    pub fn time(&self, index: usize) -> chrono::DateTime<chrono::Local> {
+        let ms = self.records[index].time_ms;
+        chrono::DateTime::from_timestamp_millis(ms).unwrap_or_default().into()
+    }
+
+    pub fn sample(&mut self, index: usize) -> io::Result<Sample> {
+        let record = &self.records[index];
+        let mut payload = vec![0u8; record.len as usize];
+        self.file.seek(SeekFrom::Start(record.offset))?;
+        self.file.read_exact(&mut payload)?;
+        Ok(serde_json::from_reader(DeflateDecoder::new(payload.as_slice()))?)
+    }
+}
+
+/// Steps through a `Recording` in (scaled) real time for `--replay`.
+pub struct Player {
+    recording: Recording,
+    position: usize,
+    playhead_ms: f64, // recording time shown, advanced while playing
+    speed: f64,
+    paused: bool,
+    last_tick: Instant,
+    origin: Instant, // stands in for the first sample's time
+    latest: Arc<Sample>,
+    loaded: usize,
+    window: usize, // samples replayed to rebuild history after a jump
+    pub error: Option<String>,
+}
+
+impl Player {
+    pub fn new(mut recording: Recording) -> io::Result<Self> {
+        let mut latest = recording.sample(0)?;
+        // Every sample refreshes at least one collector with history, so this
+        // many samples back refill each of them
+        let window = latest.sys.cpu_history.capacity()
+            + latest.gpus.iter().map(|g| g.usage_history.capacity()).max().unwrap_or(0)
+            + latest.battery.power_history.capacity();
+        if recording.version != FULL_HISTORY_VERSION {
+            continue_histories(None, &mut latest);
+        }
+        let latest = Arc::new(latest);
+        Ok(Self {
+            playhead_ms: recording.records[0].time_ms as f64,
+            recording,
+            position: 0,
+            speed: 1.0,
+            paused: false,
+            last_tick: Instant::now(),
+            origin: Instant::now(),
+            latest,
+            loaded: 0,
+            window,
+            error: None,
+        })
+    }
+
+    /// Decode sample `index` with its history. The next sample extends the
+    /// history already shown; any other replays the samples before it.
+    fn load(&mut self, index: usize) -> io::Result<Arc<Sample>> {
+        if self.recording.version == FULL_HISTORY_VERSION {
+            return Ok(Arc::new(self.recording.sample(index)?));
+        }
+        let (mut prev, from) = if index == self.loaded + 1 {
+            (Some(self.latest.clone()), index)
+        } else {
+            (None, index.saturating_sub(self.window))
+        };
+        for i in from..=index {
+            let mut sample = self.recording.sample(i)?;
+            continue_histories(prev.as_deref(), &mut sample);
+            prev = Some(Arc::new(sample));
+        }
+        Ok(prev.expect("at least one sample loaded"))
+    }
+
+    /// Advance the playhead and load the sample under it. True when `latest` changed.
+    pub fn poll(&mut self) -> bool {
+        let elapsed = self.last_tick.elapsed();
+        self.last_tick = Instant::now();
+        if !self.paused {
+            self.playhead_ms += elapsed.as_secs_f64() * 1000.0 * self.speed;
+            let records = &self.recording.records;
+            while self.position + 1 < records.len() && records[self.position + 1].time_ms as f64 <= self.playhead_ms {
+                self.position += 1;
+            }
+            if self.position + 1 == records.len() {
+                self.paused = true;
+            }
+        }
+
+        if self.position == self.loaded {
+            return false;
+        }
+        let loaded = self.load(self.position);
+        self.loaded = self.position;
+        match loaded {
+            Ok(sample) => {
+                self.latest = sample;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("sample {}: {}", self.position + 1, e)),
+        }
+        true
+    }
+
+    pub fn latest(&self) -> &Arc<Sample> {
+        &self.latest
+    }
+
+    /// Wall-clock time until the next sample is due, or `None` while paused.
+    pub fn until_next(&self) -> Option<Duration> {
+        if self.paused {
+            return None;
+        }
+        let next = self.recording.records.get(self.position + 1)?.time_ms as f64;
+        let ms = ((next - self.playhead_ms) / self.speed).max(0.0);
+        Some(Duration::from_secs_f64(ms / 1000.0))
+    }
+
+    pub fn seek(&mut self, index: usize) {
+        self.position = index.min(self.recording.len() - 1);
+        self.playhead_ms = self.recording.records[self.position].time_ms as f64;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        // Playing from the last sample starts over
+        if self.paused && self.position + 1 == self.recording.len() {
+            self.seek(0);
+        }
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.recording.len()
+    }
+
+    pub fn time(&self) -> chrono::DateTime<chrono::Local> {
+        self.recording.time(self.position)
+    }
+
+    /// The current sample's place on a monotonic clock, so duration-based
+    /// alert rules follow recording time rather than playback time.
+    pub fn clock(&self) -> Instant {
+        let records = &self.recording.records;
+        let offset = (records[self.loaded].time_ms - records[0].time_ms).max(0) as u64;
+        self.origin + Duration::from_millis(offset)
+    }
+}
+
+/// Give `sample` the histories the live collectors had when it was taken:
+/// those of `prev`, plus this sample's reading wherever a collector was
+/// read again in between. Without `prev` the histories start from this reading.
+fn continue_histories(prev: Option<&Sample>, sample: &mut Sample) {
+    let fresh = |count: fn(&Sample) -> u64| prev.is_none_or(|p| count(p) != count(sample));
+    let (sys_fresh, gpu_fresh, battery_fresh, process_fresh) = (
+        fresh(|s| s.refreshes.sys),
+        fresh(|s| s.refreshes.gpu),
+        fresh(|s| s.refreshes.battery),
+        fresh(|s| s.refreshes.process),
+    );
+
+    let sys = Arc::make_mut(&mut sample.sys);
+    let prev_sys = prev.map(|p| &*p.sys);
+    let (cpu, memory) = (sys.cpu_total(), sys.memory_fraction());
+    extend(&mut sys.cpu_history, prev_sys.map(|p| &p.cpu_history), sys_fresh, cpu);
+    extend(&mut sys.mem_history, prev_sys.map(|p| &p.mem_history), sys_fresh, memory);
+    let rates: Vec<(String, f32, f32)> =
+        sys.network_info().iter().map(|n| (n.name.clone(), n.rx_rate as f32, n.tx_rate as f32)).collect();
+    for (name, rx, tx) in rates {
+        let Some(h) = sys.net_history.get_mut(&name) else { continue };
+        let prev_h = prev_sys.and_then(|p| p.net_history.get(&name));
+        extend(&mut h.rx, prev_h.map(|p| &p.rx), sys_fresh, rx);
+        extend(&mut h.tx, prev_h.map(|p| &p.tx), sys_fresh, tx);
+    }
+
+    for gpu in &mut sample.gpus {
+        let prev_gpu = prev.and_then(|p| p.gpus.iter().find(|g| g.card == gpu.card));
+        let (usage, power) = (gpu.gpu_usage as f32, gpu.power_w as f32);
+        extend(&mut gpu.usage_history, prev_gpu.map(|g| &g.usage_history), gpu_fresh, usage);
+        extend(&mut gpu.power_history, prev_gpu.map(|g| &g.power_history), gpu_fresh, power);
+    }
+
+    let battery = &mut sample.battery;
+    let power = battery.power_now as f32;
+    extend(&mut battery.power_history, prev.map(|p| &p.battery.power_history), battery_fresh, power);
+
+    if let Some(detail) = &mut sample.process {
+        let prev_detail = prev.and_then(|p| p.process.as_ref()).filter(|p| p.is_same_process(detail));
+        let (cpu, resident) = (detail.cpu_usage, detail.resident as f32);
+        extend(&mut detail.cpu_history, prev_detail.map(|p| &p.cpu_history), process_fresh, cpu);
+        extend(&mut detail.mem_history, prev_detail.map(|p| &p.mem_history), process_fresh, resident);
+    }
+}
+
+/// Carry `prev` over (unless the retention changed between sessions) and
+/// push `value` if it is a new reading.
+fn extend(history: &mut History, prev: Option<&History>, fresh: bool, value: f32) {
+    let prev = prev.filter(|p| p.capacity() == history.capacity());
+    if let Some(prev) = prev {
+        *history = prev.clone();
+    }
+    if fresh || prev.is_none() {
+        history.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HistoryConfig, RefreshConfig};
+    use crate::system::sampler::Collectors;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn appends_and_reads_back_samples() {
+        let dir = std::env::temp_dir().join(format!("lcars-ops-recording-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.lcr");
+
+        let sample = Collectors::new(fixture("intel-laptop"), &RefreshConfig::default(), &HistoryConfig::default()).sample();
+        Recorder::append(&path).unwrap().write(&sample).unwrap();
+        // A second session appends; a torn record at the end is discarded
+        let mut recorder = Recorder::append(&path).unwrap();
+        recorder.write(&sample).unwrap();
+        drop(recorder);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0xff, 0xff, 0, 0, 1, 2]).unwrap();
+        drop(file);
+        Recorder::append(&path).unwrap().write(&sample).unwrap();
+
+        let mut recording = Recording::open(&path).unwrap();
+        assert_eq!(recording.len(), 3);
+        let replayed = recording.sample(2).unwrap();
+        assert_eq!(replayed.battery.device, "BAT0");
+        assert_eq!(replayed.gpus[0].backend_name, "INTEL SYSFS");
+        assert_eq!(replayed.sys.cpu_virtualization, "Intel VT-x");
+
+        let mut player = Player::new(recording).unwrap();
+        player.seek(2);
+        assert!(player.poll());
+        assert_eq!(player.position(), 2);
+        assert!(player.is_paused(), "playback stops at the last sample");
+
+        std::fs::write(&path, b"not a recording").unwrap();
+        assert!(Recording::open(&path).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rebuilds_history_left_out_of_records() {
+        let dir = std::env::temp_dir().join(format!("lcars-ops-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.lcr");
+
+        let refresh = RefreshConfig { system: 60.0, gpu: 60.0, battery: 60.0 };
+        let history = HistoryConfig { retention: 3600.0 };
+        let mut collectors = Collectors::new(fixture("intel-laptop"), &refresh, &history);
+        let mut recorder = Recorder::append(&path).unwrap();
+        let mut live = Vec::new();
+        for step in 0..5 {
+            // Every other sample repeats readings nothing refreshed in between
+            if step % 2 == 0 {
+                collectors.refresh_all();
+            }
+            let sample = collectors.sample();
+            recorder.write(&sample).unwrap();
+            live.push(sample);
+        }
+        drop(recorder);
+        let json = serde_json::to_value(&live[4]).unwrap();
+        assert!(json["sys"]["cpu_history"].get("samples").is_none(), "records hold no history");
+
+        let values = |h: &History| h.values().collect::<Vec<_>>();
+        let tail = |h: &History, n: usize| values(h).split_off(h.values().count() - n);
+        let mut player = Player::new(Recording::open(&path).unwrap()).unwrap();
+        for (index, n) in [(1, 1), (2, 2), (4, 3)] {
+            // 1 and 2 follow on from the sample shown; 4 is a jump
+            player.seek(index);
+            assert!(player.poll());
+            let (replayed, live) = (player.latest(), &live[index]);
+            assert_eq!(values(&replayed.sys.cpu_history), tail(&live.sys.cpu_history, n), "sample {}", index);
+            assert_eq!(values(&replayed.gpus[0].power_history), tail(&live.gpus[0].power_history, n));
+            assert_eq!(values(&replayed.battery.power_history), tail(&live.battery.power_history, n));
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
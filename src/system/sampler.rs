@@ -0,0 +1,355 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::battery::BatteryInfo;
+use super::detail::{ProcessDetail, ProcessInspector};
+use super::gpu::{GpuInfo, GpuMonitor};
+use super::info::{SystemCollector, SystemInfo};
+use super::process::ProcFiles;
+use super::sensors::{SensorChip, SensorMonitor};
+use super::sysfs::Fs;
+use crate::config::{HistoryConfig, RefreshConfig};
+
+/// How often the sampler thread checks whether a collector is due.
+const TICK: Duration = Duration::from_millis(50);
+
+/// Immutable readings of every collector at one point in time.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub sys: Arc<SystemInfo>,
+    pub gpus: Vec<GpuInfo>,
+    pub battery: BatteryInfo,
+    #[serde(default)]
+    pub sensors: Vec<SensorChip>,
+    pub process: Option<ProcessDetail>, // the process open in the detail view
+    #[serde(default)]
+    pub refreshes: Refreshes,
+}
+
+/// How many times each collector with history had been read when a sample
+/// was taken. A replay pushes a reading onto the rebuilt histories only
+/// when its count moved on, as the live collector did.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Refreshes {
+    pub sys: u64,
+    pub gpu: u64,
+    pub battery: u64,
+    pub process: u64,
+}
+
+/// All collectors, each refreshed on its own interval.
+pub struct Collectors {
+    sys: SystemCollector,
+    sys_info: Arc<SystemInfo>, // copy of the latest system reading, shared by samples
+    gpu: GpuMonitor,
+    battery: BatteryInfo,
+    sensors: SensorMonitor,
+    inspector: ProcessInspector,
+    focus: Arc<AtomicU32>, // PID to inspect, 0 for none
+    inspected: u32,
+    host: bool, // the listed processes are the host's, so a sysroot has no detail for them
+    refreshes: Refreshes,
+}
+
+impl Collectors {
+    pub fn new(fs: Fs, refresh: &RefreshConfig, history: &HistoryConfig) -> Self {
+        let retention = history.retention();
+        let host = fs.is_host();
+        let gpu = GpuMonitor::new(fs.clone()).with_interval(refresh.gpu()).with_retention(retention);
+        let sys = SystemCollector::new(fs.clone())
+            .with_interval(refresh.system())
+            .with_retention(retention)
+            .with_drm_clients(gpu.drm_clients());
+        Self {
+            sys_info: Arc::new(sys.info().clone()),
+            sys,
+            gpu,
+            battery: BatteryInfo::new(fs.clone()).with_interval(refresh.battery()).with_retention(retention),
+            sensors: SensorMonitor::new(fs.clone()).with_interval(refresh.system()),
+            inspector: ProcessInspector::new(fs, refresh.system(), retention),
+            focus: Arc::new(AtomicU32::new(0)),
+            inspected: 0,
+            host,
+            refreshes: Refreshes::default(),
+        }
+    }
+
+    /// The system collector's interval; usage and rates are deltas over it.
+    pub fn interval(&self) -> Duration {
+        self.sys.interval()
+    }
+
+    /// Refresh whatever is due; true when anything changed. A newly
+    /// focused process is read straight away rather than at the next refresh.
+    pub fn refresh(&mut self) -> bool {
+        let battery = self.battery.refresh_if_needed();
+        // The system collector reads the DRM clients some GPU backends use
+        let sys = self.sys.refresh_if_needed();
+        let gpu = self.gpu.refresh_if_needed();
+        if sys {
+            self.sys_info = Arc::new(self.sys.info().clone());
+            self.refreshes.sys += 1;
+        }
+        self.refreshes.battery += battery as u64;
+        self.refreshes.gpu += gpu as u64;
+        let sensors = self.sensors.refresh_if_needed();
+        let refocused = self.inspect(sys);
+        battery || gpu || sys || sensors || refocused
+    }
+
+    /// Refresh every collector whatever its interval, so a step never
+    /// publishes a sample with some readings left over from the last one.
+    pub fn refresh_all(&mut self) {
+        self.battery.refresh();
+        self.sys.refresh();
+        self.gpu.refresh();
+        self.sys_info = Arc::new(self.sys.info().clone());
+        self.sensors.refresh();
+        self.inspect(true);
+        self.refreshes.sys += 1;
+        self.refreshes.gpu += 1;
+        self.refreshes.battery += 1;
+    }
+
+    /// Follow a change of focus only, leaving every other reading as it was.
+    fn refocus(&mut self) -> bool {
+        self.inspect(false)
+    }
+
+    /// Re-read the focused process when the focus changed or `again` is set.
+    /// True when the focus changed.
+    fn inspect(&mut self, again: bool) -> bool {
+        let focus = self.focus.load(Ordering::Relaxed);
+        let refocused = focus != self.inspected;
+        if again || refocused {
+            self.inspected = focus;
+            self.refreshes.process += 1;
+            match focus {
+                0 => self.inspector.clear(),
+                _ if !self.host => self.inspector.clear(),
+                pid => self.inspector.inspect(pid, self.sys.info().processes()),
+            }
+        }
+        refocused
+    }
+
+    /// The system reading is copied once per system refresh, not per sample,
+    /// and its process list is shared rather than copied at all.
+    pub fn sample(&self) -> Sample {
+        Sample {
+            sys: self.sys_info.clone(),
+            gpus: self.gpu.gpus.clone(),
+            battery: self.battery.clone(),
+            sensors: self.sensors.chips.clone(),
+            process: self.inspector.detail().cloned(),
+            refreshes: self.refreshes,
+        }
+    }
+}
+
+/// Runs `Collectors` on a background thread so slow reads (process scans,
+/// NVML calls and GPU sysfs) never stall a frame. The UI only ever sees finished samples.
+pub struct Sampler {
+    rx: Receiver<Arc<Sample>>,
+    latest: Arc<Sample>,
+    focus: Arc<AtomicU32>,
+    paused: Arc<AtomicBool>,
+    steps: Arc<AtomicU32>, // samples still to take while paused
+    proc_files: Arc<Mutex<ProcFiles>>,
+    all_proc_files: bool,
+}
+
+impl Sampler {
+    /// `on_sample` runs on the sampler thread after each new sample, e.g. to
+    /// publish metrics or wake the UI.
+    pub fn spawn(mut collectors: Collectors, mut on_sample: impl FnMut(&Sample) + Send + 'static) -> Self {
+        let latest = Arc::new(collectors.sample());
+        let focus = collectors.focus.clone();
+        let proc_files = collectors.sys.proc_files();
+        let paused = Arc::new(AtomicBool::new(false));
+        let steps = Arc::new(AtomicU32::new(0));
+        let (held, pending) = (paused.clone(), steps.clone());
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("sampler".to_string())
+            .spawn(move || loop {
+                if tick(&mut collectors, &held, &pending) {
+                    let sample = Arc::new(collectors.sample());
+                    on_sample(&sample);
+                    // The receiver is gone once the window closes
+                    if tx.send(sample).is_err() {
+                        return;
+                    }
+                }
+                thread::sleep(TICK);
+            })
+            .expect("failed to spawn sampler thread");
+        Self { rx, latest, focus, paused, steps, proc_files, all_proc_files: false }
+    }
+
+    /// Take the newest sample that arrived since the last call, skipping any
+    /// the UI was too slow to show. True when `latest` changed.
+    pub fn poll(&mut self) -> bool {
+        match self.rx.try_iter().last() {
+            Some(sample) => {
+                self.latest = sample;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn latest(&self) -> &Arc<Sample> {
+        &self.latest
+    }
+
+    /// Stop or resume publishing samples. Collectors are not read while
+    /// paused, so the next sample's rates span the whole pause.
+    pub fn set_paused(&self, paused: bool) {
+        self.steps.store(0, Ordering::Relaxed);
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Take exactly one new sample, pausing first if running.
+    pub fn step(&self) {
+        if !self.is_paused() {
+            self.set_paused(true);
+        }
+        self.steps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Keep reading every optional procfs file whatever the table shows, so
+    /// a recording can be replayed with any column.
+    pub fn with_all_proc_files(mut self) -> Self {
+        self.all_proc_files = true;
+        self
+    }
+
+    /// Read only the optional procfs files the process table needs.
+    pub fn set_proc_files(&self, files: ProcFiles) {
+        if self.all_proc_files {
+            return;
+        }
+        if let Ok(mut wanted) = self.proc_files.lock() {
+            *wanted = files;
+        }
+    }
+
+    /// Select the process whose details are collected; `None` stops inspecting.
+    pub fn set_focus(&self, pid: Option<u32>) {
+        self.focus.store(pid.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+/// One pass of the sampler loop; true when a new sample is due. While
+/// paused only a step or a newly focused process publishes.
+fn tick(collectors: &mut Collectors, paused: &AtomicBool, steps: &AtomicU32) -> bool {
+    if !paused.load(Ordering::Relaxed) {
+        return collectors.refresh();
+    }
+    if steps.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1)).is_ok() {
+        collectors.refresh_all();
+        return true;
+    }
+    collectors.refocus()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn publishes_samples_from_background_thread() {
+        let refresh = RefreshConfig { system: 0.25, gpu: 0.25, battery: 0.25 };
+        let collectors = Collectors::new(fixture("intel-laptop"), &refresh, &HistoryConfig::default());
+        let published = Arc::new(AtomicUsize::new(0));
+        let counter = published.clone();
+        let mut sampler = Sampler::spawn(collectors, move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(sampler.latest().battery.device, "BAT0");
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !sampler.poll() {
+            assert!(std::time::Instant::now() < deadline, "no sample within 5s");
+            thread::sleep(TICK);
+        }
+        assert!(published.load(Ordering::SeqCst) >= 1);
+        assert_eq!(sampler.latest().gpus[0].driver, "i915");
+    }
+
+    #[test]
+    fn pauses_and_steps_one_sample_at_a_time() {
+        // Intervals long enough that nothing would be due on its own
+        let refresh = RefreshConfig { system: 60.0, gpu: 60.0, battery: 60.0 };
+        let history = HistoryConfig { retention: 3600.0 };
+        let mut collectors = Collectors::new(fixture("intel-laptop"), &refresh, &history);
+        let (paused, steps) = (AtomicBool::new(true), AtomicU32::new(0));
+        let readings = |c: &Collectors| c.sys.info().cpu_history.values().count();
+
+        let before = readings(&collectors);
+        assert!(!tick(&mut collectors, &paused, &steps), "nothing is published while paused");
+        steps.store(2, Ordering::Relaxed);
+        assert!(tick(&mut collectors, &paused, &steps));
+        assert_eq!(readings(&collectors), before + 1, "a step refreshes the system collector too");
+        assert!(tick(&mut collectors, &paused, &steps));
+        assert!(!tick(&mut collectors, &paused, &steps), "one step, one sample");
+        assert_eq!(readings(&collectors), before + 2);
+        assert_eq!(steps.load(Ordering::Relaxed), 0);
+
+        // A new focus is still followed while paused
+        collectors.focus.store(1, Ordering::Relaxed);
+        assert!(tick(&mut collectors, &paused, &steps));
+        assert!(!tick(&mut collectors, &paused, &steps));
+    }
+
+    #[test]
+    fn step_pauses_a_running_sampler() {
+        let refresh = RefreshConfig { system: 60.0, gpu: 60.0, battery: 60.0 };
+        let collectors = Collectors::new(fixture("intel-laptop"), &refresh, &HistoryConfig::default());
+        let sampler = Sampler::spawn(collectors, |_| {});
+        sampler.step();
+        assert!(sampler.is_paused());
+        sampler.set_paused(false);
+        assert_eq!(sampler.steps.load(Ordering::Relaxed), 0, "resuming drops pending steps");
+    }
+
+    #[test]
+    fn skips_per_process_reads_under_a_sysroot() {
+        let refresh = RefreshConfig { system: 60.0, gpu: 60.0, battery: 60.0 };
+        let mut collectors = Collectors::new(fixture("process"), &refresh, &HistoryConfig::default());
+        let procs = collectors.sys.info().processes();
+        assert!(!procs.is_empty(), "the host's process list");
+        assert!(procs.iter().all(|p| p.state.is_empty() && p.cgroup.is_empty()));
+
+        // The fixture has a PID 4242 too, but it isn't the host's
+        collectors.focus.store(4242, Ordering::Relaxed);
+        collectors.refocus();
+        assert!(collectors.sample().process.is_none());
+    }
+
+    #[test]
+    fn shares_system_readings_until_they_change() {
+        let refresh = RefreshConfig { system: 60.0, gpu: 60.0, battery: 60.0 };
+        let mut collectors = Collectors::new(fixture("intel-laptop"), &refresh, &HistoryConfig::default());
+        let first = collectors.sample();
+        collectors.gpu.refresh();
+        assert!(Arc::ptr_eq(&first.sys, &collectors.sample().sys), "only the GPUs were read");
+
+        collectors.refresh_all();
+        let second = collectors.sample();
+        assert!(!Arc::ptr_eq(&first.sys, &second.sys));
+        assert!(Arc::ptr_eq(&second.sys, &collectors.sample().sys));
+    }
+}
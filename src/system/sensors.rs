@@ -0,0 +1,216 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::info::REFRESH_INTERVAL;
+use super::sysfs::{self, Fs, SysFs};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Current,
+}
+
+impl SensorKind {
+    /// hwmon file prefix and the divisor from its integer units
+    /// (millidegrees, RPM, millivolts, milliamps).
+    fn attr(&self) -> (&'static str, f64) {
+        match self {
+            SensorKind::Temperature => ("temp", 1000.0),
+            SensorKind::Fan => ("fan", 1.0),
+            SensorKind::Voltage => ("in", 1000.0),
+            SensorKind::Current => ("curr", 1000.0),
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "°C",
+            SensorKind::Fan => "RPM",
+            SensorKind::Voltage => "V",
+            SensorKind::Current => "A",
+        }
+    }
+}
+
+/// One `<kind><n>_input` attribute with its limits.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensorReading {
+    pub id: String,    // temp1, fan2, in0
+    pub label: String, // `<id>_label`, or the id when the driver gives none
+    pub kind: SensorKind,
+    pub value: f64,
+    pub max: Option<f64>,
+    pub crit: Option<f64>,
+    pub peak: f64, // highest value seen since start
+}
+
+impl SensorReading {
+    /// How far the value is towards crit (or max when there is no crit),
+    /// 1.0 at the limit; `None` without a limit.
+    pub fn level(&self) -> Option<f32> {
+        let limit = self.crit.or(self.max).filter(|l| *l > 0.0)?;
+        Some((self.value / limit) as f32)
+    }
+}
+
+/// One hwmon device and every reading it offers.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensorChip {
+    pub hwmon: String,  // hwmon3
+    pub name: String,   // coretemp, nvme, thinkpad
+    pub device: String, // bus device behind it, e.g. coretemp.0; empty for virtual chips
+    pub readings: Vec<SensorReading>,
+}
+
+/// Every chip under `/sys/class/hwmon`, rescanned each refresh so sensors
+/// of hot-plugged devices come and go.
+pub struct SensorMonitor {
+    pub chips: Vec<SensorChip>,
+    fs: Fs,
+    interval: Duration,
+    last_refresh: Instant,
+}
+
+impl SensorMonitor {
+    pub fn new(fs: Fs) -> Self {
+        let mut monitor = Self {
+            chips: Vec::new(),
+            fs,
+            interval: REFRESH_INTERVAL,
+            last_refresh: Instant::now() - REFRESH_INTERVAL,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn refresh_if_needed(&mut self) -> bool {
+        let due = self.last_refresh.elapsed() >= self.interval;
+        if due {
+            self.refresh();
+        }
+        due
+    }
+
+    /// Read now, whether or not the interval has passed.
+    pub fn refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        let fs = self.fs.as_ref();
+        let mut dirs = fs.read_dir(Path::new("/sys/class/hwmon")).unwrap_or_default();
+        dirs.sort_by_key(|d| number(&sysfs::file_name(d)));
+        let mut chips: Vec<SensorChip> = dirs.iter().map(|d| read_chip(fs, d)).collect();
+        carry_peaks(&self.chips, &mut chips);
+        self.chips = chips;
+    }
+}
+
+/// Trailing number of `hwmon12` or `temp3`, so 12 sorts after 2.
+fn number(name: &str) -> u32 {
+    name.trim_start_matches(|c: char| !c.is_ascii_digit()).parse().unwrap_or(u32::MAX)
+}
+
+fn read_chip(fs: &dyn SysFs, dir: &Path) -> SensorChip {
+    let hwmon = sysfs::file_name(dir);
+    let mut readings = Vec::new();
+    for file in fs.read_dir(dir).unwrap_or_default() {
+        let file = sysfs::file_name(&file);
+        let Some(id) = file.strip_suffix("_input") else { continue };
+        let prefix = id.trim_end_matches(|c: char| c.is_ascii_digit());
+        let index = &id[prefix.len()..];
+        let kind = [SensorKind::Temperature, SensorKind::Fan, SensorKind::Voltage, SensorKind::Current]
+            .into_iter()
+            .find(|k| k.attr().0 == prefix);
+        let Some(kind) = kind.filter(|_| !index.is_empty()) else { continue };
+        let divisor = kind.attr().1;
+        let value_of = |attr: &str| {
+            fs.read(&dir.join(format!("{}_{}", id, attr)))
+                .and_then(|v| v.parse::<f64>().ok())
+                .map(|v| v / divisor)
+        };
+        // Drivers report EIO/ENODATA for absent sensors
+        let Some(value) = value_of("input") else { continue };
+        readings.push(SensorReading {
+            id: id.to_string(),
+            label: fs.read(&dir.join(format!("{}_label", id))).unwrap_or_else(|| id.to_string()),
+            kind,
+            value,
+            max: value_of("max"),
+            crit: value_of("crit"),
+            peak: value,
+        });
+    }
+    readings.sort_by_key(|r| (r.kind, number(&r.id)));
+
+    SensorChip {
+        name: fs.read(&dir.join("name")).unwrap_or_else(|| hwmon.clone()),
+        device: fs.read_link(&dir.join("device")).map(|t| sysfs::file_name(&t)).unwrap_or_default(),
+        hwmon,
+        readings,
+    }
+}
+
+/// Keep each sensor's highest value across refreshes.
+fn carry_peaks(previous: &[SensorChip], chips: &mut [SensorChip]) {
+    for chip in chips {
+        let Some(old) = previous.iter().find(|c| c.hwmon == chip.hwmon && c.name == chip.name) else {
+            continue;
+        };
+        for reading in &mut chip.readings {
+            if let Some(prev) = old.readings.iter().find(|r| r.id == reading.id) {
+                reading.peak = reading.peak.max(prev.peak);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn reads_every_hwmon_chip() {
+        let monitor = SensorMonitor::new(fixture("intel-laptop"));
+        let names: Vec<&str> = monitor.chips.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["acpitz", "BAT0", "coretemp", "thinkpad"]);
+
+        let coretemp = &monitor.chips[2];
+        assert_eq!(coretemp.device, "coretemp.0");
+        let package = &coretemp.readings[0];
+        assert_eq!((package.label.as_str(), package.value), ("Package id 0", 62.0));
+        assert_eq!((package.max, package.crit), (Some(100.0), Some(100.0)));
+        assert_eq!(package.level(), Some(0.62));
+        assert_eq!(coretemp.readings.len(), 3, "temp4 has no value and is skipped");
+
+        let battery = &monitor.chips[1];
+        assert_eq!(battery.readings[0].kind, SensorKind::Voltage);
+        assert_eq!(battery.readings[0].value, 12.456);
+        assert_eq!(battery.readings[1].kind, SensorKind::Current);
+        assert_eq!(battery.readings[1].level(), None);
+
+        let fan = &monitor.chips[3].readings[0];
+        assert_eq!((fan.kind, fan.value), (SensorKind::Fan, 2900.0));
+    }
+
+    #[test]
+    fn keeps_peak_values() {
+        let mut previous = SensorMonitor::new(fixture("intel-laptop")).chips;
+        previous[2].readings[0].peak = 91.0;
+        let mut chips = previous.clone();
+        chips[2].readings[0].value = 55.0;
+        chips[2].readings[0].peak = 55.0;
+        chips[2].readings[1].peak = 70.0;
+        carry_peaks(&previous, &mut chips);
+        assert_eq!(chips[2].readings[0].peak, 91.0);
+        assert_eq!(chips[2].readings[1].peak, 70.0, "a new high replaces the old peak");
+    }
+}
@@ -0,0 +1,169 @@
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use sysinfo::System;
+
+use super::process::ProcessKey;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProcessAction {
+    Terminate,
+    Kill,
+    Stop,
+    Continue,
+    Renice(i32),
+}
+
+impl ProcessAction {
+    pub fn label(&self) -> String {
+        match self {
+            ProcessAction::Terminate => "SIGTERM".to_string(),
+            ProcessAction::Kill => "SIGKILL".to_string(),
+            ProcessAction::Stop => "SIGSTOP".to_string(),
+            ProcessAction::Continue => "SIGCONT".to_string(),
+            ProcessAction::Renice(nice) => format!("RENICE {:+}", nice),
+        }
+    }
+
+    /// Act on `pid` only while it is still the process that started at `start`.
+    /// Signals go through a pidfd taken before the check, so a PID reused
+    /// after it can't be hit; renice has no pidfd form and checks just before.
+    fn apply(&self, (pid, start): ProcessKey) -> io::Result<()> {
+        let same_process = || match start_time(pid) {
+            Some(now) if now == start => Ok(()),
+            _ => Err(io::Error::from_raw_os_error(libc::ESRCH)),
+        };
+        let sig = match *self {
+            ProcessAction::Terminate => libc::SIGTERM,
+            ProcessAction::Kill => libc::SIGKILL,
+            ProcessAction::Stop => libc::SIGSTOP,
+            ProcessAction::Continue => libc::SIGCONT,
+            ProcessAction::Renice(nice) => {
+                same_process()?;
+                return check(unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) });
+            }
+        };
+        match pidfd_open(pid) {
+            Ok(pidfd) => {
+                same_process()?;
+                let null = std::ptr::null::<libc::siginfo_t>();
+                check(unsafe { libc::syscall(libc::SYS_pidfd_send_signal, pidfd.as_raw_fd(), sig, null, 0) } as i32)
+            }
+            // Kernels before 5.3 have no pidfds
+            Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {
+                same_process()?;
+                check(unsafe { libc::kill(pid as libc::pid_t, sig) })
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn check(ret: i32) -> io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+fn pidfd_open(pid: u32) -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(unsafe { OwnedFd::from_raw_fd(fd as i32) })
+    }
+}
+
+/// Start time of a live process in Unix seconds, computed as sysinfo does
+/// so it compares equal to `ProcessInfo::start_time`.
+pub fn start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // Field 22, counted from the state (field 3) after the parenthesised name
+    let ticks: u64 = stat[stat.rfind(')')? + 1..].split_whitespace().nth(19)?.parse().ok()?;
+    let hz = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    Some(System::boot_time() + ticks / if hz > 0 { hz as u64 } else { 100 })
+}
+
+/// Apply `action` to every target still running as the same process, returning a one-line
+/// summary for the UI and whether anything failed. A target whose PID has exited, or now
+/// belongs to a process with another start time, counts as gone and is not signalled.
+/// Liveness is checked against /proc at the moment of sending, not against the last sample.
+pub fn apply_all(targets: &[ProcessKey], action: ProcessAction) -> (String, bool) {
+    let failures: Vec<(u32, io::Error)> = targets
+        .iter()
+        .filter_map(|&key| action.apply(key).err().map(|e| (key.0, e)))
+        .collect();
+    summarize(targets, action, &failures)
+}
+
+fn summarize(targets: &[ProcessKey], action: ProcessAction, failures: &[(u32, io::Error)]) -> (String, bool) {
+    let Some((pid, err)) = failures.first() else {
+        let target = if targets.len() == 1 {
+            format!("PID {}", targets[0].0)
+        } else {
+            format!("{} PROCESSES", targets.len())
+        };
+        return (format!("{} SENT TO {}", action.label(), target), false);
+    };
+
+    let reason = match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => "PERMISSION DENIED".to_string(),
+        Some(libc::ESRCH) => "NO SUCH PROCESS".to_string(),
+        _ => err.to_string().to_uppercase(),
+    };
+    let others = if failures.len() > 1 {
+        format!(" (+{} MORE)", failures.len() - 1)
+    } else {
+        String::new()
+    };
+    (format!("{} FAILED FOR PID {}: {}{}", action.label(), pid, reason, others), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_first_failure() {
+        let targets = [(100, 7), (101, 8), (102, 9)];
+        let eperm = [(101, io::Error::from_raw_os_error(libc::EPERM))];
+        assert_eq!(
+            summarize(&targets, ProcessAction::Kill, &eperm),
+            ("SIGKILL FAILED FOR PID 101: PERMISSION DENIED".to_string(), true)
+        );
+        let esrch = [
+            (100, io::Error::from_raw_os_error(libc::ESRCH)),
+            (102, io::Error::from_raw_os_error(libc::EPERM)),
+        ];
+        assert_eq!(
+            summarize(&targets, ProcessAction::Terminate, &esrch),
+            ("SIGTERM FAILED FOR PID 100: NO SUCH PROCESS (+1 MORE)".to_string(), true)
+        );
+        assert_eq!(
+            summarize(&targets[..1], ProcessAction::Renice(5), &[]),
+            ("RENICE +5 SENT TO PID 100".to_string(), false)
+        );
+        assert_eq!(summarize(&targets, ProcessAction::Stop, &[]).0, "SIGSTOP SENT TO 3 PROCESSES");
+    }
+
+    #[test]
+    fn leaves_exited_processes_alone() {
+        let me = std::process::id();
+        let (message, failed) = apply_all(&[(me, 1)], ProcessAction::Stop);
+        assert!(failed);
+        assert_eq!(message, format!("SIGSTOP FAILED FOR PID {}: NO SUCH PROCESS", me));
+        let (_, failed) = apply_all(&[(me, 1)], ProcessAction::Renice(0));
+        assert!(failed, "renice checks the start time too");
+    }
+
+    #[test]
+    fn reads_start_times_as_sysinfo_does() {
+        let me = sysinfo::get_current_pid().unwrap();
+        let mut sys = System::new();
+        sys.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[me]), true);
+        assert_eq!(start_time(me.as_u32()), Some(sys.process(me).unwrap().start_time()));
+        assert_eq!(start_time(u32::MAX), None);
+    }
+}
@@ -0,0 +1,398 @@
+use serde::Serialize;
+
+use super::battery::SupplyKind;
+use super::process::ProcessView;
+use super::sampler::Sample;
+use super::sensors::SensorKind;
+
+/// Bumped whenever a field is renamed, removed or changes meaning. Adding
+/// fields does not bump it, so consumers should ignore keys they don't know.
+/// 2: `battery` combines every system battery rather than the first one.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// One point-in-time reading of every collector, in a stable shape for
+/// `--json` output. Sizes are bytes, rates bytes/s, temperatures °C.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub schema: u32,
+    pub timestamp: String, // RFC 3339, UTC
+    pub host: HostSnapshot,
+    pub cpu: CpuSnapshot,
+    pub memory: MemorySnapshot,
+    pub disks: Vec<DiskSnapshot>,
+    pub block_devices: Vec<BlockDeviceSnapshot>,
+    pub networks: Vec<NetworkSnapshot>,
+    pub gpus: Vec<GpuSnapshot>,
+    pub battery: Option<BatterySnapshot>,
+    pub power_supplies: Vec<PowerSupplySnapshot>,
+    pub power_domains: Vec<PowerDomainSnapshot>,
+    pub sensors: Vec<SensorSnapshot>,
+    pub processes: Vec<ProcessSnapshot>,
+}
+
+#[derive(Serialize)]
+pub struct HostSnapshot {
+    pub hostname: String,
+    pub os: String,
+    pub kernel: String,
+    pub uptime_s: u64,
+}
+
+#[derive(Serialize)]
+pub struct CpuSnapshot {
+    pub usage: f32, // percent
+    pub per_core: Vec<f32>,
+    pub per_core_mhz: Vec<Option<f32>>,
+    pub governor: String,
+    pub epp: String,
+    pub boost: Option<bool>,
+    pub temp_c: f32,
+    pub temp_max_c: f32,
+    pub max_freq_ghz: f32,
+    pub logical_cores: usize,
+    pub physical_cores: usize,
+    pub sockets: usize,
+    pub virtualization: String,
+    pub architecture: String,
+}
+
+#[derive(Serialize)]
+pub struct MemorySnapshot {
+    pub used: u64,
+    pub total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+}
+
+#[derive(Serialize)]
+pub struct DiskSnapshot {
+    pub mount: String,
+    pub device: String,
+    pub used: u64,
+    pub total: u64,
+}
+
+#[derive(Serialize)]
+pub struct BlockDeviceSnapshot {
+    pub name: String,
+    pub mounts: Vec<String>,
+    pub read_rate: f64,
+    pub write_rate: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    pub queue_depth: f64,
+    pub busy: f32, // percent
+}
+
+#[derive(Serialize)]
+pub struct NetworkSnapshot {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct GpuSnapshot {
+    pub card: String,
+    pub driver: String,
+    pub backend: String,
+    pub kind: String,
+    pub pci_slot: String,
+    pub manufacturer: String,
+    pub pcie_link: String,
+    pub usage: u32, // percent
+    pub vram_used: u64,
+    pub vram_total: u64,
+    pub gpu_freq_mhz: Option<u32>,
+    pub mem_freq_mhz: Option<u32>,
+    pub power_w: f64,
+    pub power_cap_w: Option<f64>,
+    pub temp_c: f32,
+    pub temp_max_c: f32,
+}
+
+#[derive(Serialize)]
+pub struct BatterySnapshot {
+    pub device: String,
+    pub capacity: u32, // percent
+    pub status: String,
+    pub health: f32, // percent of design capacity
+    pub energy_full_design_wh: f64,
+    pub power_w: f64,
+    pub time_to_empty_s: Option<u64>,
+    pub time_to_full_s: Option<u64>,
+    pub cycle_count: u32,
+    pub technology: String,
+    pub manufacturer: String,
+    pub model_name: String,
+}
+
+#[derive(Serialize)]
+pub struct PowerSupplySnapshot {
+    pub name: String,
+    pub kind: SupplyKind,
+    pub peripheral: bool,
+    pub online: Option<bool>,
+    pub capacity: Option<u32>, // percent
+    pub capacity_level: String,
+    pub status: String,
+    pub power_w: f64,
+    pub model_name: String,
+}
+
+#[derive(Serialize)]
+pub struct PowerDomainSnapshot {
+    pub zone: String,
+    pub name: String,
+    pub parent: String,
+    pub power_w: Option<f64>, // null until a second reading, or without read access
+    pub limit_w: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct SensorSnapshot {
+    pub chip: String,
+    pub label: String,
+    pub kind: SensorKind,
+    pub value: f64, // °C, RPM, V or A by kind
+    pub max: Option<f64>,
+    pub crit: Option<f64>,
+    pub peak: f64,
+}
+
+#[derive(Serialize)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cpu: f32, // percent of one core
+    pub memory: u64,
+    pub gpu: f32, // percent
+    pub gpu_memory: u64,
+    pub disk_read_rate: f64,
+    pub disk_write_rate: f64,
+    pub net_rx_rate: f64, // TCP only
+    pub net_tx_rate: f64,
+}
+
+impl Snapshot {
+    /// Everything in `sample`, with the top processes in `procs` order.
+    pub fn capture(sample: &Sample, procs: &ProcessView) -> Self {
+        let mut snap = Self::system(sample);
+        snap.processes = procs
+            .get_processes(sample.sys.processes())
+            .into_iter()
+            .map(|p| ProcessSnapshot {
+                pid: p.pid,
+                name: p.name,
+                user: p.user,
+                cpu: p.cpu_usage,
+                memory: p.memory,
+                gpu: p.gpu_usage,
+                gpu_memory: p.gpu_memory,
+                disk_read_rate: p.disk_read,
+                disk_write_rate: p.disk_write,
+                net_rx_rate: p.net_rx,
+                net_tx_rate: p.net_tx,
+            })
+            .collect();
+        snap
+    }
+
+    /// Everything but the process list, whose sort over every process the
+    /// metrics exporter has no use for.
+    pub fn system(sample: &Sample) -> Self {
+        let (sys, battery) = (&sample.sys, &sample.battery);
+        let unknown = || "Unknown".to_string();
+        let mut networks: Vec<NetworkSnapshot> = sys
+            .network_info()
+            .iter()
+            .map(|n| NetworkSnapshot {
+                name: n.name.clone(),
+                rx_bytes: n.rx_bytes,
+                tx_bytes: n.tx_bytes,
+                rx_rate: n.rx_rate,
+                tx_rate: n.tx_rate,
+            })
+            .collect();
+        networks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            schema: SCHEMA_VERSION,
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            host: HostSnapshot {
+                hostname: sysinfo::System::host_name().unwrap_or_else(unknown),
+                os: sysinfo::System::long_os_version().unwrap_or_else(unknown),
+                kernel: sysinfo::System::kernel_version().unwrap_or_else(unknown),
+                uptime_s: sysinfo::System::uptime(),
+            },
+            cpu: CpuSnapshot {
+                usage: sys.cpu_total(),
+                per_core: sys.cpu_per_core().to_vec(),
+                per_core_mhz: sys.cpu_freq.cores.iter().map(|c| c.mhz).collect(),
+                governor: sys.cpu_freq.governor.clone(),
+                epp: sys.cpu_freq.epp.clone(),
+                boost: sys.cpu_freq.boost,
+                temp_c: sys.cpu_temp,
+                temp_max_c: sys.cpu_temp_max,
+                max_freq_ghz: sys.cpu_max_freq_ghz,
+                logical_cores: sys.cpu_logical_cores,
+                physical_cores: sys.cpu_physical_cores,
+                sockets: sys.cpu_sockets,
+                virtualization: sys.cpu_virtualization.clone(),
+                architecture: sys.cpu_architecture.clone(),
+            },
+            memory: MemorySnapshot {
+                used: sys.memory_used(),
+                total: sys.memory_total(),
+                swap_used: sys.swap_used(),
+                swap_total: sys.swap_total(),
+            },
+            disks: sys
+                .disk_info()
+                .iter()
+                .map(|d| DiskSnapshot { mount: d.mount.clone(), device: d.device.clone(), used: d.used, total: d.total })
+                .collect(),
+            block_devices: sys
+                .block_devices()
+                .iter()
+                .map(|d| BlockDeviceSnapshot {
+                    name: d.name.clone(),
+                    mounts: d.mounts.clone(),
+                    read_rate: d.read_rate,
+                    write_rate: d.write_rate,
+                    read_iops: d.read_iops,
+                    write_iops: d.write_iops,
+                    queue_depth: d.queue_depth,
+                    busy: d.busy * 100.0,
+                })
+                .collect(),
+            networks,
+            gpus: sample
+                .gpus
+                .iter()
+                .map(|g| GpuSnapshot {
+                    card: g.card.clone(),
+                    driver: g.driver.clone(),
+                    backend: g.backend_name.clone(),
+                    kind: g.kind.label().to_string(),
+                    pci_slot: g.pci_slot.clone(),
+                    manufacturer: g.manufacturer.clone(),
+                    pcie_link: g.pcie_link.clone(),
+                    usage: g.gpu_usage,
+                    vram_used: g.vram_used,
+                    vram_total: g.vram_total,
+                    gpu_freq_mhz: g.gpu_freq_mhz,
+                    mem_freq_mhz: g.mem_freq_mhz,
+                    power_w: g.power_w,
+                    power_cap_w: g.power_cap_w,
+                    temp_c: g.temp_celsius,
+                    temp_max_c: g.temp_max,
+                })
+                .collect(),
+            battery: battery.available.then(|| BatterySnapshot {
+                device: battery.device.clone(),
+                capacity: battery.capacity,
+                status: battery.status.clone(),
+                health: battery.health,
+                energy_full_design_wh: battery.energy_full_design,
+                power_w: battery.power_now,
+                time_to_empty_s: battery.time_to_empty,
+                time_to_full_s: battery.time_to_full,
+                cycle_count: battery.cycle_count,
+                technology: battery.technology.clone(),
+                manufacturer: battery.manufacturer.clone(),
+                model_name: battery.model_name.clone(),
+            }),
+            power_supplies: battery
+                .supplies
+                .iter()
+                .map(|s| PowerSupplySnapshot {
+                    name: s.name.clone(),
+                    kind: s.kind,
+                    peripheral: s.peripheral,
+                    online: s.online,
+                    capacity: s.capacity,
+                    capacity_level: s.capacity_level.clone(),
+                    status: s.status.clone(),
+                    power_w: s.power_now,
+                    model_name: s.model_name.clone(),
+                })
+                .collect(),
+            power_domains: sys
+                .cpu_power
+                .iter()
+                .map(|d| PowerDomainSnapshot {
+                    zone: d.zone.clone(),
+                    name: d.name.clone(),
+                    parent: d.parent.clone(),
+                    power_w: d.watts,
+                    limit_w: d.limit,
+                })
+                .collect(),
+            sensors: sample
+                .sensors
+                .iter()
+                .flat_map(|chip| {
+                    chip.readings.iter().map(|r| SensorSnapshot {
+                        chip: chip.name.clone(),
+                        label: r.label.clone(),
+                        kind: r.kind,
+                        value: r.value,
+                        max: r.max,
+                        crit: r.crit,
+                        peak: r.peak,
+                    })
+                })
+                .collect(),
+            processes: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HistoryConfig, RefreshConfig};
+    use crate::system::battery::BatteryInfo;
+    use crate::system::gpu::GpuMonitor;
+    use crate::system::sampler::Collectors;
+    use crate::system::sysfs::fixture;
+
+    #[test]
+    fn serializes_fixture_collectors() {
+        let mut sample = Collectors::new(fixture("intel-laptop"), &RefreshConfig::default(), &HistoryConfig::default()).sample();
+        let snap = Snapshot::capture(&sample, &ProcessView::default());
+
+        let json = serde_json::to_value(&snap).unwrap();
+        assert_eq!(json["schema"], SCHEMA_VERSION);
+        assert_eq!(json["cpu"]["virtualization"], "Intel VT-x");
+        assert_eq!(json["gpus"][0]["driver"], "i915");
+        assert_eq!(json["gpus"][0]["kind"], "INTEGRATED");
+        assert_eq!(json["battery"]["device"], "BAT0");
+        assert_eq!(json["battery"]["capacity"], 76);
+        assert_eq!(json["battery"]["time_to_empty_s"], 14400);
+        assert_eq!(json["power_supplies"][0]["kind"], "mains");
+        assert_eq!(json["sensors"][3]["label"], "Package id 0");
+        assert_eq!(json["sensors"][3]["kind"], "temperature");
+        assert_eq!(json["power_domains"][0]["name"], "package-0");
+        assert_eq!(json["power_domains"][0]["limit_w"], 15.0);
+        assert!(json["processes"].as_array().unwrap().len() <= 50);
+
+        let desktop = fixture("desktop-no-battery");
+        sample.gpus = GpuMonitor::new(desktop.clone()).gpus;
+        sample.battery = BatteryInfo::new(desktop);
+        let snap = Snapshot::capture(&sample, &ProcessView::default());
+        let json = serde_json::to_value(&snap).unwrap();
+        assert!(json["battery"].is_null());
+        assert_eq!(json["gpus"].as_array().unwrap().len(), 0);
+
+        sample.battery = BatteryInfo::new(fixture("dual-battery"));
+        let json = serde_json::to_value(Snapshot::capture(&sample, &ProcessView::default())).unwrap();
+        assert_eq!(json["schema"], 2, "battery fields changed meaning");
+        assert_eq!(json["battery"]["device"], "BAT0 + BAT1");
+        assert_eq!(json["battery"]["capacity"], 56);
+    }
+}
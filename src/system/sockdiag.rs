@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::time::Instant;
+
+use super::openfds::OpenFds;
+use super::sysfs::Fs;
+
+/// Per-process network throughput, bytes per second.
+#[derive(Clone, Copy, Default)]
+pub struct NetProcUsage {
+    pub rx: f64,
+    pub tx: f64,
+}
+
+/// Attributes TCP traffic to processes: the kernel reports byte counters per
+/// socket over NETLINK_SOCK_DIAG, and `/proc/<pid>/fd` links map each socket
+/// inode to its owner. Only sockets of processes whose fds are readable
+/// (our own, or all as root) are counted; UDP has no counters to read.
+pub struct NetProcessSampler {
+    fs: Fs,
+    prev: HashMap<u64, SocketBytes>, // socket inode -> counters at the last refresh
+    last: Option<Instant>,
+    usage: HashMap<u32, NetProcUsage>,
+}
+
+/// Counters from `tcp_info`.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct SocketBytes {
+    received: u64,
+    acked: u64, // sent and acknowledged by the peer
+}
+
+impl NetProcessSampler {
+    pub fn new(fs: Fs) -> Self {
+        Self {
+            fs,
+            prev: HashMap::new(),
+            last: None,
+            usage: HashMap::new(),
+        }
+    }
+
+    /// Read the socket counters and attribute them through `fds`, which
+    /// must have been read with sockets.
+    pub fn refresh(&mut self, fds: &OpenFds) {
+        // The kernel's sockets only match the host's /proc
+        if !self.fs.is_host() {
+            return;
+        }
+        let now = Instant::now();
+        let Ok(sockets) = tcp_sockets() else {
+            self.clear();
+            return;
+        };
+
+        let mut usage: HashMap<u32, NetProcUsage> = HashMap::new();
+        if let Some(last) = self.last {
+            let secs = now.saturating_duration_since(last).as_secs_f64();
+            if secs > 0.0 {
+                for (inode, bytes) in &sockets {
+                    let Some(pid) = fds.socket_owner(*inode) else { continue };
+                    // Sockets opened since the last refresh count from zero
+                    let old = self.prev.get(inode).copied().unwrap_or_default();
+                    let u = usage.entry(pid).or_default();
+                    u.rx += bytes.received.saturating_sub(old.received) as f64 / secs;
+                    u.tx += bytes.acked.saturating_sub(old.acked) as f64 / secs;
+                }
+            }
+        }
+
+        self.prev = sockets;
+        self.last = Some(now);
+        self.usage = usage;
+    }
+
+    /// Forget every reading, for while no one looks at the rates; the next
+    /// refresh starts over from zero.
+    pub fn clear(&mut self) {
+        self.prev.clear();
+        self.last = None;
+        self.usage.clear();
+    }
+
+    pub fn get(&self, pid: u32) -> NetProcUsage {
+        self.usage.get(&pid).copied().unwrap_or_default()
+    }
+}
+
+// ── netlink ──────────────────────────────────────────────────────────────────
+
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+const NLMSG_HEADER: usize = 16;
+const INET_DIAG_MSG: usize = 72; // inet_diag_msg, up to and including idiag_inode
+const INET_DIAG_INFO: u16 = 2;
+const TCPI_BYTES_ACKED: usize = 120; // offsets into struct tcp_info
+const TCPI_BYTES_RECEIVED: usize = 128;
+
+/// Byte counters of every IPv4 and IPv6 TCP socket, by inode.
+fn tcp_sockets() -> io::Result<HashMap<u64, SocketBytes>> {
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_SOCK_DIAG) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut sockets = HashMap::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        send_dump_request(&fd, family as u8)?;
+        let mut buf = vec![0u8; 32 * 1024];
+        loop {
+            let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if parse_messages(&buf[..n as usize], &mut sockets)? {
+                break;
+            }
+        }
+    }
+    Ok(sockets)
+}
+
+/// nlmsghdr followed by inet_diag_req_v2 asking for `tcp_info` of sockets in any state.
+fn send_dump_request(fd: &OwnedFd, family: u8) -> io::Result<()> {
+    let mut req = Vec::with_capacity(NLMSG_HEADER + 56);
+    req.extend_from_slice(&((NLMSG_HEADER + 56) as u32).to_ne_bytes());
+    req.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    req.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+    req.extend_from_slice(&[0u8; 8]); // sequence, port id
+    req.extend_from_slice(&[family, libc::IPPROTO_TCP as u8, 1 << (INET_DIAG_INFO - 1), 0]);
+    req.extend_from_slice(&u32::MAX.to_ne_bytes()); // all states
+    req.extend_from_slice(&[0u8; 48]); // inet_diag_sockid: no filter
+
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    let sent = unsafe {
+        libc::sendto(
+            fd.as_raw_fd(),
+            req.as_ptr().cast(),
+            req.len(),
+            0,
+            (&addr as *const libc::sockaddr_nl).cast(),
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Collect the sockets in one datagram of a dump. True once the dump is done.
+fn parse_messages(mut buf: &[u8], sockets: &mut HashMap<u64, SocketBytes>) -> io::Result<bool> {
+    while buf.len() >= NLMSG_HEADER {
+        let len = u32::from_ne_bytes(buf[..4].try_into().unwrap()) as usize;
+        let kind = u16::from_ne_bytes(buf[4..6].try_into().unwrap());
+        if len < NLMSG_HEADER || len > buf.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated netlink message"));
+        }
+        let body = &buf[NLMSG_HEADER..len];
+        match kind {
+            NLMSG_DONE => return Ok(true),
+            NLMSG_ERROR => {
+                let errno = body.get(..4).map_or(0, |b| i32::from_ne_bytes(b.try_into().unwrap()));
+                return Err(io::Error::from_raw_os_error(-errno));
+            }
+            SOCK_DIAG_BY_FAMILY if body.len() >= INET_DIAG_MSG => {
+                let inode = u32::from_ne_bytes(body[68..72].try_into().unwrap()) as u64;
+                if let Some(bytes) = tcp_info_bytes(&body[INET_DIAG_MSG..]) {
+                    sockets.insert(inode, bytes);
+                }
+            }
+            _ => {}
+        }
+        buf = &buf[align(len).min(buf.len())..];
+    }
+    Ok(false)
+}
+
+/// Walk the rtattrs after an inet_diag_msg for INET_DIAG_INFO.
+fn tcp_info_bytes(mut attrs: &[u8]) -> Option<SocketBytes> {
+    while attrs.len() >= 4 {
+        let len = u16::from_ne_bytes(attrs[..2].try_into().unwrap()) as usize;
+        let kind = u16::from_ne_bytes(attrs[2..4].try_into().unwrap());
+        if len < 4 || len > attrs.len() {
+            return None;
+        }
+        let data = &attrs[4..len];
+        // Kernels before 4.1 send a shorter tcp_info without byte counters
+        if kind == INET_DIAG_INFO && data.len() >= TCPI_BYTES_RECEIVED + 8 {
+            let read = |at: usize| u64::from_ne_bytes(data[at..at + 8].try_into().unwrap());
+            return Some(SocketBytes { received: read(TCPI_BYTES_RECEIVED), acked: read(TCPI_BYTES_ACKED) });
+        }
+        attrs = &attrs[align(len).min(attrs.len())..];
+    }
+    None
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diag_message(inode: u32, received: u64, acked: u64) -> Vec<u8> {
+        let mut info = vec![0u8; 160];
+        info[TCPI_BYTES_ACKED..TCPI_BYTES_ACKED + 8].copy_from_slice(&acked.to_ne_bytes());
+        info[TCPI_BYTES_RECEIVED..TCPI_BYTES_RECEIVED + 8].copy_from_slice(&received.to_ne_bytes());
+
+        let mut body = vec![0u8; INET_DIAG_MSG];
+        body[68..72].copy_from_slice(&inode.to_ne_bytes());
+        body.extend_from_slice(&((4 + info.len()) as u16).to_ne_bytes());
+        body.extend_from_slice(&INET_DIAG_INFO.to_ne_bytes());
+        body.extend_from_slice(&info);
+
+        let mut msg = ((NLMSG_HEADER + body.len()) as u32).to_ne_bytes().to_vec();
+        msg.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+        msg.extend_from_slice(&[0u8; 10]);
+        msg.extend_from_slice(&body);
+        msg
+    }
+
+    #[test]
+    fn parses_sock_diag_dump() {
+        let mut buf = diag_message(47, 5000, 1200);
+        buf.extend(diag_message(48, 1, 2));
+        let mut sockets = HashMap::new();
+        assert!(!parse_messages(&buf, &mut sockets).unwrap(), "dump continues");
+        assert_eq!(sockets[&47], SocketBytes { received: 5000, acked: 1200 });
+        assert_eq!(sockets.len(), 2);
+
+        let mut done = (NLMSG_HEADER as u32 + 4).to_ne_bytes().to_vec();
+        done.extend_from_slice(&NLMSG_DONE.to_ne_bytes());
+        done.extend_from_slice(&[0u8; 14]);
+        assert!(parse_messages(&done, &mut sockets).unwrap());
+    }
+
+}
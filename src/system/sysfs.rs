@@ -0,0 +1,87 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Read-only view of `/sys` and `/proc`. Collectors take absolute host paths
+/// and the implementation decides where they actually live, so parsing can be
+/// tested against fixture trees or replayed from a captured snapshot.
+pub trait SysFs: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// Entries of `path`, returned as absolute paths in the same namespace.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+    fn exists(&self, path: &Path) -> bool;
+    /// True when reads hit the running system, so host-only APIs (NVML) agree with sysfs.
+    fn is_host(&self) -> bool;
+
+    fn read(&self, path: &Path) -> Option<String> {
+        self.read_to_string(path).ok().map(|s| s.trim().to_string())
+    }
+
+    fn read_u64(&self, path: &Path) -> Option<u64> {
+        self.read(path)?.parse().ok()
+    }
+}
+
+pub type Fs = Arc<dyn SysFs>;
+
+/// The real filesystem, optionally re-rooted under a captured tree (`--sysroot`).
+pub struct RootedFs {
+    root: PathBuf,
+}
+
+impl RootedFs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+}
+
+impl SysFs for RootedFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(self.resolve(path))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(fs::read_dir(self.resolve(path))?
+            .filter_map(|e| e.ok())
+            .map(|e| path.join(e.file_name()))
+            .collect())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(self.resolve(path))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.resolve(path).exists()
+    }
+
+    fn is_host(&self) -> bool {
+        self.root == Path::new("/")
+    }
+}
+
+pub fn host() -> Fs {
+    Arc::new(RootedFs::new("/"))
+}
+
+pub fn rooted(root: impl Into<PathBuf>) -> Fs {
+    Arc::new(RootedFs::new(root))
+}
+
+/// Last path component as a `String`.
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+pub fn fixture(name: &str) -> Fs {
+    rooted(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name))
+}
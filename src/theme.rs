@@ -13,6 +13,19 @@ pub const GREEN: Color32 = Color32::from_rgb(0x33, 0xCC, 0x66);
 pub const YELLOW: Color32 = Color32::from_rgb(0xFF, 0xCC, 0x00);
 pub const RED: Color32 = Color32::from_rgb(0xFF, 0x33, 0x33);
 
+// Alert palette: chrome flashes between the bright and dim tone
+pub const RED_DIM: Color32 = Color32::from_rgb(0x80, 0x10, 0x10);
+pub const YELLOW_DIM: Color32 = Color32::from_rgb(0x80, 0x66, 0x00);
+pub const ALERT_FLASH_HZ: f64 = 1.5;
+
+pub fn flash(bright: Color32, dim: Color32, time: f64) -> Color32 {
+    if ((time * ALERT_FLASH_HZ * 2.0) as u64).is_multiple_of(2) {
+        bright
+    } else {
+        dim
+    }
+}
+
 pub fn disk_color(fraction: f32) -> Color32 {
     if fraction <= 0.49 {
         GREEN
@@ -35,6 +48,7 @@ pub const ELBOW_RADIUS: f32 = 40.0;
 pub const BAR_SPACING: f32 = 4.0;
 pub const BUTTON_HEIGHT: f32 = 48.0;
 pub const GAUGE_HEIGHT: f32 = 22.0;
+pub const SPARKLINE_HEIGHT: f32 = 48.0;
 
 // Decorative sidebar labels
 pub const SIDEBAR_LABELS: &[&str] = &[
@@ -1,9 +1,13 @@
 use egui::Ui;
 
-use crate::system::battery::BatteryInfo;
+use crate::system::battery::{BatteryInfo, PowerSupply};
 use crate::theme;
 use crate::widgets::gauge::LcarsGauge;
 use crate::widgets::panel::LcarsPanel;
+use crate::widgets::sparkline::LcarsSparkline;
+
+const LABEL_W: f32 = 260.0;
+const BATTERY_STATE_W: f32 = 380.0;
 
 fn battery_color(capacity: u32) -> egui::Color32 {
     if capacity > 50 {
@@ -15,20 +19,113 @@ fn battery_color(capacity: u32) -> egui::Color32 {
     }
 }
 
+fn format_remaining(secs: u64) -> String {
+    format!("{} H {:02} MIN", secs / 3600, secs % 3600 / 60)
+}
+
+/// Rough fill for peripherals that report only `capacity_level`.
+fn level_fraction(level: &str) -> f32 {
+    match level {
+        "Full" => 1.0,
+        "High" => 0.8,
+        "Normal" => 0.6,
+        "Low" => 0.2,
+        "Critical" => 0.05,
+        _ => 0.0,
+    }
+}
+
 pub fn show(ui: &mut Ui, bat: &BatteryInfo) {
-    if !bat.available {
+    let full_width = ui.available_width() - 20.0;
+
+    if bat.available {
+        show_usage(ui, bat, full_width);
+    } else {
         ui.add_space(20.0);
         ui.label(
             egui::RichText::new("NO BATTERY DETECTED")
                 .color(theme::ORANGE)
                 .font(egui::FontId::monospace(40.0)),
         );
+        ui.add_space(20.0);
+    }
+
+    // Power Sources Panel
+    let sources: Vec<&PowerSupply> = bat.sources().collect();
+    if !sources.is_empty() {
+        LcarsPanel::new("Power Sources", theme::ORANGE).show(ui, |ui| {
+            for source in sources {
+                let state = match source.online {
+                    Some(true) => "ONLINE",
+                    Some(false) => "OFFLINE",
+                    None => "UNKNOWN",
+                };
+                let color = if source.online == Some(true) { theme::GREEN } else { theme::ORANGE };
+                match source.capacity {
+                    Some(capacity) => {
+                        LcarsGauge::new(source.label(), capacity as f32 / 100.0, battery_color(capacity))
+                            .width(full_width)
+                            .label_width(LABEL_W)
+                            .font_size(20.0)
+                            .show(ui);
+                        ui.label(
+                            egui::RichText::new(format!("          {}   {}", state, source.status.to_uppercase()))
+                                .color(color)
+                                .font(egui::FontId::monospace(20.0)),
+                        );
+                    }
+                    None => show_prop(ui, &source.label(), state, color),
+                }
+            }
+        });
+    }
+
+    // Peripherals Panel
+    let peripherals: Vec<&PowerSupply> = bat.peripherals().collect();
+    if !peripherals.is_empty() {
+        LcarsPanel::new("Peripherals", theme::BLUE).show(ui, |ui| {
+            for dev in peripherals {
+                let (fraction, value) = match dev.capacity {
+                    Some(capacity) => (capacity as f32 / 100.0, format!("{}%", capacity)),
+                    None => (level_fraction(&dev.capacity_level), dev.capacity_level.to_uppercase()),
+                };
+                let name = if dev.model_name == "Unknown" { &dev.name } else { &dev.model_name };
+                LcarsGauge::new(name.to_uppercase(), fraction, battery_color((fraction * 100.0) as u32))
+                    .width(full_width)
+                    .label_width(LABEL_W)
+                    .value_text(value)
+                    .font_size(20.0)
+                    .show(ui);
+            }
+        });
+    }
+
+    if !bat.available {
         return;
     }
 
-    let full_width = ui.available_width() - 20.0;
+    // Properties Panel, one per battery
+    let batteries: Vec<&PowerSupply> = bat.batteries().collect();
+    for b in &batteries {
+        let title = if batteries.len() > 1 { format!("Properties {}", b.name) } else { "Properties".to_string() };
+        LcarsPanel::new(title, theme::LAVENDER).show(ui, |ui| {
+            let props: &[(&str, String)] = &[
+                ("BATTERY HEALTH", b.health().map_or("N/A".to_string(), |h| format!("{:.0}%", h))),
+                ("DESIGN CAPACITY", format!("{:.1} Wh", b.energy_full_design)),
+                ("CHARGE CYCLES", format!("{}", b.cycle_count)),
+                ("TECHNOLOGY", b.technology.clone()),
+                ("MANUFACTURER", b.manufacturer.clone()),
+                ("MODEL NAME", b.model_name.clone()),
+                ("DEVICE", b.name.clone()),
+            ];
+            for (label, value) in props {
+                show_prop(ui, label, value, theme::LAVENDER);
+            }
+        });
+    }
+}
 
-    // Usage Panel
+fn show_usage(ui: &mut Ui, bat: &BatteryInfo, full_width: f32) {
     LcarsPanel::new("Usage", theme::GREEN).show(ui, |ui| {
         let charge_color = battery_color(bat.capacity);
 
@@ -36,12 +133,42 @@ pub fn show(ui: &mut Ui, bat: &BatteryInfo) {
             .width(full_width)
             .font_size(20.0)
             .show(ui);
+        let remaining = match (bat.time_to_empty, bat.time_to_full) {
+            (Some(secs), _) => format!("   {} REMAINING", format_remaining(secs)),
+            (None, Some(secs)) => format!("   {} TO FULL", format_remaining(secs)),
+            (None, None) => String::new(),
+        };
         ui.label(
-            egui::RichText::new(format!("          {}", bat.status.to_uppercase()))
+            egui::RichText::new(format!("          {}{}", bat.status.to_uppercase(), remaining))
                 .color(charge_color)
                 .font(egui::FontId::monospace(20.0)),
         );
 
+        // Each battery's share when there are several
+        let batteries: Vec<&PowerSupply> = bat.batteries().collect();
+        if batteries.len() > 1 {
+            ui.add_space(4.0);
+            for b in batteries {
+                let capacity = b.capacity.unwrap_or(0);
+                ui.horizontal(|ui| {
+                    LcarsGauge::new(&b.name, capacity as f32 / 100.0, battery_color(capacity))
+                        .width(full_width - BATTERY_STATE_W)
+                        .font_size(20.0)
+                        .show(ui);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{}  {:.1} / {:.1} Wh",
+                            b.status.to_uppercase(),
+                            b.energy_now,
+                            b.energy_full
+                        ))
+                        .color(battery_color(capacity))
+                        .font(egui::FontId::monospace(18.0)),
+                    );
+                });
+            }
+        }
+
         ui.add_space(8.0);
 
         const MAX_POWER: f64 = 60.0;
@@ -56,47 +183,29 @@ pub fn show(ui: &mut Ui, bat: &BatteryInfo) {
                 .color(theme::PEACH)
                 .font(egui::FontId::monospace(20.0)),
         );
+        LcarsSparkline::new("HISTORY", &bat.power_history, MAX_POWER as f32, theme::PEACH)
+            .width(full_width)
+            .font_size(20.0)
+            .value_text(format!("{:.1} W", bat.power_now))
+            .show(ui);
     });
+}
 
-    // Properties Panel
-    LcarsPanel::new("Properties", theme::LAVENDER).show(ui, |ui| {
-        let label_font = egui::FontId::monospace(18.0);
-        let value_font = egui::FontId::monospace(22.0);
-        let label_color = theme::LAVENDER.linear_multiply(0.65);
-        let value_color = theme::LAVENDER;
-        let row_h = 30.0;
-        let label_w = 220.0;
-
-        let props: &[(&str, String)] = &[
-            ("BATTERY HEALTH", format!("{:.0}%", bat.health)),
-            ("DESIGN CAPACITY", format!("{:.1} Wh", bat.energy_full_design)),
-            ("CHARGE CYCLES", format!("{}", bat.cycle_count)),
-            ("TECHNOLOGY", bat.technology.clone()),
-            ("MANUFACTURER", bat.manufacturer.clone()),
-            ("MODEL NAME", bat.model_name.clone()),
-            ("DEVICE", bat.device.clone()),
-        ];
-
-        for (label, value) in props {
-            ui.horizontal(|ui| {
-                let (r, _) = ui.allocate_exact_size(
-                    egui::Vec2::new(label_w, row_h),
-                    egui::Sense::hover(),
-                );
-                ui.painter().text(
-                    r.left_center(),
-                    egui::Align2::LEFT_CENTER,
-                    *label,
-                    label_font.clone(),
-                    label_color,
-                );
-                ui.label(
-                    egui::RichText::new(value.as_str())
-                        .color(value_color)
-                        .font(value_font.clone()),
-                );
-            });
-            ui.add_space(2.0);
-        }
+fn show_prop(ui: &mut Ui, label: &str, value: &str, color: egui::Color32) {
+    ui.horizontal(|ui| {
+        let (r, _) = ui.allocate_exact_size(egui::Vec2::new(220.0, 30.0), egui::Sense::hover());
+        ui.painter().text(
+            r.left_center(),
+            egui::Align2::LEFT_CENTER,
+            label,
+            egui::FontId::monospace(18.0),
+            color.linear_multiply(0.65),
+        );
+        ui.label(
+            egui::RichText::new(value)
+                .color(color)
+                .font(egui::FontId::monospace(22.0)),
+        );
     });
+    ui.add_space(2.0);
 }
@@ -1,239 +1,373 @@
 use egui::Ui;
 
-use crate::system::info::{format_bytes, format_uptime, rate_fraction, NetworkData, SystemInfo};
+use crate::config::PanelConfig;
+use crate::system::battery::BatteryInfo;
+use crate::system::cpufreq::{CoreFreq, CpuFreq};
+use crate::system::gpu::GpuInfo;
+use crate::system::info::{format_bytes, format_rate, format_uptime, rate_fraction, NetHistory, NetworkData, SystemInfo};
+use crate::system::powercap::PowerDomain;
 use crate::theme;
 use crate::widgets::gauge::LcarsGauge;
 use crate::widgets::panel::LcarsPanel;
+use crate::widgets::sparkline::LcarsSparkline;
 
-pub fn show(ui: &mut Ui, sys: &SystemInfo) {
+pub fn show(ui: &mut Ui, sys: &SystemInfo, battery: &BatteryInfo, gpus: &[GpuInfo], panels: &PanelConfig) {
     // CPU Panel
-    LcarsPanel::new("Processor", theme::ORANGE).show(ui, |ui| {
-        let full_width = ui.available_width() - 20.0;
+    if panels.processor {
+        LcarsPanel::new("Processor", theme::ORANGE).show(ui, |ui| {
+            let full_width = ui.available_width() - 20.0;
 
-        // Total spans full width
-        LcarsGauge::new("Total", sys.cpu_total() / 100.0, theme::ORANGE)
-            .width(full_width)
-            .font_size(20.0)
-            .show(ui);
+            // Total spans full width
+            LcarsGauge::new("Total", sys.cpu_total() / 100.0, theme::ORANGE)
+                .width(full_width)
+                .font_size(20.0)
+                .show(ui);
+            LcarsSparkline::new("History", &sys.cpu_history, 100.0, theme::ORANGE)
+                .width(full_width)
+                .font_size(20.0)
+                .show(ui);
+            ui.add_space(4.0);
+            if let Some(summary) = scaling_summary(&sys.cpu_freq) {
+                ui.label(
+                    egui::RichText::new(summary)
+                        .color(theme::ORANGE)
+                        .font(egui::FontId::monospace(20.0)),
+                );
+            }
 
-        let cores = sys.cpu_per_core();
-        let colors = [theme::PEACH, theme::BLUE, theme::PERIWINKLE, theme::LAVENDER];
-        let half = (cores.len() + 1) / 2;
-        let col_width = (full_width - 16.0) / 2.0;
-
-        ui.horizontal(|ui| {
-            // Left column
-            ui.vertical(|ui| {
-                for (i, usage) in cores[..half].iter().enumerate() {
-                    let label = format!("Core {}", i);
-                    let color = colors[i % colors.len()];
-                    LcarsGauge::new(label, usage / 100.0, color)
-                        .width(col_width)
-                        .font_size(20.0)
-                        .show(ui);
-                }
-            });
+            let cores = sys.cpu_per_core();
+            let colors = [theme::PEACH, theme::BLUE, theme::PERIWINKLE, theme::LAVENDER];
+            let half = cores.len().div_ceil(2);
+            let col_width = (full_width - 16.0) / 2.0;
 
-            // Right column
-            ui.vertical(|ui| {
-                for (i, usage) in cores[half..].iter().enumerate() {
-                    let idx = i + half;
-                    let label = format!("Core {}", idx);
-                    let color = colors[idx % colors.len()];
-                    LcarsGauge::new(label, usage / 100.0, color)
-                        .width(col_width)
-                        .font_size(20.0)
-                        .show(ui);
-                }
+            ui.horizontal(|ui| {
+                // Left column
+                ui.vertical(|ui| {
+                    for (i, usage) in cores[..half].iter().enumerate() {
+                        show_core(ui, i, *usage, colors[i % colors.len()], col_width, &sys.cpu_freq);
+                    }
+                });
+
+                // Right column
+                ui.vertical(|ui| {
+                    for (i, usage) in cores[half..].iter().enumerate() {
+                        let idx = i + half;
+                        show_core(ui, idx, *usage, colors[idx % colors.len()], col_width, &sys.cpu_freq);
+                    }
+                });
             });
         });
-    });
+    }
 
     // CPU Sensors Panel
-    LcarsPanel::new("Sensors", theme::ORANGE).show(ui, |ui| {
-        let temp_fraction = (sys.cpu_temp / 100.0).clamp(0.0, 1.0);
-        let temp_color = if sys.cpu_temp >= 80.0 {
-            theme::RED
-        } else if sys.cpu_temp >= 60.0 {
-            theme::YELLOW
-        } else {
-            theme::GREEN
-        };
-        let full_width = ui.available_width() - 20.0;
-        LcarsGauge::new("TEMP", temp_fraction, temp_color)
-            .width(full_width)
-            .font_size(20.0)
-            .show(ui);
-        ui.label(
-            egui::RichText::new(format!(
-                "          {:.0}\u{00b0}C  \u{2022}  Highest: {:.0}\u{00b0}C",
-                sys.cpu_temp, sys.cpu_temp_max
-            ))
-            .color(temp_color)
-            .font(egui::FontId::monospace(20.0)),
-        );
-    });
-
-    // CPU Properties Panel
-    LcarsPanel::new("Properties", theme::PEACH).show(ui, |ui| {
-        let uptime = format_uptime(sysinfo::System::uptime());
-        let freq_str = if sys.cpu_max_freq_ghz > 0.0 {
-            format!("{:.2} GHz", sys.cpu_max_freq_ghz)
-        } else {
-            "N/A".to_string()
-        };
-
-        let left: &[(&str, String)] = &[
-            ("MAX FREQUENCY", freq_str),
-            ("LOGICAL CORES", sys.cpu_logical_cores.to_string()),
-            ("PHYSICAL CORES", sys.cpu_physical_cores.to_string()),
-            ("SOCKETS", sys.cpu_sockets.to_string()),
-        ];
-        let right: &[(&str, String)] = &[
-            ("UPTIME", uptime),
-            ("VIRTUALIZATION", sys.cpu_virtualization.clone()),
-            ("ARCHITECTURE", sys.cpu_architecture.clone()),
-        ];
-
-        ui.columns(2, |cols| {
-            for (label, value) in left {
-                show_cpu_prop(&mut cols[0], label, value);
-            }
-            for (label, value) in right {
-                show_cpu_prop(&mut cols[1], label, value);
-            }
+    if panels.sensors {
+        LcarsPanel::new("Sensors", theme::ORANGE).show(ui, |ui| {
+            let temp_fraction = (sys.cpu_temp / 100.0).clamp(0.0, 1.0);
+            let temp_color = if sys.cpu_temp >= 80.0 {
+                theme::RED
+            } else if sys.cpu_temp >= 60.0 {
+                theme::YELLOW
+            } else {
+                theme::GREEN
+            };
+            let full_width = ui.available_width() - 20.0;
+            LcarsGauge::new("TEMP", temp_fraction, temp_color)
+                .width(full_width)
+                .font_size(20.0)
+                .show(ui);
+            ui.label(
+                egui::RichText::new(format!(
+                    "          {:.0}\u{00b0}C  \u{2022}  Highest: {:.0}\u{00b0}C",
+                    sys.cpu_temp, sys.cpu_temp_max
+                ))
+                .color(temp_color)
+                .font(egui::FontId::monospace(20.0)),
+            );
         });
-    });
+    }
 
-    // Memory Panel
-    LcarsPanel::new("Memory", theme::PEACH).show(ui, |ui| {
-        let full_width = ui.available_width() - 20.0;
-        let col_width = (full_width - 16.0) / 2.0;
-
-        ui.horizontal(|ui| {
-            // RAM column
-            ui.vertical(|ui| {
-                LcarsGauge::new("RAM", sys.memory_fraction(), theme::PEACH)
-                    .width(col_width)
-                    .font_size(20.0)
-                    .show(ui);
-                ui.label(
-                    egui::RichText::new(format!(
-                        "          {} / {}",
-                        format_bytes(sys.memory_used()),
-                        format_bytes(sys.memory_total())
-                    ))
-                    .color(theme::PEACH)
-                    .font(egui::FontId::monospace(20.0)),
-                );
+    // Power Panel: RAPL domains beside battery and GPU draw
+    if panels.power {
+        let discharging = battery.available && battery.status == "Discharging";
+        let gpu_watts: f64 = gpus.iter().map(|g| g.power_w).sum();
+        if !sys.cpu_power.is_empty() || discharging || gpu_watts > 0.0 {
+            LcarsPanel::new("Power", theme::YELLOW).show(ui, |ui| {
+                let full_width = ui.available_width() - 20.0;
+                for domain in &sys.cpu_power {
+                    show_power_domain(ui, domain, &sys.cpu_power, full_width);
+                }
+                if discharging {
+                    show_power_line(ui, "BATTERY", battery.power_now, theme::GREEN);
+                }
+                if gpu_watts > 0.0 {
+                    show_power_line(ui, "GPU", gpu_watts, theme::PERIWINKLE);
+                }
+                if let Some((watts, source)) = system_draw(&sys.cpu_power, battery, gpu_watts) {
+                    ui.add_space(4.0);
+                    ui.label(
+                        egui::RichText::new(format!("TOTAL  {:.1} W  ({})", watts, source))
+                            .color(theme::YELLOW)
+                            .font(egui::FontId::monospace(22.0)),
+                    );
+                }
             });
+        }
+    }
 
-            // Swap column
-            ui.vertical(|ui| {
-                LcarsGauge::new("Swap", sys.swap_fraction(), theme::LAVENDER)
-                    .width(col_width)
-                    .font_size(20.0)
-                    .show(ui);
-                ui.label(
-                    egui::RichText::new(format!(
-                        "          {} / {}",
-                        format_bytes(sys.swap_used()),
-                        format_bytes(sys.swap_total())
-                    ))
-                    .color(theme::LAVENDER)
-                    .font(egui::FontId::monospace(20.0)),
-                );
+    // CPU Properties Panel
+    if panels.properties {
+        LcarsPanel::new("Properties", theme::PEACH).show(ui, |ui| {
+            let uptime = format_uptime(sysinfo::System::uptime());
+            let freq_str = if sys.cpu_max_freq_ghz > 0.0 {
+                format!("{:.2} GHz", sys.cpu_max_freq_ghz)
+            } else {
+                "N/A".to_string()
+            };
+
+            let left: &[(&str, String)] = &[
+                ("MAX FREQUENCY", freq_str),
+                ("LOGICAL CORES", sys.cpu_logical_cores.to_string()),
+                ("PHYSICAL CORES", sys.cpu_physical_cores.to_string()),
+                ("SOCKETS", sys.cpu_sockets.to_string()),
+            ];
+            let right: &[(&str, String)] = &[
+                ("UPTIME", uptime),
+                ("VIRTUALIZATION", sys.cpu_virtualization.clone()),
+                ("ARCHITECTURE", sys.cpu_architecture.clone()),
+            ];
+
+            ui.columns(2, |cols| {
+                for (label, value) in left {
+                    show_cpu_prop(&mut cols[0], label, value);
+                }
+                for (label, value) in right {
+                    show_cpu_prop(&mut cols[1], label, value);
+                }
             });
         });
-    });
+    }
 
-    // Disk Panel
-    let disks = sys.disk_info();
-    if !disks.is_empty() {
-        LcarsPanel::new("Storage", theme::PERIWINKLE).show(ui, |ui| {
+    // Memory Panel
+    if panels.memory {
+        LcarsPanel::new("Memory", theme::PEACH).show(ui, |ui| {
             let full_width = ui.available_width() - 20.0;
             let col_width = (full_width - 16.0) / 2.0;
-            let half = (disks.len() + 1) / 2;
 
             ui.horizontal(|ui| {
-                // Left column
+                // RAM column
                 ui.vertical(|ui| {
-                    for disk in &disks[..half] {
-                        let label = if disk.mount.len() > 10 {
-                            format!("..{}", &disk.mount[disk.mount.len() - 8..])
-                        } else {
-                            disk.mount.clone()
-                        };
-                        let color = theme::disk_color(disk.fraction);
-                        LcarsGauge::new(label, disk.fraction, color)
-                            .width(col_width)
-                            .font_size(20.0)
-                            .show(ui);
-                        ui.label(
-                            egui::RichText::new(format!(
-                                "          {} / {}",
-                                format_bytes(disk.used),
-                                format_bytes(disk.total)
-                            ))
-                            .color(color)
-                            .font(egui::FontId::monospace(20.0)),
-                        );
-                    }
+                    LcarsGauge::new("RAM", sys.memory_fraction(), theme::PEACH)
+                        .width(col_width)
+                        .font_size(20.0)
+                        .show(ui);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "          {} / {}",
+                            format_bytes(sys.memory_used()),
+                            format_bytes(sys.memory_total())
+                        ))
+                        .color(theme::PEACH)
+                        .font(egui::FontId::monospace(20.0)),
+                    );
                 });
 
-                // Right column
+                // Swap column
                 ui.vertical(|ui| {
-                    for disk in &disks[half..] {
-                        let label = if disk.mount.len() > 10 {
-                            format!("..{}", &disk.mount[disk.mount.len() - 8..])
-                        } else {
-                            disk.mount.clone()
-                        };
-                        let color = theme::disk_color(disk.fraction);
-                        LcarsGauge::new(label, disk.fraction, color)
-                            .width(col_width)
-                            .font_size(20.0)
-                            .show(ui);
-                        ui.label(
-                            egui::RichText::new(format!(
-                                "          {} / {}",
-                                format_bytes(disk.used),
-                                format_bytes(disk.total)
-                            ))
-                            .color(color)
-                            .font(egui::FontId::monospace(20.0)),
-                        );
-                    }
+                    LcarsGauge::new("Swap", sys.swap_fraction(), theme::LAVENDER)
+                        .width(col_width)
+                        .font_size(20.0)
+                        .show(ui);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "          {} / {}",
+                            format_bytes(sys.swap_used()),
+                            format_bytes(sys.swap_total())
+                        ))
+                        .color(theme::LAVENDER)
+                        .font(egui::FontId::monospace(20.0)),
+                    );
                 });
             });
+
+            LcarsSparkline::new("History", &sys.mem_history, 1.0, theme::PEACH)
+                .width(full_width)
+                .font_size(20.0)
+                .show(ui);
         });
     }
 
-    // Network Panel
-    let nets = sys.network_info();
-    if !nets.is_empty() {
-        LcarsPanel::new("Network", theme::BLUE).show(ui, |ui| {
-            let full_width = ui.available_width() - 20.0;
-            let col_width = (full_width - 16.0) / 2.0;
-            let half = (nets.len() + 1) / 2;
+    // Disk Panel
+    if panels.storage {
+        let disks = sys.disk_info();
+        let devices = sys.block_devices();
+        if !disks.is_empty() || !devices.is_empty() {
+            LcarsPanel::new("Storage", theme::PERIWINKLE).show(ui, |ui| {
+                let full_width = ui.available_width() - 20.0;
+                let col_width = (full_width - 16.0) / 2.0;
+                let half = disks.len().div_ceil(2);
 
-            ui.horizontal(|ui| {
-                // Left column
-                ui.vertical(|ui| {
-                    for net in &nets[..half] {
-                        show_network_iface(ui, net, col_width);
-                    }
+                ui.horizontal(|ui| {
+                    // Left column
+                    ui.vertical(|ui| {
+                        for disk in &disks[..half] {
+                            let label = if disk.mount.len() > 10 {
+                                format!("..{}", &disk.mount[disk.mount.len() - 8..])
+                            } else {
+                                disk.mount.clone()
+                            };
+                            let color = theme::disk_color(disk.fraction);
+                            LcarsGauge::new(label, disk.fraction, color)
+                                .width(col_width)
+                                .font_size(20.0)
+                                .show(ui);
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "          {} / {}   {}",
+                                    format_bytes(disk.used),
+                                    format_bytes(disk.total),
+                                    disk.device.to_uppercase()
+                                ))
+                                .color(color)
+                                .font(egui::FontId::monospace(20.0)),
+                            );
+                        }
+                    });
+
+                    // Right column
+                    ui.vertical(|ui| {
+                        for disk in &disks[half..] {
+                            let label = if disk.mount.len() > 10 {
+                                format!("..{}", &disk.mount[disk.mount.len() - 8..])
+                            } else {
+                                disk.mount.clone()
+                            };
+                            let color = theme::disk_color(disk.fraction);
+                            LcarsGauge::new(label, disk.fraction, color)
+                                .width(col_width)
+                                .font_size(20.0)
+                                .show(ui);
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "          {} / {}   {}",
+                                    format_bytes(disk.used),
+                                    format_bytes(disk.total),
+                                    disk.device.to_uppercase()
+                                ))
+                                .color(color)
+                                .font(egui::FontId::monospace(20.0)),
+                            );
+                        }
+                    });
                 });
 
-                // Right column
-                ui.vertical(|ui| {
-                    for net in &nets[half..] {
-                        show_network_iface(ui, net, col_width);
-                    }
+                // Per-disk I/O, busy time as the gauge
+                for dev in devices {
+                    let color = theme::disk_color(dev.busy);
+                    LcarsGauge::new(dev.name.to_uppercase(), dev.busy, color)
+                        .width(full_width)
+                        .font_size(20.0)
+                        .show(ui);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "          READ {} {:.0} IOPS   WRITE {} {:.0} IOPS   QUEUE {:.2}   {}",
+                            format_rate(dev.read_rate),
+                            dev.read_iops,
+                            format_rate(dev.write_rate),
+                            dev.write_iops,
+                            dev.queue_depth,
+                            dev.mounts.join("  ")
+                        ))
+                        .color(color)
+                        .font(egui::FontId::monospace(20.0)),
+                    );
+                }
+            });
+        }
+    }
+
+    // Network Panel
+    if panels.network {
+        let nets = sys.network_info();
+        if !nets.is_empty() {
+            LcarsPanel::new("Network", theme::BLUE).show(ui, |ui| {
+                let full_width = ui.available_width() - 20.0;
+                let col_width = (full_width - 16.0) / 2.0;
+                let half = nets.len().div_ceil(2);
+
+                ui.horizontal(|ui| {
+                    // Left column
+                    ui.vertical(|ui| {
+                        for net in &nets[..half] {
+                            show_network_iface(ui, net, sys.net_history.get(&net.name), col_width);
+                        }
+                    });
+
+                    // Right column
+                    ui.vertical(|ui| {
+                        for net in &nets[half..] {
+                            show_network_iface(ui, net, sys.net_history.get(&net.name), col_width);
+                        }
+                    });
                 });
             });
-        });
+        }
+    }
+}
+
+/// Packages and psys fill towards their power limit, subdomains towards
+/// their package's draw.
+fn show_power_domain(ui: &mut Ui, domain: &PowerDomain, all: &[PowerDomain], width: f32) {
+    let (fraction, color, label) = if domain.parent.is_empty() {
+        let fraction = match (domain.watts, domain.limit) {
+            (Some(w), Some(limit)) => (w / limit) as f32,
+            _ => 0.0,
+        };
+        (fraction, theme::disk_color(fraction), domain.name.to_uppercase())
+    } else {
+        let package = all.iter().find(|d| d.zone == domain.parent).and_then(|d| d.watts);
+        let fraction = match (domain.watts, package) {
+            (Some(w), Some(p)) if p > 0.0 => (w / p) as f32,
+            _ => 0.0,
+        };
+        (fraction, theme::PEACH, format!("  {}", domain.name.to_uppercase()))
+    };
+    let value = match (domain.watts, domain.limit) {
+        (Some(w), Some(limit)) => format!("{:.1} / {:.0} W", w, limit),
+        (Some(w), None) => format!("{:.1} W", w),
+        (None, _) if domain.denied => "ACCESS DENIED".to_string(),
+        (None, _) => "—".to_string(),
+    };
+    LcarsGauge::new(label, fraction, color)
+        .width(width)
+        .label_width(200.0)
+        .value_text(value)
+        .font_size(20.0)
+        .show(ui);
+}
+
+fn show_power_line(ui: &mut Ui, label: &str, watts: f64, color: egui::Color32) {
+    ui.label(
+        egui::RichText::new(format!("{:<18}{:.1} W", label, watts))
+            .color(color)
+            .font(egui::FontId::monospace(20.0)),
+    );
+}
+
+/// Whole-system draw from the best source available: the platform (psys)
+/// domain, else the battery while it alone powers the machine, else CPU
+/// packages plus GPUs as a lower bound.
+fn system_draw(domains: &[PowerDomain], battery: &BatteryInfo, gpu_watts: f64) -> Option<(f64, &'static str)> {
+    if let Some(psys) = domains.iter().find(|d| d.name == "psys").and_then(|d| d.watts) {
+        return Some((psys, "PLATFORM"));
     }
+    if battery.available && battery.status == "Discharging" && battery.power_now > 0.0 {
+        return Some((battery.power_now, "BATTERY"));
+    }
+    let packages: Vec<f64> = domains.iter().filter(|d| d.is_package()).filter_map(|d| d.watts).collect();
+    if packages.is_empty() {
+        return None;
+    }
+    Some((packages.iter().sum::<f64>() + gpu_watts, "CPU + GPU"))
 }
 
 fn show_cpu_prop(ui: &mut Ui, label: &str, value: &str) {
@@ -250,11 +384,7 @@ fn show_cpu_prop(ui: &mut Ui, label: &str, value: &str) {
     ui.add_space(4.0);
 }
 
-fn format_rate(bytes_per_sec: f64) -> String {
-    format!("{}/s", format_bytes(bytes_per_sec as u64))
-}
-
-fn show_network_iface(ui: &mut Ui, net: &NetworkData, width: f32) {
+fn show_network_iface(ui: &mut Ui, net: &NetworkData, history: Option<&NetHistory>, width: f32) {
     ui.label(
         egui::RichText::new(net.name.to_uppercase())
             .color(theme::BLUE)
@@ -289,5 +419,84 @@ fn show_network_iface(ui: &mut Ui, net: &NetworkData, width: f32) {
         .font(egui::FontId::monospace(20.0)),
     );
 
+    if let Some(h) = history {
+        // Shared scale so RX and TX are visually comparable; floor at 1 KB/s
+        let scale = h.rx.max().max(h.tx.max()).max(1_000.0);
+        LcarsSparkline::new("RX", &h.rx, scale, theme::BLUE)
+            .width(width)
+            .font_size(20.0)
+            .value_text(format_rate(net.rx_rate))
+            .show(ui);
+        LcarsSparkline::new("TX", &h.tx, scale, theme::PEACH)
+            .width(width)
+            .font_size(20.0)
+            .value_text(format_rate(net.tx_rate))
+            .show(ui);
+    }
+
     ui.add_space(8.0);
 }
+
+const CORE_FREQ_W: f32 = 150.0; // frequency and idle state beside a core's gauge
+
+/// Governor, EPP, boost and driver shared by the cores, when cpufreq is present.
+fn scaling_summary(freq: &CpuFreq) -> Option<String> {
+    let mut parts = Vec::new();
+    if !freq.governor.is_empty() {
+        parts.push(format!("GOVERNOR {}", freq.governor));
+    }
+    if !freq.epp.is_empty() {
+        parts.push(format!("EPP {}", freq.epp));
+    }
+    if let Some(boost) = freq.boost {
+        parts.push(format!("BOOST {}", if boost { "ON" } else { "OFF" }));
+    }
+    if !freq.driver.is_empty() {
+        parts.push(freq.driver.clone());
+    }
+    (!parts.is_empty()).then(|| format!("          {}", parts.join("   ").to_uppercase()))
+}
+
+/// A core's usage gauge, followed by its clock and the idle state it spent
+/// most time in; every idle state's residency is in the hover text.
+fn show_core(ui: &mut Ui, index: usize, usage: f32, color: egui::Color32, width: f32, freq: &CpuFreq) {
+    // sysinfo lists online cores in CPU-number order
+    let Some(core) = freq.cores.get(index).filter(|c| c.mhz.is_some() || !c.idle.is_empty()) else {
+        LcarsGauge::new(format!("Core {}", index), usage / 100.0, color)
+            .width(width)
+            .font_size(20.0)
+            .show(ui);
+        return;
+    };
+    ui.horizontal(|ui| {
+        LcarsGauge::new(format!("Core {}", index), usage / 100.0, color)
+            .width(width - CORE_FREQ_W)
+            .font_size(20.0)
+            .show(ui);
+        let clock = core.mhz.map_or("—".to_string(), |mhz| format!("{:.2} GHz", mhz / 1000.0));
+        let idle = core
+            .dominant_idle()
+            .map(|s| format!("{} {:.0}%", s.name, s.residency * 100.0))
+            .unwrap_or_default();
+        ui.label(
+            egui::RichText::new(format!("{}  {}", clock, idle))
+                .color(color)
+                .font(egui::FontId::monospace(18.0)),
+        )
+        .on_hover_text(core_detail(core));
+    });
+}
+
+fn core_detail(core: &CoreFreq) -> String {
+    let mut lines = vec![format!("CPU {}", core.cpu)];
+    if !core.governor.is_empty() {
+        lines.push(format!("GOVERNOR {}", core.governor.to_uppercase()));
+    }
+    if !core.epp.is_empty() {
+        lines.push(format!("EPP {}", core.epp.to_uppercase()));
+    }
+    for state in &core.idle {
+        lines.push(format!("{:<6} {:5.1}%", state.name, state.residency * 100.0));
+    }
+    lines.join("\n")
+}
@@ -3,11 +3,14 @@ use egui::Ui;
 use crate::system::gpu::GpuInfo;
 use crate::system::info::format_bytes;
 use crate::theme;
+use crate::widgets::button::LcarsButton;
 use crate::widgets::gauge::LcarsGauge;
 use crate::widgets::panel::LcarsPanel;
+use crate::widgets::sparkline::LcarsSparkline;
 
-pub fn show(ui: &mut Ui, gpu: &GpuInfo) {
-    if !gpu.available {
+/// `selected` holds the card name to focus on; `None` shows every card side by side.
+pub fn show(ui: &mut Ui, gpus: &[GpuInfo], selected: &mut Option<String>) {
+    if gpus.is_empty() {
         ui.add_space(20.0);
         ui.label(
             egui::RichText::new("NO GPU DETECTED")
@@ -17,6 +20,49 @@ pub fn show(ui: &mut Ui, gpu: &GpuInfo) {
         return;
     }
 
+    if gpus.len() == 1 {
+        show_gpu(ui, &gpus[0]);
+        return;
+    }
+
+    // Device selector
+    let button_size = egui::Vec2::new(180.0, 34.0);
+    ui.horizontal(|ui| {
+        let color = if selected.is_none() { theme::ORANGE } else { theme::PERIWINKLE };
+        if LcarsButton::new("All", color).size(button_size).font_size(24.0).show(ui).clicked() {
+            *selected = None;
+        }
+        for gpu in gpus {
+            let active = selected.as_deref() == Some(gpu.card.as_str());
+            let color = if active { theme::ORANGE } else { theme::MAGENTA };
+            let label = format!("{} {}", gpu.card, gpu.kind.label());
+            if LcarsButton::new(label, color).size(button_size).font_size(24.0).show(ui).clicked() {
+                *selected = Some(gpu.card.clone());
+            }
+        }
+    });
+    ui.add_space(8.0);
+
+    // A card that disappeared falls back to the side-by-side view
+    match selected.as_deref().and_then(|card| gpus.iter().find(|g| g.card == card)) {
+        Some(gpu) => show_gpu(ui, gpu),
+        None => {
+            *selected = None;
+            ui.columns(gpus.len(), |cols| {
+                for (col, gpu) in cols.iter_mut().zip(gpus) {
+                    col.label(
+                        egui::RichText::new(format!("{}  \u{2022}  {}", gpu.card.to_uppercase(), gpu.kind.label()))
+                            .color(theme::ORANGE)
+                            .font(egui::FontId::monospace(30.0)),
+                    );
+                    show_gpu(col, gpu);
+                }
+            });
+        }
+    }
+}
+
+fn show_gpu(ui: &mut Ui, gpu: &GpuInfo) {
     let full_width = ui.available_width() - 20.0;
 
     // Usage Panel
@@ -25,6 +71,10 @@ pub fn show(ui: &mut Ui, gpu: &GpuInfo) {
             .width(full_width)
             .font_size(20.0)
             .show(ui);
+        LcarsSparkline::new("HISTORY", &gpu.usage_history, 100.0, theme::MAGENTA)
+            .width(full_width)
+            .font_size(20.0)
+            .show(ui);
 
         ui.add_space(8.0);
 
@@ -63,6 +113,17 @@ pub fn show(ui: &mut Ui, gpu: &GpuInfo) {
         show_stat(ui, "GPU FREQUENCY", &freq_str, theme::MAGENTA);
         show_stat(ui, "MEM FREQUENCY", &mfreq_str, theme::MAGENTA);
         show_stat(ui, "POWER USAGE", &format!("{:.1} W", gpu.power_w), theme::MAGENTA);
+
+        let power_scale = gpu
+            .power_cap_w
+            .map(|p| p as f32)
+            .unwrap_or_else(|| gpu.power_history.max())
+            .max(1.0);
+        LcarsSparkline::new("POWER", &gpu.power_history, power_scale, theme::MAGENTA)
+            .width(full_width)
+            .font_size(20.0)
+            .value_text(format!("{:.1} W", gpu.power_w))
+            .show(ui);
     });
 
     // Sensors Panel
@@ -92,9 +153,12 @@ pub fn show(ui: &mut Ui, gpu: &GpuInfo) {
             .unwrap_or_else(|| "N/A".to_string());
 
         let props: &[(&str, String)] = &[
+            ("CARD", gpu.card.clone()),
+            ("TYPE", gpu.kind.label().to_string()),
             ("MANUFACTURER", gpu.manufacturer.clone()),
             ("PCI SLOT", gpu.pci_slot.clone()),
             ("DRIVER", gpu.driver.clone()),
+            ("BACKEND", gpu.backend_name.clone()),
             ("MAX POWER CAP", power_cap_str),
             ("LINK", gpu.pcie_link.clone()),
         ];
@@ -0,0 +1,56 @@
+use egui::{Rounding, Stroke, Vec2};
+
+use crate::keymap::{Action, Keymap};
+use crate::theme;
+use crate::widgets::button::LcarsButton;
+use crate::widgets::panel::LcarsPanel;
+
+const KEYS_W: f32 = 220.0;
+const ROW_H: f32 = 30.0;
+
+/// Key reference drawn over the whole window, listing the bindings in effect
+/// (configured ones included). Returns true when CLOSE was clicked.
+pub fn show(ctx: &egui::Context, keymap: &Keymap) -> bool {
+    let mut close = false;
+    egui::Area::new(egui::Id::new("help_overlay"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Frame::none()
+                .fill(theme::BLACK)
+                .stroke(Stroke::new(4.0, theme::ORANGE))
+                .rounding(Rounding::same(24.0))
+                .inner_margin(24.0)
+                .show(ui, |ui| {
+                    ui.set_width(620.0);
+                    LcarsPanel::new("KEYBOARD COMMANDS", theme::ORANGE).show(ui, |ui| {
+                        let key_font = egui::FontId::monospace(22.0);
+                        let label_font = egui::FontId::monospace(20.0);
+                        for (i, action) in Action::ALL.into_iter().enumerate() {
+                            let keys = keymap.keys(action);
+                            let keys = if keys.is_empty() { "—".to_string() } else { keys.join(" / ") };
+                            let color = theme::color_for_index(i);
+                            ui.horizontal(|ui| {
+                                let (r, _) = ui.allocate_exact_size(Vec2::new(KEYS_W, ROW_H), egui::Sense::hover());
+                                ui.painter().rect_filled(r.shrink2(Vec2::new(0.0, 3.0)), Rounding::same(ROW_H / 2.0 - 3.0), color);
+                                ui.painter().text(
+                                    r.right_center() - egui::vec2(ROW_H / 2.0, -2.0),
+                                    egui::Align2::RIGHT_CENTER,
+                                    keys,
+                                    key_font.clone(),
+                                    theme::BLACK,
+                                );
+                                ui.add_space(theme::BAR_SPACING * 2.0);
+                                ui.label(egui::RichText::new(action.label()).color(color).font(label_font.clone()));
+                            });
+                        }
+                    });
+                    close = LcarsButton::new("CLOSE", theme::ORANGE)
+                        .size(Vec2::new(120.0, theme::BUTTON_HEIGHT))
+                        .font_size(24.0)
+                        .show(ui)
+                        .clicked();
+                });
+        });
+    close
+}
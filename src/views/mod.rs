@@ -1,4 +1,8 @@
 pub mod battery;
 pub mod dashboard;
 pub mod gpu;
+pub mod help;
+pub mod process;
 pub mod processes;
+pub mod replay;
+pub mod sensors;
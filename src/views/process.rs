@@ -0,0 +1,146 @@
+use egui::Ui;
+
+use crate::system::detail::ProcessDetail;
+use crate::system::info::format_bytes;
+use crate::theme;
+use crate::widgets::button::LcarsButton;
+use crate::widgets::gauge::LcarsGauge;
+use crate::widgets::panel::LcarsPanel;
+use crate::widgets::sparkline::LcarsSparkline;
+
+const DENIED: &str = "ACCESS DENIED";
+
+/// Full detail for one process. `detail` is `None` (or another PID) once the
+/// process has exited or, in a replay, when it was not being inspected.
+/// `replay` marks a recorded detail, which never holds the environment.
+/// Returns true when the user asked to go back to the process table.
+pub fn show(ui: &mut Ui, pid: u32, detail: Option<&ProcessDetail>, replay: bool) -> bool {
+    let back = LcarsButton::new("BACK", theme::ORANGE)
+        .size(egui::Vec2::new(120.0, theme::BUTTON_HEIGHT))
+        .font_size(24.0)
+        .show(ui)
+        .clicked();
+    ui.add_space(8.0);
+
+    let Some(d) = detail.filter(|d| d.pid == pid) else {
+        ui.label(
+            egui::RichText::new(format!("PROCESS {} NOT AVAILABLE", pid))
+                .color(theme::ORANGE)
+                .font(egui::FontId::monospace(40.0)),
+        );
+        return back;
+    };
+    let full_width = ui.available_width() - 20.0;
+
+    LcarsPanel::new(format!("{}  {}", d.name, d.pid), theme::ORANGE).show(ui, |ui| {
+        let start = d
+            .start_time
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string());
+        show_props(ui, theme::ORANGE, &[
+            ("COMMAND", if d.cmdline.is_empty() { format!("[{}]", d.name) } else { d.cmdline.join(" ") }),
+            ("EXECUTABLE", d.exe.clone().unwrap_or_else(|| DENIED.to_string())),
+            ("WORKING DIRECTORY", d.cwd.clone().unwrap_or_else(|| DENIED.to_string())),
+            ("USER", d.user.clone()),
+            ("STATE", d.state.to_uppercase()),
+            ("STARTED", start.unwrap_or_else(|| "?".to_string())),
+            ("THREADS", d.threads.to_string()),
+            ("CGROUP", d.cgroup.clone()),
+        ]);
+    });
+
+    LcarsPanel::new("Processor", theme::PEACH).show(ui, |ui| {
+        LcarsGauge::new("CPU", (d.cpu_usage / 100.0).clamp(0.0, 1.0), theme::PEACH)
+            .width(full_width)
+            .font_size(20.0)
+            .show(ui);
+        LcarsSparkline::new("HISTORY", &d.cpu_history, 100.0, theme::PEACH)
+            .width(full_width)
+            .font_size(20.0)
+            .value_text(format!("{:.1}%", d.cpu_usage))
+            .show(ui);
+    });
+
+    LcarsPanel::new("Memory", theme::LAVENDER).show(ui, |ui| {
+        match d.memory {
+            Some(m) => show_props(ui, theme::LAVENDER, &[
+                ("RESIDENT (RSS)", format_bytes(m.rss)),
+                ("PROPORTIONAL (PSS)", format_bytes(m.pss)),
+                ("UNIQUE (USS)", format_bytes(m.uss)),
+                ("SWAPPED", format_bytes(m.swap)),
+            ]),
+            None => show_props(ui, theme::LAVENDER, &[("RESIDENT (RSS)", format_bytes(d.resident))]),
+        }
+        ui.add_space(4.0);
+        LcarsSparkline::new("HISTORY", &d.mem_history, d.mem_history.max().max(1.0), theme::LAVENDER)
+            .width(full_width)
+            .font_size(20.0)
+            .value_text(format_bytes(d.resident))
+            .show(ui);
+    });
+
+    LcarsPanel::new("Disk I/O", theme::BLUE).show(ui, |ui| match d.io {
+        Some(io) => show_props(ui, theme::BLUE, &[
+            ("STORAGE READ", format_bytes(io.read_bytes)),
+            ("STORAGE WRITTEN", format_bytes(io.write_bytes)),
+            ("WRITES CANCELLED", format_bytes(io.cancelled_write_bytes)),
+            ("CHARACTERS READ", format_bytes(io.rchar)),
+            ("CHARACTERS WRITTEN", format_bytes(io.wchar)),
+            ("READ CALLS", io.syscr.to_string()),
+            ("WRITE CALLS", io.syscw.to_string()),
+        ]),
+        None => show_props(ui, theme::BLUE, &[("COUNTERS", DENIED.to_string())]),
+    });
+
+    LcarsPanel::new("Namespaces", theme::PERIWINKLE).show(ui, |ui| match &d.namespaces {
+        Some(ns) => {
+            let props: Vec<(String, String)> = ns.iter().map(|(kind, inode)| (kind.to_uppercase(), inode.clone())).collect();
+            show_props(ui, theme::PERIWINKLE, &props);
+        }
+        None => show_props(ui, theme::PERIWINKLE, &[("NAMESPACES", DENIED.to_string())]),
+    });
+
+    LcarsPanel::new("File Descriptors", theme::MAGENTA).show(ui, |ui| match d.fd_count {
+        Some(count) => {
+            let mut props = vec![("OPEN".to_string(), count.to_string())];
+            props.extend(d.fds.iter().map(|(fd, target)| (fd.to_string(), target.clone())));
+            if count > d.fds.len() {
+                props.push((String::new(), format!("+{} MORE", count - d.fds.len())));
+            }
+            show_props(ui, theme::MAGENTA, &props);
+        }
+        None => show_props(ui, theme::MAGENTA, &[("OPEN", DENIED.to_string())]),
+    });
+
+    LcarsPanel::new("Environment", theme::GREEN).show(ui, |ui| match &d.environ {
+        Some(vars) => {
+            let props: Vec<(&str, String)> = vars
+                .iter()
+                .map(|v| v.split_once('=').map_or((v.as_str(), String::new()), |(k, val)| (k, val.to_string())))
+                .collect();
+            show_props(ui, theme::GREEN, &props);
+        }
+        None if replay => show_props(ui, theme::GREEN, &[("VARIABLES", "NOT RECORDED".to_string())]),
+        None => show_props(ui, theme::GREEN, &[("VARIABLES", DENIED.to_string())]),
+    });
+
+    back
+}
+
+/// Label/value rows in the style of the battery properties panel.
+fn show_props<L: AsRef<str>>(ui: &mut Ui, color: egui::Color32, props: &[(L, String)]) {
+    let label_font = egui::FontId::monospace(18.0);
+    let value_font = egui::FontId::monospace(22.0);
+    let label_color = color.linear_multiply(0.65);
+    let row_h = 30.0;
+    let label_w = 260.0;
+
+    for (label, value) in props {
+        ui.horizontal(|ui| {
+            let (r, _) = ui.allocate_exact_size(egui::Vec2::new(label_w, row_h), egui::Sense::hover());
+            ui.painter().text(r.left_center(), egui::Align2::LEFT_CENTER, label.as_ref(), label_font.clone(), label_color);
+            ui.add(egui::Label::new(egui::RichText::new(value.as_str()).color(color).font(value_font.clone())).wrap());
+        });
+        ui.add_space(2.0);
+    }
+}
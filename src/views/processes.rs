@@ -1,11 +1,13 @@
 use egui::{Ui, Vec2};
 
-use crate::system::info::format_bytes;
-use crate::system::process::{ProcessView, SortColumn, SortOrder};
+use crate::config::COLUMN_WIDTH_RANGE;
+use crate::system::info::{format_bytes, format_rate, format_uptime};
+use crate::system::process::{ProcessInfo, ProcessRow, ProcessView, Column, SortOrder};
+use crate::system::signal::ProcessAction;
 use crate::theme;
-use sysinfo::{System, Users};
+use crate::widgets::button::LcarsButton;
 
-fn arrow_str(pv: &ProcessView, col: SortColumn) -> &'static str {
+fn arrow_str(pv: &ProcessView, col: Column) -> &'static str {
     if pv.sort_column == col {
         match pv.sort_order {
             SortOrder::Ascending => " ^",
@@ -26,14 +28,11 @@ fn cpu_color(usage: f32) -> egui::Color32 {
     }
 }
 
-// Column widths
-const NAME_W: f32 = 220.0;
-const PID_W:  f32 = 110.0;
-const USER_W: f32 = 120.0;
-const MEM_W:  f32 = 130.0;
-const CPU_W:  f32 = 100.0;
-const GPU_W:  f32 = 90.0;
-const ROW_H:  f32 = 34.0;
+const ROW_H: f32 = 34.0;
+const TREE_INDENT: f32 = 18.0; // per nesting level
+const RESIZE_GRIP: f32 = 8.0;  // draggable band around a header's right edge
+
+const FILTER_ID: &str = "process_filter";
 
 // Child column widths
 const CHILD_NAME_W: f32 = 160.0;
@@ -42,85 +41,315 @@ const CHILD_USER_W: f32 = 90.0;
 const CHILD_MEM_W:  f32 = 100.0;
 const CHILD_CPU_W:  f32 = 80.0;
 
-/// Draw the sticky column headers (call outside scroll area)
-pub fn show_header(ui: &mut Ui, pv: &mut ProcessView) {
-    let font = egui::FontId::monospace(28.0);
+/// Give the filter field keyboard focus; takes effect when it is next drawn.
+pub fn focus_filter(ctx: &egui::Context) {
+    ctx.memory_mut(|m| m.request_focus(egui::Id::new(FILTER_ID)));
+}
 
-    ui.horizontal(|ui| {
-        // PROCESS (name) — sortable
-        let (r, _) = ui.allocate_exact_size(Vec2::new(NAME_W, ROW_H), egui::Sense::click());
-        if ui.allocate_rect(r, egui::Sense::click()).clicked() {
-            pv.toggle_sort(SortColumn::Name);
+/// Confirmation prompt for a pending signal, or the result of the last one
+fn show_action_bar(ui: &mut Ui, pv: &mut ProcessView) {
+    let font = egui::FontId::monospace(24.0);
+    let button_size = Vec2::new(120.0, 30.0);
+
+    if let Some(pending) = &pv.pending_action {
+        let pid = pending.targets[0].0;
+        let target = if pending.targets.len() > 1 {
+            format!("{} ({}) AND {} DESCENDANTS", pending.name, pid, pending.targets.len() - 1)
+        } else {
+            format!("{} ({})", pending.name, pid)
+        };
+        let prompt = format!("{} {}?", pending.action.label(), target.to_uppercase());
+
+        let mut confirm = false;
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(prompt).color(theme::YELLOW).font(font.clone()));
+            ui.add_space(12.0);
+            confirm = LcarsButton::new("Confirm", theme::RED)
+                .size(button_size)
+                .font_size(22.0)
+                .show(ui)
+                .clicked();
+            cancel = LcarsButton::new("Cancel", theme::PERIWINKLE)
+                .size(button_size)
+                .font_size(22.0)
+                .show(ui)
+                .clicked();
+        });
+        if confirm {
+            pv.confirm_action();
+        } else if cancel {
+            pv.cancel_action();
+        }
+        ui.add_space(4.0);
+    } else if let Some((message, is_error)) = &pv.action_status {
+        let color = if *is_error { theme::RED } else { theme::GREEN };
+        let mut dismiss = false;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(message.as_str()).color(color).font(font.clone()));
+            ui.add_space(12.0);
+            dismiss = LcarsButton::new("Dismiss", theme::PERIWINKLE)
+                .size(button_size)
+                .font_size(22.0)
+                .show(ui)
+                .clicked();
+        });
+        if dismiss {
+            pv.action_status = None;
         }
-        ui.painter().text(
-            r.left_center() + egui::vec2(4.0, 0.0),
-            egui::Align2::LEFT_CENTER,
-            format!("PROCESS{}", arrow_str(pv, SortColumn::Name)),
-            font.clone(),
-            theme::ORANGE,
+        ui.add_space(4.0);
+    }
+}
+
+/// Right-click menu for a process row; returns the action the user picked.
+/// A replayed table (`renice_value` is `None`) offers only the detail view.
+fn row_context_menu(
+    ui: &mut Ui,
+    name: &str,
+    pid: u32,
+    subtree: bool,
+    renice_value: Option<&mut i32>,
+    inspect_pid: &mut Option<u32>,
+) -> Option<ProcessAction> {
+    let font = egui::FontId::monospace(22.0);
+    let mut picked = None;
+
+    ui.label(
+        egui::RichText::new(format!("{} ({})", name.to_uppercase(), pid))
+            .color(theme::ORANGE)
+            .font(font.clone()),
+    );
+    if subtree && renice_value.is_some() {
+        ui.label(
+            egui::RichText::new("APPLIES TO SUBTREE")
+                .color(theme::LAVENDER)
+                .font(egui::FontId::monospace(18.0)),
         );
+    }
+    ui.separator();
 
-        // PID — sortable
-        let (r, _) = ui.allocate_exact_size(Vec2::new(PID_W, ROW_H), egui::Sense::click());
-        if ui.allocate_rect(r, egui::Sense::click()).clicked() {
-            pv.toggle_sort(SortColumn::Pid);
+    if ui.button(egui::RichText::new("DETAILS").font(font.clone())).clicked() {
+        *inspect_pid = Some(pid);
+        ui.ctx().request_repaint();
+        ui.close_menu();
+    }
+    let renice_value = renice_value?;
+
+    for action in [
+        ProcessAction::Terminate,
+        ProcessAction::Kill,
+        ProcessAction::Stop,
+        ProcessAction::Continue,
+    ] {
+        if ui.button(egui::RichText::new(action.label()).font(font.clone())).clicked() {
+            picked = Some(action);
         }
-        ui.painter().text(
-            r.left_center() + egui::vec2(4.0, 0.0),
-            egui::Align2::LEFT_CENTER,
-            format!("PROCESS ID{}", arrow_str(pv, SortColumn::Pid)),
-            font.clone(),
-            theme::ORANGE,
-        );
+    }
 
-        // USER — sortable
-        let (r, _) = ui.allocate_exact_size(Vec2::new(USER_W, ROW_H), egui::Sense::click());
-        if ui.allocate_rect(r, egui::Sense::click()).clicked() {
-            pv.toggle_sort(SortColumn::User);
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.add(egui::DragValue::new(renice_value).range(-20..=19));
+        if ui.button(egui::RichText::new("RENICE").font(font.clone())).clicked() {
+            picked = Some(ProcessAction::Renice(*renice_value));
         }
-        ui.painter().text(
-            r.left_center() + egui::vec2(4.0, 0.0),
-            egui::Align2::LEFT_CENTER,
-            format!("USER{}", arrow_str(pv, SortColumn::User)),
-            font.clone(),
-            theme::ORANGE,
-        );
+    });
+
+    if picked.is_some() {
+        ui.close_menu();
+    }
+    picked
+}
 
-        // MEMORY — sortable, right-aligned
-        let (r, _) = ui.allocate_exact_size(Vec2::new(MEM_W, ROW_H), egui::Sense::click());
-        if ui.allocate_rect(r, egui::Sense::click()).clicked() {
-            pv.toggle_sort(SortColumn::Memory);
+/// Incremental filter box with regex / ownership toggles
+fn show_filter_bar(ui: &mut Ui, pv: &mut ProcessView) {
+    let font = egui::FontId::monospace(24.0);
+    let button_size = Vec2::new(110.0, 30.0);
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("FILTER").color(theme::ORANGE).font(font.clone()));
+        let edit = egui::TextEdit::singleline(&mut pv.filter.text)
+            .id(egui::Id::new(FILTER_ID))
+            .hint_text("NAME, PID, USER OR COMMAND")
+            .font(font.clone())
+            .text_color(theme::PEACH)
+            .desired_width(360.0);
+        changed |= ui.add(edit).changed();
+
+        let toggles: [(&str, &mut bool); 3] = [
+            ("Regex", &mut pv.filter.regex_mode),
+            ("Mine", &mut pv.filter.mine_only),
+            ("User only", &mut pv.filter.hide_system),
+        ];
+        for (label, on) in toggles {
+            let color = if *on { theme::ORANGE } else { theme::PERIWINKLE.linear_multiply(0.5) };
+            if LcarsButton::new(label, color).size(button_size).font_size(22.0).show(ui).clicked() {
+                *on = !*on;
+                changed = true;
+            }
         }
-        ui.painter().text(
-            r.right_center() - egui::vec2(4.0, 0.0),
-            egui::Align2::RIGHT_CENTER,
-            format!("MEMORY{}", arrow_str(pv, SortColumn::Memory)),
-            font.clone(),
-            theme::ORANGE,
-        );
 
-        // PROCESSOR (CPU%) — sortable, right-aligned
-        let (r, _) = ui.allocate_exact_size(Vec2::new(CPU_W, ROW_H), egui::Sense::click());
-        if ui.allocate_rect(r, egui::Sense::click()).clicked() {
-            pv.toggle_sort(SortColumn::Cpu);
+        if pv.filter.is_active()
+            && LcarsButton::new("Clear", theme::LAVENDER).size(button_size).font_size(22.0).show(ui).clicked()
+        {
+            pv.filter.text.clear();
+            pv.filter.mine_only = false;
+            pv.filter.hide_system = false;
+            changed = true;
         }
-        ui.painter().text(
-            r.right_center() - egui::vec2(4.0, 0.0),
-            egui::Align2::RIGHT_CENTER,
-            format!("PROCESSOR{}", arrow_str(pv, SortColumn::Cpu)),
-            font.clone(),
-            theme::ORANGE,
-        );
 
-        // GPU — static, right-aligned
-        let (r, _) = ui.allocate_exact_size(Vec2::new(GPU_W, ROW_H), egui::Sense::hover());
-        ui.painter().text(
-            r.right_center() - egui::vec2(4.0, 0.0),
-            egui::Align2::RIGHT_CENTER,
-            "GPU",
-            font.clone(),
-            theme::ORANGE,
-        );
+        if let Some(err) = &pv.filter.error {
+            ui.label(egui::RichText::new(err.as_str()).color(theme::RED).font(egui::FontId::monospace(18.0)));
+        }
+    });
+
+    if changed {
+        pv.filter.update();
+    }
+    ui.add_space(4.0);
+}
+
+/// Title, default width and right alignment of each column
+fn column_spec(column: Column) -> (&'static str, f32, bool) {
+    match column {
+        Column::Name => ("PROCESS", 220.0, false),
+        Column::Pid => ("PROCESS ID", 110.0, false),
+        Column::Ppid => ("PARENT", 90.0, false),
+        Column::User => ("USER", 120.0, false),
+        Column::State => ("STATE", 70.0, false),
+        Column::Threads => ("THREADS", 90.0, true),
+        Column::Nice => ("NICE", 60.0, true),
+        Column::Priority => ("PRIORITY", 90.0, true),
+        Column::StartTime => ("STARTED", 110.0, false),
+        Column::Elapsed => ("ELAPSED", 110.0, true),
+        Column::Memory => ("MEMORY", 130.0, true),
+        Column::Virtual => ("VIRTUAL", 130.0, true),
+        Column::Shared => ("SHARED", 120.0, true),
+        Column::Cpu => ("PROCESSOR", 100.0, true),
+        Column::Gpu => ("GPU", 90.0, true),
+        Column::DiskRead => ("DISK READ", 130.0, true),
+        Column::DiskWrite => ("DISK WRITE", 130.0, true),
+        Column::NetRx => ("NET RX", 130.0, true),
+        Column::NetTx => ("NET TX", 130.0, true),
+        Column::Command => ("COMMAND", 400.0, false),
+        Column::Cgroup => ("CGROUP", 300.0, false),
+    }
+}
+
+fn column_width(pv: &ProcessView, column: Column) -> f32 {
+    pv.widths.get(&column).copied().unwrap_or(column_spec(column).1)
+}
+
+/// Tree toggles and the column chooser
+fn show_display_bar(ui: &mut Ui, pv: &mut ProcessView, all: &[ProcessInfo]) {
+    let font = egui::FontId::monospace(24.0);
+    let button_size = Vec2::new(110.0, 30.0);
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new("DISPLAY").color(theme::ORANGE).font(font.clone()));
+        let tree_color = if pv.tree { theme::ORANGE } else { theme::PERIWINKLE.linear_multiply(0.5) };
+        if LcarsButton::new("Tree", tree_color).size(button_size).font_size(22.0).show(ui).clicked() {
+            pv.tree = !pv.tree;
+        }
+        if pv.tree {
+            if LcarsButton::new("Expand all", theme::LAVENDER).size(button_size).font_size(22.0).show(ui).clicked() {
+                pv.expand_all(all);
+            }
+            if LcarsButton::new("Collapse all", theme::LAVENDER).size(button_size).font_size(22.0).show(ui).clicked() {
+                pv.collapse_all();
+            }
+        }
+
+        ui.add_space(12.0);
+        let popup = egui::Id::new("process_columns");
+        let button = LcarsButton::new("Columns", theme::PEACH).size(button_size).font_size(22.0).show(ui);
+        if button.clicked() {
+            ui.memory_mut(|m| m.toggle_popup(popup));
+        }
+        egui::popup_below_widget(ui, popup, &button, egui::PopupCloseBehavior::CloseOnClickOutside, |ui| {
+            ui.set_min_width(280.0);
+            column_chooser(ui, pv);
+        });
+    });
+    ui.add_space(4.0);
+}
+
+/// Every column with a visibility checkbox; shown ones can be moved left or right
+fn column_chooser(ui: &mut Ui, pv: &mut ProcessView) {
+    let font = egui::FontId::monospace(22.0);
+    for column in Column::ALL {
+        ui.horizontal(|ui| {
+            let mut shown = pv.columns.contains(&column);
+            let title = egui::RichText::new(column_spec(column).0).font(font.clone());
+            let checkbox = ui.add_enabled(column != Column::Name, egui::Checkbox::new(&mut shown, title));
+            if checkbox.changed() {
+                pv.toggle_column(column);
+            }
+            if shown {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(egui::RichText::new(">").font(font.clone())).on_hover_text("MOVE RIGHT").clicked() {
+                        pv.move_column(column, 1);
+                    }
+                    if ui.button(egui::RichText::new("<").font(font.clone())).on_hover_text("MOVE LEFT").clicked() {
+                        pv.move_column(column, -1);
+                    }
+                });
+            }
+        });
+    }
+    ui.separator();
+    if ui.button(egui::RichText::new("RESET COLUMNS").font(font)).clicked() {
+        pv.columns = Column::DEFAULT.to_vec();
+        pv.widths.clear();
+    }
+}
+
+/// Column title: click to sort, drag its right edge to resize, double-click the edge to reset
+fn column_header(ui: &mut Ui, pv: &mut ProcessView, column: Column, font: &egui::FontId) {
+    let (label, _, right) = column_spec(column);
+    let width = column_width(pv, column);
+    let (r, _) = ui.allocate_exact_size(Vec2::new(width, ROW_H), egui::Sense::hover());
+    let edge = r.right() + ui.spacing().item_spacing.x / 2.0;
+    let grip = egui::Rect::from_x_y_ranges(edge - RESIZE_GRIP / 2.0..=edge + RESIZE_GRIP / 2.0, r.y_range());
+
+    let title = ui.interact(r.with_max_x(grip.left()), ui.id().with(("sort", column)), egui::Sense::click());
+    if title.clicked() {
+        pv.toggle_sort(column);
+    }
+    let resize = ui.interact(grip, ui.id().with(("resize", column)), egui::Sense::click_and_drag());
+    if resize.hovered() || resize.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+        ui.painter().vline(edge, r.y_range(), egui::Stroke::new(2.0, theme::ORANGE.linear_multiply(0.6)));
+    }
+    if resize.dragged() {
+        let (lo, hi) = COLUMN_WIDTH_RANGE;
+        pv.widths.insert(column, (width + resize.drag_delta().x).clamp(lo, hi));
+    }
+    if resize.double_clicked() {
+        pv.widths.remove(&column);
+    }
+
+    let (pos, align) = if right {
+        (r.right_center() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_CENTER)
+    } else {
+        (r.left_center() + egui::vec2(4.0, 0.0), egui::Align2::LEFT_CENTER)
+    };
+    ui.painter()
+        .with_clip_rect(r)
+        .text(pos, align, format!("{}{}", label, arrow_str(pv, column)), font.clone(), theme::ORANGE);
+}
+
+/// Draw the sticky column headers (call outside scroll area)
+pub fn show_header(ui: &mut Ui, pv: &mut ProcessView, all: &[ProcessInfo]) {
+    show_action_bar(ui, pv);
+    show_filter_bar(ui, pv);
+    show_display_bar(ui, pv, all);
+
+    let font = egui::FontId::monospace(28.0);
+
+    ui.horizontal(|ui| {
+        for column in pv.columns.clone() {
+            column_header(ui, pv, column, &font);
+        }
     });
 
     // Separator line
@@ -138,96 +367,69 @@ pub fn show_header(ui: &mut Ui, pv: &mut ProcessView) {
 }
 
 /// Draw the scrollable process rows (call inside scroll area)
-pub fn show_rows(ui: &mut Ui, pv: &mut ProcessView, system: &System, users: &Users) {
-    let procs = pv.get_processes(system, users);
+pub fn show_rows(ui: &mut Ui, pv: &mut ProcessView, all: &[ProcessInfo]) {
+    pv.forget_exited(all);
+    let rows = pv.rows(all);
     let font = egui::FontId::monospace(28.0);
+
+    if rows.is_empty() && pv.filter.is_active() {
+        ui.add_space(12.0);
+        ui.label(
+            egui::RichText::new("NO MATCHING PROCESSES")
+                .color(theme::ORANGE)
+                .font(font.clone()),
+        );
+        return;
+    }
     let row_colors = [theme::PEACH, theme::BLUE];
 
-    let mut toggle_pid: Option<u32> = None;
+    let mut toggle = None;
+    let mut requested: Option<(u32, String, ProcessAction)> = None;
 
-    for (i, proc_info) in procs.iter().enumerate() {
+    for (i, row) in rows.iter().enumerate() {
+        let proc_info = &row.info;
         let base_color = row_colors[i % 2].linear_multiply(0.8);
-        let expanded = pv.is_expanded(proc_info.pid);
+        let expanded = row.expanded;
 
         let row_resp = ui.horizontal(|ui| {
-            // PROCESS name
-            let (r, _) = ui.allocate_exact_size(Vec2::new(NAME_W, ROW_H), egui::Sense::hover());
-            let name = if proc_info.name.len() > 16 {
-                format!("{:.16}", proc_info.name)
-            } else {
-                proc_info.name.clone()
-            };
-            ui.painter().text(
-                r.left_center() + egui::vec2(4.0, 0.0),
-                egui::Align2::LEFT_CENTER,
-                name,
-                font.clone(),
-                base_color,
-            );
-
-            // PID
-            let (r, _) = ui.allocate_exact_size(Vec2::new(PID_W, ROW_H), egui::Sense::hover());
-            ui.painter().text(
-                r.left_center() + egui::vec2(4.0, 0.0),
-                egui::Align2::LEFT_CENTER,
-                format!("{}", proc_info.pid),
-                font.clone(),
-                base_color,
-            );
-
-            // USER
-            let (r, _) = ui.allocate_exact_size(Vec2::new(USER_W, ROW_H), egui::Sense::hover());
-            let user = if proc_info.user.len() > 10 {
-                format!("{:.10}", proc_info.user)
-            } else {
-                proc_info.user.clone()
-            };
-            ui.painter().text(
-                r.left_center() + egui::vec2(4.0, 0.0),
-                egui::Align2::LEFT_CENTER,
-                user,
-                font.clone(),
-                base_color,
-            );
-
-            // MEMORY — right-aligned
-            let (r, _) = ui.allocate_exact_size(Vec2::new(MEM_W, ROW_H), egui::Sense::hover());
-            ui.painter().text(
-                r.right_center() - egui::vec2(4.0, 0.0),
-                egui::Align2::RIGHT_CENTER,
-                format_bytes(proc_info.memory),
-                font.clone(),
-                base_color,
-            );
-
-            // CPU% — right-aligned, color-coded
-            let (r, _) = ui.allocate_exact_size(Vec2::new(CPU_W, ROW_H), egui::Sense::hover());
-            ui.painter().text(
-                r.right_center() - egui::vec2(4.0, 0.0),
-                egui::Align2::RIGHT_CENTER,
-                format!("{:.1}%", proc_info.cpu_usage),
-                font.clone(),
-                cpu_color(proc_info.cpu_usage),
-            );
-
-            // GPU — always 0.0%, right-aligned
-            let (r, _) = ui.allocate_exact_size(Vec2::new(GPU_W, ROW_H), egui::Sense::hover());
-            ui.painter().text(
-                r.right_center() - egui::vec2(4.0, 0.0),
-                egui::Align2::RIGHT_CENTER,
-                "0.0%",
-                font.clone(),
-                base_color.linear_multiply(0.6),
-            );
+            for &column in &pv.columns {
+                show_cell(ui, column, column_width(pv, column), row, pv.tree, base_color, &font);
+            }
         });
 
         // Entire row is clickable to expand/collapse children
         let row_rect = row_resp.response.rect;
-        let click_resp = ui.allocate_rect(row_rect, egui::Sense::click());
-        if click_resp.clicked() {
-            toggle_pid = Some(proc_info.pid);
+        let mut click_resp = ui.allocate_rect(row_rect, egui::Sense::click());
+        if row.descendants > 0 {
+            click_resp = click_resp.on_hover_text(subtree_summary(row));
         }
-        if click_resp.hovered() {
+        if click_resp.double_clicked() {
+            pv.inspect_pid = Some(proc_info.pid);
+            ui.ctx().request_repaint();
+        } else if click_resp.clicked() && (!pv.tree || row.children > 0) {
+            toggle = Some(proc_info.key());
+        }
+        if click_resp.secondary_clicked() {
+            pv.selected_pid = Some(proc_info.pid);
+        }
+        click_resp.context_menu(|ui| {
+            let renice_value = (!pv.read_only).then_some(&mut pv.renice_value);
+            if let Some(action) =
+                row_context_menu(ui, &proc_info.name, proc_info.pid, expanded, renice_value, &mut pv.inspect_pid)
+            {
+                requested = Some((proc_info.pid, proc_info.name.clone(), action));
+            }
+        });
+        if pv.selected_pid == Some(proc_info.pid) {
+            if pv.scroll_to_selected {
+                ui.scroll_to_rect(row_rect, None);
+            }
+            ui.painter().rect_filled(
+                row_rect,
+                egui::Rounding::ZERO,
+                theme::ORANGE.linear_multiply(0.12),
+            );
+        } else if click_resp.hovered() {
             ui.painter().rect_filled(
                 row_rect,
                 egui::Rounding::ZERO,
@@ -235,28 +437,117 @@ pub fn show_rows(ui: &mut Ui, pv: &mut ProcessView, system: &System, users: &Use
             );
         }
 
-        // Render children if expanded
-        if expanded {
-            let children = pv.get_children(proc_info.pid, system, users);
+        // Render children if expanded; the tree lists them as rows of their own
+        if expanded && !pv.tree {
+            let children = pv.get_children(proc_info.pid, all);
             if !children.is_empty() {
                 show_children(ui, &children);
             }
         }
     }
 
-    if let Some(pid) = toggle_pid {
-        pv.toggle_expanded(pid);
+    pv.scroll_to_selected = false;
+    if let Some(key) = toggle {
+        pv.toggle_expanded(key);
+    }
+    if let Some((pid, name, action)) = requested {
+        pv.request_action(pid, name, action, all);
+    }
+}
+
+/// One table cell, clipped to its column
+fn show_cell(ui: &mut Ui, column: Column, width: f32, row: &ProcessRow, tree: bool, base_color: egui::Color32, font: &egui::FontId) {
+    let (r, _) = ui.allocate_exact_size(Vec2::new(width, ROW_H), egui::Sense::hover());
+    if !ui.is_rect_visible(r) {
+        return;
+    }
+    let painter = ui.painter().with_clip_rect(r);
+    let info = &row.info;
+    let dim = base_color.linear_multiply(0.6);
+    let rate_color = |rate: f64| if rate >= 1.0 { base_color } else { dim };
+
+    let (text, color) = match column {
+        // Indented and marked with its expansion state in tree mode
+        Column::Name if tree => {
+            let x = r.left() + 4.0 + row.depth as f32 * TREE_INDENT;
+            let marker = match (row.children, row.expanded) {
+                (0, _) => "",
+                (_, true) => "-",
+                (_, false) => "+",
+            };
+            painter.text(egui::pos2(x, r.center().y), egui::Align2::LEFT_CENTER, marker, font.clone(), theme::LAVENDER);
+            painter.text(egui::pos2(x + TREE_INDENT, r.center().y), egui::Align2::LEFT_CENTER, &info.name, font.clone(), base_color);
+            return;
+        }
+        Column::Name => (info.name.clone(), base_color),
+        Column::Pid => (info.pid.to_string(), base_color),
+        Column::Ppid => (info.parent.map_or("-".to_string(), |p| p.to_string()), base_color),
+        Column::User => (info.user.clone(), base_color),
+        Column::State => (info.state.clone(), base_color),
+        Column::Threads => (info.threads.to_string(), base_color),
+        Column::Nice => (info.nice.to_string(), base_color),
+        Column::Priority => (info.priority.to_string(), base_color),
+        Column::StartTime => (format_start(info.start_time), base_color),
+        Column::Elapsed => (format_uptime(info.run_time), base_color),
+        Column::Memory => (format_bytes(info.memory), base_color),
+        Column::Virtual => (format_bytes(info.virtual_memory), base_color),
+        Column::Shared => (format_bytes(info.shared_memory), base_color),
+        Column::Cpu => (format!("{:.1}%", info.cpu_usage), cpu_color(info.cpu_usage)),
+        // Dimmed when the process has no DRM clients
+        Column::Gpu => {
+            let color = if info.gpu_memory > 0 { cpu_color(info.gpu_usage) } else { dim };
+            (format!("{:.1}%", info.gpu_usage), color)
+        }
+        Column::DiskRead => (format_rate(info.disk_read), rate_color(info.disk_read)),
+        Column::DiskWrite => (format_rate(info.disk_write), rate_color(info.disk_write)),
+        Column::NetRx => (format_rate(info.net_rx), rate_color(info.net_rx)),
+        Column::NetTx => (format_rate(info.net_tx), rate_color(info.net_tx)),
+        Column::Command if info.cmd.is_empty() => (format!("[{}]", info.name), dim),
+        Column::Command => (info.cmd.clone(), base_color),
+        Column::Cgroup => (info.cgroup.clone(), base_color),
+    };
+
+    let (pos, align) = if column_spec(column).2 {
+        (r.right_center() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_CENTER)
+    } else {
+        (r.left_center() + egui::vec2(4.0, 0.0), egui::Align2::LEFT_CENTER)
+    };
+    painter.text(pos, align, text, font.clone(), color);
+}
+
+/// Clock time for processes started today, otherwise the date
+fn format_start(start_time: u64) -> String {
+    let Some(start) = chrono::DateTime::from_timestamp(start_time as i64, 0) else {
+        return "?".to_string();
+    };
+    let start = start.with_timezone(&chrono::Local);
+    if start.date_naive() == chrono::Local::now().date_naive() {
+        start.format("%H:%M:%S").to_string()
+    } else {
+        start.format("%b %d").to_string().to_uppercase()
     }
 }
 
+/// Hover text for a tree node: its own usage beside the subtree totals shown in the row
+fn subtree_summary(row: &ProcessRow) -> String {
+    format!(
+        "OWN {} / {:.1}%\nSUBTREE OF {} PROCESSES {} / {:.1}%",
+        format_bytes(row.own_memory),
+        row.own_cpu,
+        row.descendants + 1,
+        format_bytes(row.info.memory),
+        row.info.cpu_usage
+    )
+}
+
 /// Render child processes with LCARS bracket accent
-fn show_children(ui: &mut Ui, children: &[crate::system::process::ProcessInfo]) {
+fn show_children(ui: &mut Ui, children: &[ProcessInfo]) {
     let child_font = egui::FontId::monospace(22.0);
     let row_h = theme::CHILD_ROW_H;
     let dim = theme::CHILD_DIM;
     let accent_color = theme::LAVENDER;
 
-    let mid = (children.len() + 1) / 2;
+    let mid = children.len().div_ceil(2);
     let left = &children[..mid];
     let right = &children[mid..];
     let col_rows = left.len().max(right.len());
@@ -296,17 +587,19 @@ fn show_children(ui: &mut Ui, children: &[crate::system::process::ProcessInfo])
             let color = theme::PEACH.linear_multiply(dim);
 
             // Name
-            let name = if child.name.len() > 12 { format!("{:.12}", child.name) } else { child.name.clone() };
-            painter.text(egui::pos2(col_x + 4.0, cy), egui::Align2::LEFT_CENTER, name, child_font.clone(), color);
+            let name_rect = egui::Rect::from_min_size(egui::pos2(col_x, y), Vec2::new(CHILD_NAME_W - 4.0, row_h));
+            painter.with_clip_rect(name_rect).text(egui::pos2(col_x + 4.0, cy), egui::Align2::LEFT_CENTER,
+                &child.name, child_font.clone(), color);
 
             // PID
             painter.text(egui::pos2(col_x + CHILD_NAME_W + 4.0, cy), egui::Align2::LEFT_CENTER,
                 format!("{}", child.pid), child_font.clone(), color);
 
             // User
-            let user = if child.user.len() > 8 { format!("{:.8}", child.user) } else { child.user.clone() };
-            painter.text(egui::pos2(col_x + CHILD_NAME_W + CHILD_PID_W + 4.0, cy),
-                egui::Align2::LEFT_CENTER, user, child_font.clone(), color);
+            let user_x = col_x + CHILD_NAME_W + CHILD_PID_W;
+            let user_rect = egui::Rect::from_min_size(egui::pos2(user_x, y), Vec2::new(CHILD_USER_W - 4.0, row_h));
+            painter.with_clip_rect(user_rect).text(egui::pos2(user_x + 4.0, cy),
+                egui::Align2::LEFT_CENTER, &child.user, child_font.clone(), color);
 
             // Memory
             painter.text(
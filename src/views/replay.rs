@@ -0,0 +1,84 @@
+use egui::{Rect, Rounding, Sense, Ui, Vec2};
+
+use crate::system::recording::{Player, SPEEDS};
+use crate::theme;
+use crate::widgets::button::LcarsButton;
+
+const BUTTON_W: f32 = 52.0;
+const SPEED_W: f32 = 60.0;
+const LABEL_W: f32 = 240.0;
+
+/// Transport controls drawn over the footer bar during `--replay`: step and
+/// jump buttons, play/pause, a seek track and the playback speed.
+pub fn show_controls(ui: &mut Ui, rect: Rect, player: &mut Player) {
+    let mut ui = ui.new_child(egui::UiBuilder::new().max_rect(rect.shrink2(Vec2::new(8.0, 6.0))));
+    let button = Vec2::new(BUTTON_W, ui.available_height());
+    let last = player.len() - 1;
+
+    ui.horizontal_centered(|ui| {
+        ui.spacing_mut().item_spacing.x = theme::BAR_SPACING;
+        if LcarsButton::new("|<", theme::ORANGE).size(button).font_size(24.0).show(ui).clicked() {
+            player.seek(0);
+        }
+        if LcarsButton::new("<", theme::PEACH).size(button).font_size(24.0).show(ui).clicked() {
+            player.seek(player.position().saturating_sub(1));
+        }
+        let play = if player.is_paused() { "PLAY" } else { "PAUSE" };
+        if LcarsButton::new(play, theme::ORANGE).size(Vec2::new(BUTTON_W * 1.5, button.y)).font_size(24.0).show(ui).clicked() {
+            player.toggle_pause();
+        }
+        if LcarsButton::new(">", theme::PEACH).size(button).font_size(24.0).show(ui).clicked() {
+            player.seek(player.position() + 1);
+        }
+        if LcarsButton::new(">|", theme::ORANGE).size(button).font_size(24.0).show(ui).clicked() {
+            player.seek(last);
+        }
+
+        // Seek track fills whatever the fixed-width controls leave over
+        let track_w = (ui.available_width() - LABEL_W - SPEED_W - BUTTON_W * 2.0 - theme::BAR_SPACING * 5.0).max(40.0);
+        let (track, response) = ui.allocate_exact_size(Vec2::new(track_w, button.y * 0.5), Sense::click_and_drag());
+        let fraction = if last == 0 { 1.0 } else { player.position() as f32 / last as f32 };
+        let rounding = Rounding::same(track.height() / 2.0);
+        ui.painter().rect_filled(track, rounding, theme::BLACK);
+        let filled = Rect::from_min_size(track.min, Vec2::new(track.width() * fraction, track.height()));
+        ui.painter().rect_filled(filled, rounding, theme::ORANGE);
+        if let Some(pos) = response.interact_pointer_pos() {
+            let at = ((pos.x - track.left()) / track.width()).clamp(0.0, 1.0);
+            player.seek((at * last as f32).round() as usize);
+        }
+
+        let status = match &player.error {
+            Some(e) => e.to_uppercase(),
+            None => format!(
+                "{}   {}/{}",
+                player.time().format("%Y-%m-%d %H:%M:%S"),
+                player.position() + 1,
+                player.len()
+            ),
+        };
+        let (label, _) = ui.allocate_exact_size(Vec2::new(LABEL_W, button.y), Sense::hover());
+        ui.painter().text(
+            label.center() + egui::vec2(0.0, 3.0),
+            egui::Align2::CENTER_CENTER,
+            status,
+            egui::FontId::monospace(26.0),
+            theme::BLACK,
+        );
+
+        let index = SPEEDS.iter().position(|s| *s == player.speed()).unwrap_or(2);
+        if LcarsButton::new("-", theme::PERIWINKLE).size(button).font_size(24.0).show(ui).clicked() {
+            player.set_speed(SPEEDS[index.saturating_sub(1)]);
+        }
+        let (speed, _) = ui.allocate_exact_size(Vec2::new(SPEED_W, button.y), Sense::hover());
+        ui.painter().text(
+            speed.center() + egui::vec2(0.0, 3.0),
+            egui::Align2::CENTER_CENTER,
+            format!("\u{00d7}{}", player.speed()),
+            egui::FontId::monospace(26.0),
+            theme::BLACK,
+        );
+        if LcarsButton::new("+", theme::PERIWINKLE).size(button).font_size(24.0).show(ui).clicked() {
+            player.set_speed(SPEEDS[(index + 1).min(SPEEDS.len() - 1)]);
+        }
+    });
+}
@@ -0,0 +1,99 @@
+use egui::Ui;
+
+use crate::system::sensors::{SensorChip, SensorKind, SensorReading};
+use crate::theme;
+use crate::widgets::gauge::LcarsGauge;
+use crate::widgets::panel::LcarsPanel;
+
+const LABEL_W: f32 = 200.0;
+const LIMITS_W: f32 = 420.0;
+
+/// Green until 80% of the way to crit, yellow to 95%, then red.
+fn level_color(level: f32) -> egui::Color32 {
+    if level >= 0.95 {
+        theme::RED
+    } else if level >= 0.80 {
+        theme::YELLOW
+    } else {
+        theme::GREEN
+    }
+}
+
+/// Readings without a limit to judge against.
+fn kind_color(kind: SensorKind) -> egui::Color32 {
+    match kind {
+        SensorKind::Temperature => theme::PEACH,
+        SensorKind::Fan => theme::BLUE,
+        SensorKind::Voltage => theme::LAVENDER,
+        SensorKind::Current => theme::PERIWINKLE,
+    }
+}
+
+fn format_value(kind: SensorKind, value: f64) -> String {
+    match kind {
+        SensorKind::Temperature => format!("{:.1} {}", value, kind.unit()),
+        SensorKind::Fan => format!("{:.0} {}", value, kind.unit()),
+        SensorKind::Voltage | SensorKind::Current => format!("{:.2} {}", value, kind.unit()),
+    }
+}
+
+/// Every hwmon chip as a panel of its readings, each bar filled towards the
+/// sensor's crit (or max) limit.
+pub fn show(ui: &mut Ui, chips: &[SensorChip]) {
+    if chips.is_empty() {
+        ui.add_space(20.0);
+        ui.label(
+            egui::RichText::new("NO SENSORS DETECTED")
+                .color(theme::ORANGE)
+                .font(egui::FontId::monospace(40.0)),
+        );
+        return;
+    }
+
+    let full_width = ui.available_width() - 20.0;
+    for (i, chip) in chips.iter().enumerate() {
+        let title = if chip.device.is_empty() {
+            format!("{}  {}", chip.name, chip.hwmon)
+        } else {
+            format!("{}  {}  {}", chip.name, chip.device, chip.hwmon)
+        };
+        LcarsPanel::new(title, theme::color_for_index(i)).show(ui, |ui| {
+            if chip.readings.is_empty() {
+                ui.label(
+                    egui::RichText::new("NO READINGS")
+                        .color(theme::color_for_index(i))
+                        .font(egui::FontId::monospace(20.0)),
+                );
+            }
+            for reading in &chip.readings {
+                show_reading(ui, reading, full_width);
+            }
+        });
+    }
+}
+
+fn show_reading(ui: &mut Ui, r: &SensorReading, width: f32) {
+    let level = r.level();
+    let color = level.map_or(kind_color(r.kind), level_color);
+    let mut limits = format!("PEAK {}", format_value(r.kind, r.peak));
+    if let Some(max) = r.max {
+        limits.push_str(&format!("   MAX {}", format_value(r.kind, max)));
+    }
+    if let Some(crit) = r.crit {
+        limits.push_str(&format!("   CRIT {}", format_value(r.kind, crit)));
+    }
+
+    ui.horizontal(|ui| {
+        LcarsGauge::new(&r.label, level.unwrap_or(0.0), color)
+            .width(width - LIMITS_W)
+            .label_width(LABEL_W)
+            .value_text(format_value(r.kind, r.value))
+            .font_size(20.0)
+            .show(ui);
+        ui.label(
+            egui::RichText::new(limits)
+                .color(color.linear_multiply(0.7))
+                .font(egui::FontId::monospace(18.0)),
+        );
+    });
+}
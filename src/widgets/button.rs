@@ -2,20 +2,20 @@ use egui::{Color32, Response, Rounding, Sense, Ui, Vec2};
 
 use crate::theme;
 
-#[allow(dead_code)]
 pub struct LcarsButton {
     label: String,
     color: Color32,
     size: Vec2,
+    font_size: f32,
 }
 
-#[allow(dead_code)]
 impl LcarsButton {
     pub fn new(label: impl Into<String>, color: Color32) -> Self {
         Self {
             label: label.into(),
             color,
             size: Vec2::new(theme::SIDEBAR_WIDTH, theme::BUTTON_HEIGHT),
+            font_size: 15.0,
         }
     }
 
@@ -24,6 +24,11 @@ impl LcarsButton {
         self
     }
 
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
     pub fn show(&self, ui: &mut Ui) -> Response {
         let (rect, response) = ui.allocate_exact_size(self.size, Sense::click());
 
@@ -38,7 +43,7 @@ impl LcarsButton {
             ui.painter().rect_filled(rect, rounding, color);
 
             let text = self.label.to_uppercase();
-            let font = egui::FontId::monospace(15.0);
+            let font = egui::FontId::monospace(self.font_size);
             let text_color = theme::BLACK;
             ui.painter().text(
                 rect.center(),
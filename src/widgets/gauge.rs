@@ -8,6 +8,8 @@ pub struct LcarsGauge {
     color: Color32,
     width: f32,
     font_size: f32,
+    label_width: f32,
+    value_text: Option<String>, // replaces the percentage
 }
 
 impl LcarsGauge {
@@ -18,6 +20,8 @@ impl LcarsGauge {
             color,
             width: 300.0,
             font_size: 15.0,
+            label_width: 80.0,
+            value_text: None,
         }
     }
 
@@ -31,10 +35,20 @@ impl LcarsGauge {
         self
     }
 
+    pub fn label_width(mut self, w: f32) -> Self {
+        self.label_width = w;
+        self
+    }
+
+    pub fn value_text(mut self, text: impl Into<String>) -> Self {
+        self.value_text = Some(text.into());
+        self
+    }
+
     pub fn show(&self, ui: &mut Ui) {
         let height = theme::GAUGE_HEIGHT;
-        let label_width = 80.0;
-        let pct_width = 50.0;
+        let label_width = self.label_width;
+        let pct_width = if self.value_text.is_some() { 130.0 } else { 50.0 };
         let bar_width = self.width - label_width - pct_width - 8.0;
 
         ui.horizontal(|ui| {
@@ -81,7 +95,7 @@ impl LcarsGauge {
                 ui.painter().text(
                     pct_rect.left_center() + egui::vec2(4.0, 0.0),
                     egui::Align2::LEFT_CENTER,
-                    format!("{:5.1}%", self.value * 100.0),
+                    self.value_text.clone().unwrap_or_else(|| format!("{:5.1}%", self.value * 100.0)),
                     egui::FontId::monospace(self.font_size),
                     self.color,
                 );
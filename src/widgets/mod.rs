@@ -2,3 +2,4 @@ pub mod button;
 pub mod elbow;
 pub mod gauge;
 pub mod panel;
+pub mod sparkline;
@@ -0,0 +1,147 @@
+use egui::{Color32, Pos2, Rect, Rounding, Shape, Stroke, Ui, Vec2};
+
+use crate::system::history::History;
+use crate::theme;
+
+pub struct LcarsSparkline {
+    label: String,
+    values: Vec<f32>,
+    slots: usize,
+    max: f32,
+    value_text: Option<String>,
+    color: Color32,
+    width: f32,
+    height: f32,
+    font_size: f32,
+}
+
+impl LcarsSparkline {
+    /// Area chart of `history`, scaled so that `max` fills the full height.
+    pub fn new(label: impl Into<String>, history: &History, max: f32, color: Color32) -> Self {
+        Self {
+            label: label.into(),
+            values: history.values().collect(),
+            slots: history.capacity(),
+            max,
+            value_text: None,
+            color,
+            width: 300.0,
+            height: theme::SPARKLINE_HEIGHT,
+            font_size: 15.0,
+        }
+    }
+
+    pub fn width(mut self, w: f32) -> Self {
+        self.width = w;
+        self
+    }
+
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Text shown right of the chart; defaults to the latest sample as a percentage of `max`.
+    pub fn value_text(mut self, text: impl Into<String>) -> Self {
+        self.value_text = Some(text.into());
+        self
+    }
+
+    pub fn show(&self, ui: &mut Ui) {
+        let height = self.height;
+        let label_width = 80.0;
+        let value_width = 110.0;
+        let chart_width = self.width - label_width - value_width - 8.0;
+
+        ui.horizontal(|ui| {
+            // Label
+            let (label_rect, _) = ui.allocate_exact_size(Vec2::new(label_width, height), egui::Sense::hover());
+            if ui.is_rect_visible(label_rect) {
+                ui.painter().text(
+                    label_rect.right_center() - egui::vec2(4.0, 0.0),
+                    egui::Align2::RIGHT_CENTER,
+                    self.label.to_uppercase(),
+                    egui::FontId::monospace(self.font_size),
+                    self.color,
+                );
+            }
+
+            // Chart
+            let (chart_rect, _) = ui.allocate_exact_size(Vec2::new(chart_width, height), egui::Sense::hover());
+            if ui.is_rect_visible(chart_rect) {
+                self.draw_chart(ui, chart_rect);
+            }
+
+            // Current value
+            let (value_rect, _) = ui.allocate_exact_size(Vec2::new(value_width, height), egui::Sense::hover());
+            if ui.is_rect_visible(value_rect) {
+                let text = self.value_text.clone().unwrap_or_else(|| {
+                    let last = self.values.last().copied().unwrap_or(0.0);
+                    format!("{:5.1}%", self.fraction(last) * 100.0)
+                });
+                ui.painter().text(
+                    value_rect.left_center() + egui::vec2(4.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    text,
+                    egui::FontId::monospace(self.font_size),
+                    self.color,
+                );
+            }
+        });
+    }
+
+    fn fraction(&self, value: f32) -> f32 {
+        if self.max > 0.0 {
+            (value / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn draw_chart(&self, ui: &Ui, rect: Rect) {
+        let painter = ui.painter();
+        painter.rect_filled(rect, Rounding::same(2.0), theme::DARK_BG);
+
+        // Horizontal grid at quarters, LCARS-style faint rules
+        for q in 1..4 {
+            let y = rect.bottom() - rect.height() * q as f32 / 4.0;
+            painter.line_segment(
+                [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+                Stroke::new(1.0, self.color.linear_multiply(0.12)),
+            );
+        }
+
+        if self.values.len() < 2 {
+            return;
+        }
+
+        // Right-align so the newest sample is always at the right edge
+        let step = rect.width() / (self.slots.max(2) - 1) as f32;
+        let offset = self.slots.saturating_sub(self.values.len()) as f32 * step;
+        let points: Vec<Pos2> = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                Pos2::new(
+                    rect.left() + offset + i as f32 * step,
+                    rect.bottom() - self.fraction(*v) * rect.height(),
+                )
+            })
+            .collect();
+
+        // Area fill as convex quads down to the baseline
+        let fill = self.color.linear_multiply(0.35);
+        for pair in points.windows(2) {
+            let quad = vec![
+                pair[0],
+                pair[1],
+                Pos2::new(pair[1].x, rect.bottom()),
+                Pos2::new(pair[0].x, rect.bottom()),
+            ];
+            painter.add(Shape::convex_polygon(quad, fill, Stroke::NONE));
+        }
+
+        painter.add(Shape::line(points, Stroke::new(2.0, self.color)));
+    }
+}